@@ -0,0 +1,25 @@
+CREATE TABLE adguard_schedules (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    client_name TEXT,
+    action TEXT NOT NULL,
+    blocked_services TEXT,
+    days TEXT NOT NULL,
+    start_time TEXT NOT NULL,
+    end_time TEXT NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    active BOOLEAN NOT NULL DEFAULT FALSE,
+    restore_state TEXT,
+    created_at DATETIME NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE TABLE adguard_schedule_runs (
+    id INTEGER PRIMARY KEY,
+    schedule_id INTEGER NOT NULL REFERENCES adguard_schedules(id) ON DELETE CASCADE,
+    phase TEXT NOT NULL,
+    ran_at DATETIME NOT NULL DEFAULT (datetime('now')),
+    success BOOLEAN NOT NULL,
+    error TEXT
+);
+
+CREATE INDEX adguard_schedule_runs_schedule_id_idx ON adguard_schedule_runs(schedule_id, ran_at);
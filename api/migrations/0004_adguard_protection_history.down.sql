@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS adguard_protection_history_created_at_idx;
+DROP TABLE IF EXISTS adguard_protection_history;
@@ -0,0 +1,15 @@
+CREATE TABLE adguard_protection_history (
+    id INTEGER PRIMARY KEY,
+    created_at DATETIME NOT NULL DEFAULT (datetime('now')),
+    action TEXT NOT NULL,
+    duration_seconds INTEGER,
+    actor TEXT NOT NULL DEFAULT 'api',
+    client_ip TEXT,
+    forwarded_for TEXT,
+    success BOOLEAN NOT NULL,
+    error TEXT,
+    protection_enabled BOOLEAN,
+    protection_disabled_until TEXT
+);
+
+CREATE INDEX adguard_protection_history_created_at_idx ON adguard_protection_history(created_at);
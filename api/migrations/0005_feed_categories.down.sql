@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS feeds_category_idx;
+ALTER TABLE feeds DROP COLUMN category;
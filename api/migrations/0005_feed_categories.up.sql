@@ -0,0 +1,3 @@
+ALTER TABLE feeds ADD COLUMN category TEXT;
+
+CREATE INDEX feeds_category_idx ON feeds(category);
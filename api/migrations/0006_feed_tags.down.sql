@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS feed_tags_tag_idx;
+DROP TABLE IF EXISTS feed_tags;
@@ -0,0 +1,4 @@
+DROP TRIGGER IF EXISTS feed_items_fts_update;
+DROP TRIGGER IF EXISTS feed_items_fts_delete;
+DROP TRIGGER IF EXISTS feed_items_fts_insert;
+DROP TABLE IF EXISTS feed_items_fts;
@@ -0,0 +1,26 @@
+CREATE VIRTUAL TABLE feed_items_fts USING fts5(
+    title,
+    description,
+    content = 'feed_items',
+    content_rowid = 'id',
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+
+INSERT INTO feed_items_fts (feed_items_fts) VALUES ('rebuild');
+
+CREATE TRIGGER feed_items_fts_insert AFTER INSERT ON feed_items BEGIN
+    INSERT INTO feed_items_fts (rowid, title, description)
+    VALUES (new.id, new.title, new.description);
+END;
+
+CREATE TRIGGER feed_items_fts_delete AFTER DELETE ON feed_items BEGIN
+    INSERT INTO feed_items_fts (feed_items_fts, rowid, title, description)
+    VALUES ('delete', old.id, old.title, old.description);
+END;
+
+CREATE TRIGGER feed_items_fts_update AFTER UPDATE OF title, description ON feed_items BEGIN
+    INSERT INTO feed_items_fts (feed_items_fts, rowid, title, description)
+    VALUES ('delete', old.id, old.title, old.description);
+    INSERT INTO feed_items_fts (rowid, title, description)
+    VALUES (new.id, new.title, new.description);
+END;
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS feed_items_starred_at_idx;
+ALTER TABLE feed_items DROP COLUMN starred_at;
@@ -0,0 +1,3 @@
+ALTER TABLE feed_items ADD COLUMN starred_at DATETIME;
+
+CREATE INDEX feed_items_starred_at_idx ON feed_items(starred_at);
@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS feeds_next_fetch_at_idx;
+ALTER TABLE feeds DROP COLUMN error_count;
+ALTER TABLE feeds DROP COLUMN next_fetch_at;
@@ -0,0 +1,4 @@
+ALTER TABLE feeds ADD COLUMN next_fetch_at DATETIME;
+ALTER TABLE feeds ADD COLUMN error_count INTEGER NOT NULL DEFAULT 0;
+
+CREATE INDEX feeds_next_fetch_at_idx ON feeds(next_fetch_at);
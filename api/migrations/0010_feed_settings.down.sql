@@ -0,0 +1,2 @@
+ALTER TABLE feeds DROP COLUMN refresh_interval_minutes;
+ALTER TABLE feeds DROP COLUMN custom_title;
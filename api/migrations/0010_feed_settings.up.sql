@@ -0,0 +1,2 @@
+ALTER TABLE feeds ADD COLUMN custom_title TEXT;
+ALTER TABLE feeds ADD COLUMN refresh_interval_minutes INTEGER;
@@ -1,12 +1,21 @@
+use std::convert::Infallible;
+use std::net::SocketAddr;
 use std::sync::Arc;
 
-use axum::{extract::Request, http::header, middleware::Next, response::Response};
+use axum::{
+    extract::{ConnectInfo, FromRequestParts, Request},
+    http::{header, request::Parts},
+    middleware::Next,
+    response::Response,
+};
 use subtle::ConstantTimeEq;
 use tracing::warn;
 
 use crate::error::AppError;
 
 const BEARER_PREFIX: &str = "Bearer ";
+/// Longest `X-Forwarded-For` value kept for audit records.
+const MAX_FORWARDED_FOR_LEN: usize = 256;
 
 #[derive(Clone)]
 pub struct ApiKey(Arc<String>);
@@ -30,6 +39,39 @@ impl ApiKey {
     }
 }
 
+/// Where a request came from, for audit records. Fields are `None` when unknown,
+/// e.g. when the router is served without connect info.
+#[derive(Debug, Clone, Default)]
+pub struct RequestIdentity {
+    /// Address of the connected peer, which is the proxy when behind one.
+    pub client_ip: Option<String>,
+    /// The `X-Forwarded-For` header as sent. Any caller can set it, so it is kept
+    /// alongside the peer address rather than in place of it.
+    pub forwarded_for: Option<String>,
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for RequestIdentity {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let client_ip = parts
+            .extensions
+            .get::<ConnectInfo<SocketAddr>>()
+            .map(|ConnectInfo(addr)| addr.ip().to_string());
+        let forwarded_for = parts
+            .headers
+            .get("x-forwarded-for")
+            .and_then(|v| v.to_str().ok())
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(|v| v.chars().take(MAX_FORWARDED_FOR_LEN).collect());
+        Ok(Self {
+            client_ip,
+            forwarded_for,
+        })
+    }
+}
+
 pub async fn auth_middleware(
     req: Request,
     next: Next,
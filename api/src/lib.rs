@@ -6,6 +6,7 @@ use chrono::{DateTime, Utc};
 
 use crate::services::adguard::AdguardService;
 use crate::services::docker::DockerService;
+use crate::services::feed::FeedFetcher;
 use crate::services::ir::IrService;
 
 pub mod auth;
@@ -21,6 +22,7 @@ pub struct AppState {
     pub docker_service: Option<DockerService>,
     pub ir_service: Option<IrService>,
     pub docker_cache: Arc<Mutex<DockerCache>>,
+    pub feed_fetcher: FeedFetcher,
 }
 
 #[derive(Clone, Default)]
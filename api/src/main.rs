@@ -4,12 +4,13 @@ use std::str::FromStr;
 use std::time::Duration;
 use tokio::net::TcpListener;
 use tokio::signal;
+use tokio::time::MissedTickBehavior;
 use tower_http::trace::TraceLayer;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 use openhome_api::auth;
 use openhome_api::routes;
-use openhome_api::services::{adguard, docker, feed, ir};
+use openhome_api::services::{adguard, adguard_schedule, adguard_sync, docker, feed, ir};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -60,12 +61,40 @@ async fn main() -> anyhow::Result<()> {
     };
 
     let adguard_service = if !adguard_host.is_empty() {
-        Some(adguard::AdguardService::new(
-            &adguard_host,
-            &adguard_username,
-            &adguard_password,
-            adguard_insecure_tls,
-        )?)
+        let mut replicas = Vec::new();
+        for name in std::env::var("ADGUARD_REPLICAS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+        {
+            let prefix = format!("ADGUARD_{}", name.to_uppercase().replace('-', "_"));
+            let host = std::env::var(format!("{prefix}_HOST"))
+                .map_err(|_| anyhow::anyhow!("{prefix}_HOST must be set for replica '{name}'"))?;
+            let username =
+                std::env::var(format!("{prefix}_USERNAME")).unwrap_or(adguard_username.clone());
+            let password =
+                std::env::var(format!("{prefix}_PASSWORD")).unwrap_or(adguard_password.clone());
+            let insecure_tls = std::env::var(format!("{prefix}_INSECURE_TLS"))
+                .map(|value| value == "true")
+                .unwrap_or(adguard_insecure_tls);
+            replicas.push(
+                adguard::AdguardService::new(&host, &username, &password, insecure_tls)?
+                    .with_name(name),
+            );
+        }
+        if !replicas.is_empty() {
+            tracing::info!(count = replicas.len(), "AdGuard replicas configured");
+        }
+        Some(
+            adguard::AdguardService::new(
+                &adguard_host,
+                &adguard_username,
+                &adguard_password,
+                adguard_insecure_tls,
+            )?
+            .with_replicas(replicas),
+        )
     } else {
         tracing::warn!("ADGUARD_HOST not set, AdGuard integration disabled");
         None
@@ -86,6 +115,7 @@ async fn main() -> anyhow::Result<()> {
         docker_cache: std::sync::Arc::new(tokio::sync::Mutex::new(
             openhome_api::DockerCache::default(),
         )),
+        feed_fetcher: feed::FeedFetcher::new()?,
     };
 
     let api_key = auth::ApiKey::new(
@@ -99,6 +129,7 @@ async fn main() -> anyhow::Result<()> {
         .merge(routes::feeds::router())
         .merge(routes::timeline::router())
         .merge(routes::adguard::router())
+        .merge(routes::adguard_schedules::router())
         .merge(routes::docker::router())
         .merge(routes::ir::router())
         .with_state(state.clone())
@@ -109,26 +140,87 @@ async fn main() -> anyhow::Result<()> {
 
     let scheduler_state = state.clone();
     tokio::spawn(async move {
-        tracing::info!("Starting RSS feed scheduler (initial fetch + 24h interval)");
-        if let Err(e) = feed::refresh_all_feeds(&scheduler_state.db).await {
-            tracing::warn!(error = %e, "Initial RSS feed refresh failed");
-        }
+        tracing::info!("Starting RSS feed scheduler (60s tick, fetching due feeds)");
+        let mut interval = tokio::time::interval(Duration::from_secs(60));
+        // A refresh can take longer than a tick; skip the missed ticks rather than
+        // starting the next passes back to back.
+        interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
         loop {
-            tokio::time::sleep(Duration::from_secs(24 * 60 * 60)).await;
-            tracing::info!("Running scheduled RSS feed refresh");
-            if let Err(e) = feed::refresh_all_feeds(&scheduler_state.db).await {
-                tracing::warn!(error = %e, "Scheduled RSS feed refresh failed");
-                tokio::time::sleep(Duration::from_secs(60)).await;
+            interval.tick().await;
+            match feed::refresh_due_feeds(&scheduler_state.db, &scheduler_state.feed_fetcher).await
+            {
+                Ok(results) if !results.is_empty() => {
+                    let failed = results.iter().filter(|r| r.error.is_some()).count();
+                    tracing::info!(feeds = results.len(), failed, "Refreshed due RSS feeds");
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!(error = %e, "Scheduled RSS feed refresh failed"),
             }
         }
     });
 
+    if let Some(adguard_service) = state.adguard_service.clone() {
+        let schedule_db = state.db.clone();
+        let timezone: chrono_tz::Tz = match std::env::var("ADGUARD_SCHEDULE_TZ") {
+            Ok(name) if !name.trim().is_empty() => name.trim().parse().map_err(|_| {
+                anyhow::anyhow!("ADGUARD_SCHEDULE_TZ must be an IANA time zone name")
+            })?,
+            _ => chrono_tz::UTC,
+        };
+        tokio::spawn(async move {
+            tracing::info!(
+                "Starting AdGuard schedule runner (30s interval, {} time)",
+                timezone
+            );
+            let mut interval = tokio::time::interval(Duration::from_secs(30));
+            interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+            loop {
+                interval.tick().await;
+                let now = chrono::Utc::now().with_timezone(&timezone).naive_local();
+                if let Err(e) =
+                    adguard_schedule::run_due_schedules(&schedule_db, &adguard_service, now).await
+                {
+                    tracing::warn!(error = %e, "AdGuard schedule run failed");
+                }
+            }
+        });
+    }
+
+    if let Some(adguard_service) = state
+        .adguard_service
+        .clone()
+        .filter(|service| !service.replicas().is_empty())
+    {
+        let interval_minutes = std::env::var("ADGUARD_SYNC_INTERVAL_MINUTES")
+            .ok()
+            .and_then(|value| value.parse::<u64>().ok())
+            .filter(|minutes| *minutes > 0)
+            .unwrap_or(60);
+        tokio::spawn(async move {
+            tracing::info!(
+                "Starting AdGuard replica sync ({}m interval)",
+                interval_minutes
+            );
+            let mut interval = tokio::time::interval(Duration::from_secs(interval_minutes * 60));
+            interval.set_missed_tick_behavior(MissedTickBehavior::Skip);
+            loop {
+                interval.tick().await;
+                if let Err(e) = adguard_sync::sync_replicas(&adguard_service).await {
+                    tracing::warn!(error = %e, "AdGuard replica sync failed");
+                }
+            }
+        });
+    }
+
     let listener = TcpListener::bind("0.0.0.0:8000").await?;
     tracing::info!("Listening on {}", listener.local_addr()?);
 
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
-        .await?;
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown_signal())
+    .await?;
 
     Ok(())
 }
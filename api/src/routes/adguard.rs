@@ -1,31 +1,249 @@
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::time::Duration;
+
 use axum::{
     Json, Router,
-    extract::State,
-    routing::{get, post},
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::{get, patch, post, put},
 };
+use chrono::{DateTime, Utc};
 use serde::Deserialize;
+use url::Url;
 
+use crate::auth::RequestIdentity;
 use crate::error::{AppError, Result};
-use crate::services::adguard::AdguardStatusResponse;
+use crate::services::adguard::{
+    self, AdguardService, AdguardStatusResponse, BlockableServicesResponse, BlockedServices,
+    BlockedServicesSchedule, CheckHostResponse, ClientSettingsUpdate, ClientsResponse, DhcpConfig,
+    DhcpStatus, FilterList, FilterListsResponse, FilterRefreshResponse, FilteringPolicy,
+    InstancesResponse, PersistentClient, ProtectionState, QueryLogQuery, QueryLogResponse,
+    RewriteEntry, RewritesResponse, SafeSearchSettings, StaticLease, UpstreamConfig, UpstreamMode,
+    UpstreamTestResponse, UserRulesResponse,
+};
+use crate::services::adguard_history::{self, NewProtectionChange, ProtectionChange};
+use crate::services::adguard_sync::{self, SyncResponse};
 
-#[derive(Debug, Deserialize)]
+const MAX_RULE_LENGTH: usize = 1024;
+const MAX_FILTER_NAME_LENGTH: usize = 256;
+const QUERY_LOG_DEFAULT_LIMIT: u32 = 50;
+const QUERY_LOG_MAX_LIMIT: u32 = 500;
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+const MAX_UPSTREAM_LENGTH: usize = 1024;
+const MAX_UPSTREAMS: usize = 64;
+const MAX_PAUSE_SECONDS: u64 = 24 * 60 * 60;
+
+/// Pause length, given as exactly one of a relative duration or an absolute end time.
+#[derive(Debug, Default, Deserialize)]
 pub struct PauseRequest {
-    pub minutes: u64,
+    pub seconds: Option<u64>,
+    pub minutes: Option<u64>,
+    /// RFC 3339 timestamp at which protection comes back on.
+    pub until: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RuleRequest {
+    pub rule: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DomainRequest {
+    pub domain: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateFilterListRequest {
+    pub name: String,
+    pub url: String,
+    #[serde(default)]
+    pub whitelist: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateFilterListRequest {
+    pub name: Option<String>,
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CheckHostQuery {
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct QueryLogParams {
+    pub limit: Option<u32>,
+    pub older_than: Option<String>,
+    pub search: Option<String>,
+    #[serde(default)]
+    pub blocked: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RewriteRequest {
+    pub domain: String,
+    pub answer: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateRewriteRequest {
+    pub target: RewriteRequest,
+    pub update: RewriteRequest,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateBlockedServicesRequest {
+    pub ids: Vec<String>,
+    pub schedule: Option<BlockedServicesSchedule>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StaticLeaseRequest {
+    pub mac: String,
+    pub ip: String,
+    pub hostname: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateStaticLeaseRequest {
+    pub ip: String,
+    pub hostname: String,
+}
+
+/// Upstream settings to save or test; omitted fields keep their current values.
+#[derive(Debug, Deserialize)]
+pub struct UpstreamsRequest {
+    pub upstream_dns: Option<Vec<String>>,
+    pub bootstrap_dns: Option<Vec<String>>,
+    pub fallback_dns: Option<Vec<String>>,
+    pub upstream_mode: Option<UpstreamMode>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FeatureToggleRequest {
+    pub enabled: bool,
+}
+
+/// Safe search changes; omitted engines keep their current setting.
+#[derive(Debug, Deserialize)]
+pub struct SafeSearchRequest {
+    pub enabled: Option<bool>,
+    pub bing: Option<bool>,
+    pub duckduckgo: Option<bool>,
+    pub ecosia: Option<bool>,
+    pub google: Option<bool>,
+    pub pixabay: Option<bool>,
+    pub yandex: Option<bool>,
+    pub youtube: Option<bool>,
+}
+
+impl SafeSearchRequest {
+    fn apply(&self, settings: &mut SafeSearchSettings) {
+        for (update, current) in [
+            (self.enabled, &mut settings.enabled),
+            (self.bing, &mut settings.bing),
+            (self.duckduckgo, &mut settings.duckduckgo),
+            (self.ecosia, &mut settings.ecosia),
+            (self.google, &mut settings.google),
+            (self.pixabay, &mut settings.pixabay),
+            (self.yandex, &mut settings.yandex),
+            (self.youtube, &mut settings.youtube),
+        ] {
+            if let Some(value) = update {
+                *current = value;
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HistoryQuery {
+    pub limit: Option<i64>,
+    pub before: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RefreshFiltersQuery {
+    #[serde(default)]
+    pub whitelist: bool,
 }
 
 pub fn router() -> Router<crate::AppState> {
     Router::new()
         .route("/api/adguard/status", get(get_status))
+        .route("/api/adguard/instances", get(get_instances))
+        .route("/api/adguard/sync", post(sync_replicas))
         .route("/api/adguard/enable", post(enable_protection))
         .route("/api/adguard/disable", post(disable_protection))
         .route("/api/adguard/pause", post(pause_protection))
+        .route("/api/adguard/resume", post(resume_protection))
+        .route("/api/adguard/history", get(get_history))
+        .route(
+            "/api/adguard/rules",
+            get(get_rules).post(add_rule).delete(remove_rule),
+        )
+        .route("/api/adguard/rules/block", post(block_domain))
+        .route("/api/adguard/rules/allow", post(allow_domain))
+        .route(
+            "/api/adguard/filters",
+            get(get_filter_lists).post(add_filter_list),
+        )
+        .route("/api/adguard/filters/refresh", post(refresh_filter_lists))
+        .route(
+            "/api/adguard/filters/{id}",
+            patch(update_filter_list).delete(remove_filter_list),
+        )
+        .route("/api/adguard/check", get(check_host))
+        .route("/api/adguard/querylog", get(get_query_log))
+        .route(
+            "/api/adguard/rewrites",
+            get(get_rewrites)
+                .post(add_rewrite)
+                .put(update_rewrite)
+                .delete(delete_rewrite),
+        )
+        .route(
+            "/api/adguard/blocked-services",
+            get(get_blocked_services).put(update_blocked_services),
+        )
+        .route(
+            "/api/adguard/blocked-services/available",
+            get(get_available_services),
+        )
+        .route("/api/adguard/clients", get(get_clients))
+        .route("/api/adguard/clients/{name}", patch(update_client))
+        .route("/api/adguard/dhcp", get(get_dhcp_status))
+        .route("/api/adguard/dhcp/config", put(set_dhcp_config))
+        .route("/api/adguard/dhcp/static-leases", post(add_static_lease))
+        .route(
+            "/api/adguard/dhcp/static-leases/{mac}",
+            put(update_static_lease).delete(remove_static_lease),
+        )
+        .route(
+            "/api/adguard/upstreams",
+            get(get_upstreams).put(set_upstreams),
+        )
+        .route("/api/adguard/upstreams/test", post(test_upstreams))
+        .route("/api/adguard/cache/flush", post(flush_dns_cache))
+        .route("/api/adguard/policy", get(get_filtering_policy))
+        .route("/api/adguard/policy/safe-browsing", put(set_safe_browsing))
+        .route(
+            "/api/adguard/policy/parental-control",
+            put(set_parental_control),
+        )
+        .route("/api/adguard/policy/safe-search", put(set_safe_search))
 }
 
-async fn get_status(State(state): State<crate::AppState>) -> Result<Json<AdguardStatusResponse>> {
-    let service = state
+fn adguard_service(state: &crate::AppState) -> Result<&AdguardService> {
+    state
         .adguard_service
         .as_ref()
-        .ok_or_else(|| AppError::ServiceUnavailable("AdGuard is not configured".to_string()))?;
+        .ok_or_else(|| AppError::ServiceUnavailable("AdGuard is not configured".to_string()))
+}
+
+async fn get_status(State(state): State<crate::AppState>) -> Result<Json<AdguardStatusResponse>> {
+    let service = adguard_service(&state)?;
     let status = service
         .get_status()
         .await
@@ -33,59 +251,937 @@ async fn get_status(State(state): State<crate::AppState>) -> Result<Json<Adguard
     Ok(Json(status))
 }
 
+async fn get_instances(State(state): State<crate::AppState>) -> Result<Json<InstancesResponse>> {
+    let service = adguard_service(&state)?;
+    let instances = service.get_instance_statuses().await;
+    Ok(Json(InstancesResponse { instances }))
+}
+
+async fn sync_replicas(State(state): State<crate::AppState>) -> Result<Json<SyncResponse>> {
+    let service = adguard_service(&state)?;
+    let replicas = adguard_sync::sync_replicas(service).await.map_err(|e| {
+        AppError::Internal(anyhow::anyhow!("Failed to sync AdGuard replicas: {}", e))
+    })?;
+    Ok(Json(SyncResponse { replicas }))
+}
+
 async fn enable_protection(
     State(state): State<crate::AppState>,
+    identity: RequestIdentity,
 ) -> Result<Json<AdguardStatusResponse>> {
-    let service = state
-        .adguard_service
-        .as_ref()
-        .ok_or_else(|| AppError::ServiceUnavailable("AdGuard is not configured".to_string()))?;
-    let status = service
-        .set_protection(true, None)
-        .await
+    let service = adguard_service(&state)?;
+    let change = protection_change("enable", None, &identity);
+    let result = service.set_protection(true, None).await;
+    record_protection_change(&state, &change, &result).await;
+    let status = result
         .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to enable protection: {}", e)))?;
     Ok(Json(status))
 }
 
 async fn disable_protection(
     State(state): State<crate::AppState>,
+    identity: RequestIdentity,
 ) -> Result<Json<AdguardStatusResponse>> {
-    let service = state
-        .adguard_service
-        .as_ref()
-        .ok_or_else(|| AppError::ServiceUnavailable("AdGuard is not configured".to_string()))?;
-    let status = service
-        .set_protection(false, None)
-        .await
+    let service = adguard_service(&state)?;
+    let change = protection_change("disable", None, &identity);
+    let result = service.set_protection(false, None).await;
+    record_protection_change(&state, &change, &result).await;
+    let status = result
         .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to disable protection: {}", e)))?;
     Ok(Json(status))
 }
 
 async fn pause_protection(
     State(state): State<crate::AppState>,
+    identity: RequestIdentity,
     Json(payload): Json<PauseRequest>,
 ) -> Result<Json<AdguardStatusResponse>> {
-    const MAX_MINUTES: u64 = 1440;
-    if payload.minutes == 0 || payload.minutes > MAX_MINUTES {
+    let duration = pause_duration(&payload, Utc::now())?;
+    let service = adguard_service(&state)?;
+    let change = protection_change("pause", Some(duration.as_secs() as i64), &identity);
+    let result = service.set_protection(false, Some(duration)).await;
+    record_protection_change(&state, &change, &result).await;
+    let status = result
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to pause protection: {}", e)))?;
+    Ok(Json(status))
+}
+
+/// Ends a pause early. Protection that was disabled indefinitely is left alone so a
+/// deliberate disable is not undone by a stale "resume" button.
+async fn resume_protection(
+    State(state): State<crate::AppState>,
+    identity: RequestIdentity,
+) -> Result<Json<AdguardStatusResponse>> {
+    let service = adguard_service(&state)?;
+    let current = service
+        .get_status()
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get AdGuard status: {}", e)))?;
+    match current.protection {
+        ProtectionState::Enabled => return Ok(Json(current)),
+        ProtectionState::DisabledIndefinitely => {
+            return Err(AppError::Conflict(
+                "Protection is disabled, not paused; use enable instead".to_string(),
+            ));
+        }
+        ProtectionState::Paused { .. } => {}
+    }
+    let change = protection_change("resume", None, &identity);
+    let result = service.set_protection(true, None).await;
+    record_protection_change(&state, &change, &result).await;
+    let status = result
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to resume protection: {}", e)))?;
+    Ok(Json(status))
+}
+
+/// Resolves a pause request to a duration of whole seconds, from exactly one of
+/// `seconds`, `minutes` or an absolute `until` timestamp.
+fn pause_duration(payload: &PauseRequest, now: DateTime<Utc>) -> Result<Duration> {
+    let seconds = match (payload.seconds, payload.minutes, payload.until.as_deref()) {
+        (Some(seconds), None, None) => seconds,
+        (None, Some(minutes), None) => minutes
+            .checked_mul(60)
+            .ok_or_else(|| AppError::Validation("minutes too large".to_string()))?,
+        (None, None, Some(until)) => {
+            let until = DateTime::parse_from_rfc3339(until).map_err(|_| {
+                AppError::Validation("until must be an RFC 3339 timestamp".to_string())
+            })?;
+            let remaining = until.with_timezone(&Utc) - now;
+            if remaining <= chrono::Duration::zero() {
+                return Err(AppError::Validation(
+                    "until must be in the future".to_string(),
+                ));
+            }
+            // Round up so the pause never ends before the requested time.
+            (remaining.num_milliseconds() as u64).div_ceil(1000)
+        }
+        _ => {
+            return Err(AppError::Validation(
+                "Exactly one of seconds, minutes or until is required".to_string(),
+            ));
+        }
+    };
+    if seconds == 0 || seconds > MAX_PAUSE_SECONDS {
         return Err(AppError::Validation(format!(
-            "minutes must be between 1 and {MAX_MINUTES}"
+            "Pause must be between 1 second and {} hours",
+            MAX_PAUSE_SECONDS / 3600
         )));
     }
-    let service = state
-        .adguard_service
-        .as_ref()
-        .ok_or_else(|| AppError::ServiceUnavailable("AdGuard is not configured".to_string()))?;
-    let duration_ms = payload
-        .minutes
-        .checked_mul(60_000)
-        .ok_or_else(|| AppError::Validation("minutes too large".to_string()))?;
+    Ok(Duration::from_secs(seconds))
+}
+
+async fn get_history(
+    State(state): State<crate::AppState>,
+    Query(query): Query<HistoryQuery>,
+) -> Result<Json<Vec<ProtectionChange>>> {
+    let limit = query.limit.map(|limit| limit.clamp(1, 500)).unwrap_or(50);
+    let changes = adguard_history::list_protection_changes(&state.db, query.before, limit)
+        .await
+        .map_err(|e| {
+            AppError::Internal(anyhow::anyhow!("Failed to list protection history: {}", e))
+        })?;
+    Ok(Json(changes))
+}
+
+fn protection_change<'a>(
+    action: &'a str,
+    duration_seconds: Option<i64>,
+    identity: &'a RequestIdentity,
+) -> NewProtectionChange<'a> {
+    NewProtectionChange {
+        actor: adguard_history::API_ACTOR,
+        action,
+        duration_seconds,
+        client_ip: identity.client_ip.as_deref(),
+        forwarded_for: identity.forwarded_for.as_deref(),
+    }
+}
+
+/// Audits a protection change. The change has already been applied (or failed) by
+/// now, so a failure to write the record is logged rather than returned.
+async fn record_protection_change(
+    state: &crate::AppState,
+    change: &NewProtectionChange<'_>,
+    result: &anyhow::Result<AdguardStatusResponse>,
+) {
+    if let Err(e) = adguard_history::record_protection_change(&state.db, change, result).await {
+        tracing::warn!(action = change.action, error = %e, "Failed to record protection change");
+    }
+}
+
+async fn get_rules(State(state): State<crate::AppState>) -> Result<Json<UserRulesResponse>> {
+    let service = adguard_service(&state)?;
+    let rules = service
+        .get_user_rules()
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get filtering rules: {}", e)))?;
+    Ok(Json(UserRulesResponse { rules }))
+}
+
+async fn add_rule(
+    State(state): State<crate::AppState>,
+    Json(payload): Json<RuleRequest>,
+) -> Result<Json<UserRulesResponse>> {
+    let rule = validate_rule(&payload.rule)?;
+    let service = adguard_service(&state)?;
+    let rules = service
+        .add_user_rule(rule)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to add filtering rule: {}", e)))?;
+    Ok(Json(UserRulesResponse { rules }))
+}
+
+async fn remove_rule(
+    State(state): State<crate::AppState>,
+    Json(payload): Json<RuleRequest>,
+) -> Result<Json<UserRulesResponse>> {
+    let rule = validate_rule(&payload.rule)?;
+    let service = adguard_service(&state)?;
+    let rules = service
+        .remove_user_rule(rule)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to remove filtering rule: {}", e)))?
+        .ok_or_else(|| AppError::NotFound(format!("Rule '{}' not found", rule)))?;
+    Ok(Json(UserRulesResponse { rules }))
+}
+
+async fn block_domain(
+    State(state): State<crate::AppState>,
+    Json(payload): Json<DomainRequest>,
+) -> Result<Json<UserRulesResponse>> {
+    let domain = adguard::normalize_domain(&payload.domain).map_err(AppError::Validation)?;
+    let service = adguard_service(&state)?;
+    let rules = service
+        .block_domain(&domain)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to block domain: {}", e)))?;
+    Ok(Json(UserRulesResponse { rules }))
+}
+
+async fn allow_domain(
+    State(state): State<crate::AppState>,
+    Json(payload): Json<DomainRequest>,
+) -> Result<Json<UserRulesResponse>> {
+    let domain = adguard::normalize_domain(&payload.domain).map_err(AppError::Validation)?;
+    let service = adguard_service(&state)?;
+    let rules = service
+        .allow_domain(&domain)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to allow domain: {}", e)))?;
+    Ok(Json(UserRulesResponse { rules }))
+}
+
+async fn get_filter_lists(
+    State(state): State<crate::AppState>,
+) -> Result<Json<FilterListsResponse>> {
+    let service = adguard_service(&state)?;
+    let filters = service
+        .get_filter_lists()
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get filter lists: {}", e)))?;
+    Ok(Json(FilterListsResponse { filters }))
+}
+
+async fn add_filter_list(
+    State(state): State<crate::AppState>,
+    Json(payload): Json<CreateFilterListRequest>,
+) -> Result<(StatusCode, Json<FilterListsResponse>)> {
+    let name = validate_filter_name(&payload.name)?;
+    let url = validate_filter_url(&payload.url)?;
+    let service = adguard_service(&state)?;
+
+    let filters = service
+        .get_filter_lists()
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get filter lists: {}", e)))?;
+    if filters
+        .iter()
+        .any(|list| list.url == url.as_str() && list.whitelist == payload.whitelist)
+    {
+        return Err(AppError::Conflict(
+            "Filter list with this URL already exists".to_string(),
+        ));
+    }
+
+    service
+        .add_filter_list(name, url.as_str(), payload.whitelist)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to add filter list: {}", e)))?;
+    let filters = service
+        .get_filter_lists()
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get filter lists: {}", e)))?;
+    Ok((StatusCode::CREATED, Json(FilterListsResponse { filters })))
+}
+
+async fn update_filter_list(
+    State(state): State<crate::AppState>,
+    Path(id): Path<i64>,
+    Json(payload): Json<UpdateFilterListRequest>,
+) -> Result<Json<FilterList>> {
+    let name = payload
+        .name
+        .as_deref()
+        .map(validate_filter_name)
+        .transpose()?;
+    let service = adguard_service(&state)?;
+    let list = find_filter_list(service, id).await?;
+
+    service
+        .update_filter_list(
+            &list,
+            name.unwrap_or(&list.name),
+            payload.enabled.unwrap_or(list.enabled),
+        )
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to update filter list: {}", e)))?;
+
+    let updated = find_filter_list(service, id).await?;
+    Ok(Json(updated))
+}
+
+async fn remove_filter_list(
+    State(state): State<crate::AppState>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode> {
+    let service = adguard_service(&state)?;
+    let list = find_filter_list(service, id).await?;
+    service
+        .remove_filter_list(&list)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to remove filter list: {}", e)))?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn refresh_filter_lists(
+    State(state): State<crate::AppState>,
+    Query(query): Query<RefreshFiltersQuery>,
+) -> Result<Json<FilterRefreshResponse>> {
+    let service = adguard_service(&state)?;
+    let updated = service
+        .refresh_filter_lists(query.whitelist)
+        .await
+        .map_err(|e| {
+            AppError::Internal(anyhow::anyhow!("Failed to refresh filter lists: {}", e))
+        })?;
+    Ok(Json(FilterRefreshResponse { updated }))
+}
+
+async fn find_filter_list(service: &AdguardService, id: i64) -> Result<FilterList> {
+    service
+        .find_filter_list(id)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get filter lists: {}", e)))?
+        .ok_or_else(|| AppError::NotFound(format!("Filter list with id {} not found", id)))
+}
+
+fn validate_filter_name(raw: &str) -> Result<&str> {
+    let name = raw.trim();
+    if name.is_empty() {
+        return Err(AppError::Validation("name is required".to_string()));
+    }
+    if name.len() > MAX_FILTER_NAME_LENGTH {
+        return Err(AppError::Validation(format!(
+            "name must be at most {MAX_FILTER_NAME_LENGTH} characters"
+        )));
+    }
+    Ok(name)
+}
+
+fn validate_filter_url(raw: &str) -> Result<Url> {
+    let url =
+        Url::parse(raw.trim()).map_err(|e| AppError::Validation(format!("Invalid URL: {}", e)))?;
+    if url.scheme() != "https" && url.scheme() != "http" {
+        return Err(AppError::Validation(
+            "URL must use HTTP or HTTPS scheme".to_string(),
+        ));
+    }
+    if url.host_str().is_none() {
+        return Err(AppError::Validation("URL missing host".to_string()));
+    }
+    Ok(url)
+}
+
+async fn check_host(
+    State(state): State<crate::AppState>,
+    Query(query): Query<CheckHostQuery>,
+) -> Result<Json<CheckHostResponse>> {
+    let name = query
+        .name
+        .as_deref()
+        .filter(|name| !name.trim().is_empty())
+        .ok_or_else(|| AppError::Validation("name is required".to_string()))?;
+    let name = adguard::normalize_domain(name).map_err(AppError::Validation)?;
+    let service = adguard_service(&state)?;
+    let result = service
+        .check_host(&name)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to check host: {}", e)))?;
+    Ok(Json(result))
+}
+
+async fn get_query_log(
+    State(state): State<crate::AppState>,
+    Query(params): Query<QueryLogParams>,
+) -> Result<Json<QueryLogResponse>> {
+    let service = adguard_service(&state)?;
+    let query = QueryLogQuery {
+        limit: params
+            .limit
+            .unwrap_or(QUERY_LOG_DEFAULT_LIMIT)
+            .clamp(1, QUERY_LOG_MAX_LIMIT),
+        older_than: params.older_than.filter(|value| !value.is_empty()),
+        search: params
+            .search
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty()),
+        blocked_only: params.blocked,
+    };
+    let log = service
+        .get_query_log(&query)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get query log: {}", e)))?;
+    Ok(Json(log))
+}
+
+async fn get_rewrites(State(state): State<crate::AppState>) -> Result<Json<RewritesResponse>> {
+    let service = adguard_service(&state)?;
+    let rewrites = load_rewrites(service).await?;
+    Ok(Json(RewritesResponse { rewrites }))
+}
+
+async fn add_rewrite(
+    State(state): State<crate::AppState>,
+    Json(payload): Json<RewriteRequest>,
+) -> Result<(StatusCode, Json<RewriteEntry>)> {
+    let entry = validate_rewrite(&payload)?;
+    let service = adguard_service(&state)?;
+    if load_rewrites(service).await?.contains(&entry) {
+        return Err(AppError::Conflict("Rewrite already exists".to_string()));
+    }
+    service
+        .add_rewrite(&entry)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to add rewrite: {}", e)))?;
+    Ok((StatusCode::CREATED, Json(entry)))
+}
+
+async fn update_rewrite(
+    State(state): State<crate::AppState>,
+    Json(payload): Json<UpdateRewriteRequest>,
+) -> Result<Json<RewriteEntry>> {
+    let target = existing_rewrite(&payload.target);
+    let update = validate_rewrite(&payload.update)?;
+    let service = adguard_service(&state)?;
+    let rewrites = load_rewrites(service).await?;
+    if !rewrites.contains(&target) {
+        return Err(AppError::NotFound(format!(
+            "Rewrite {} -> {} not found",
+            target.domain, target.answer
+        )));
+    }
+    if target != update && rewrites.contains(&update) {
+        return Err(AppError::Conflict("Rewrite already exists".to_string()));
+    }
+    service
+        .update_rewrite(&target, &update)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to update rewrite: {}", e)))?;
+    Ok(Json(update))
+}
+
+async fn delete_rewrite(
+    State(state): State<crate::AppState>,
+    Json(payload): Json<RewriteRequest>,
+) -> Result<StatusCode> {
+    let entry = existing_rewrite(&payload);
+    let service = adguard_service(&state)?;
+    if !load_rewrites(service).await?.contains(&entry) {
+        return Err(AppError::NotFound(format!(
+            "Rewrite {} -> {} not found",
+            entry.domain, entry.answer
+        )));
+    }
+    service
+        .delete_rewrite(&entry)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to delete rewrite: {}", e)))?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn load_rewrites(service: &AdguardService) -> Result<Vec<RewriteEntry>> {
+    service
+        .get_rewrites()
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get rewrites: {}", e)))
+}
+
+/// Identifies a rewrite that already exists in AdGuard.
+///
+/// Existing entries are matched exactly as given rather than normalized, so entries
+/// created elsewhere with a mixed-case domain or a non-canonical IPv6 answer can
+/// still be updated and deleted.
+fn existing_rewrite(payload: &RewriteRequest) -> RewriteEntry {
+    RewriteEntry {
+        domain: payload.domain.clone(),
+        answer: payload.answer.clone(),
+    }
+}
+
+fn validate_rewrite(payload: &RewriteRequest) -> Result<RewriteEntry> {
+    Ok(RewriteEntry {
+        domain: adguard::normalize_rewrite_domain(&payload.domain).map_err(AppError::Validation)?,
+        answer: adguard::normalize_rewrite_answer(&payload.answer).map_err(AppError::Validation)?,
+    })
+}
+
+async fn get_available_services(
+    State(state): State<crate::AppState>,
+) -> Result<Json<BlockableServicesResponse>> {
+    let service = adguard_service(&state)?;
+    let services = service.get_available_services().await.map_err(|e| {
+        AppError::Internal(anyhow::anyhow!("Failed to get available services: {}", e))
+    })?;
+    Ok(Json(BlockableServicesResponse { services }))
+}
+
+async fn get_blocked_services(
+    State(state): State<crate::AppState>,
+) -> Result<Json<BlockedServices>> {
+    let service = adguard_service(&state)?;
+    let blocked = service.get_blocked_services().await.map_err(|e| {
+        AppError::Internal(anyhow::anyhow!("Failed to get blocked services: {}", e))
+    })?;
+    Ok(Json(blocked))
+}
+
+async fn update_blocked_services(
+    State(state): State<crate::AppState>,
+    Json(payload): Json<UpdateBlockedServicesRequest>,
+) -> Result<Json<BlockedServices>> {
+    if let Some(schedule) = &payload.schedule {
+        validate_schedule(schedule)?;
+    }
+    let service = adguard_service(&state)?;
+
+    let mut ids = payload.ids;
+    ids.sort();
+    ids.dedup();
+    check_service_ids(service, &ids).await?;
+
+    let schedule = match payload.schedule {
+        Some(schedule) => schedule,
+        None => {
+            service
+                .get_blocked_services()
+                .await
+                .map_err(|e| {
+                    AppError::Internal(anyhow::anyhow!("Failed to get blocked services: {}", e))
+                })?
+                .schedule
+        }
+    };
+    let blocked = BlockedServices { ids, schedule };
+    service.set_blocked_services(&blocked).await.map_err(|e| {
+        AppError::Internal(anyhow::anyhow!("Failed to update blocked services: {}", e))
+    })?;
+    Ok(Json(blocked))
+}
+
+/// Rejects service ids that are not in AdGuard's list of blockable services.
+async fn check_service_ids(service: &AdguardService, ids: &[String]) -> Result<()> {
+    if ids.is_empty() {
+        return Ok(());
+    }
+    let available = service.get_available_services().await.map_err(|e| {
+        AppError::Internal(anyhow::anyhow!("Failed to get available services: {}", e))
+    })?;
+    if let Some(unknown) = ids
+        .iter()
+        .find(|id| !available.iter().any(|service| &service.id == *id))
+    {
+        return Err(AppError::Validation(format!(
+            "Unknown service id '{}'",
+            unknown
+        )));
+    }
+    Ok(())
+}
+
+fn validate_schedule(schedule: &BlockedServicesSchedule) -> Result<()> {
+    for (day, range) in schedule.days() {
+        if let Some(range) = range
+            && (range.start_seconds >= range.end_seconds || range.end_seconds > SECONDS_PER_DAY)
+        {
+            return Err(AppError::Validation(format!(
+                "schedule for {day} must satisfy start_seconds < end_seconds <= {SECONDS_PER_DAY}"
+            )));
+        }
+    }
+    Ok(())
+}
+
+async fn get_clients(State(state): State<crate::AppState>) -> Result<Json<ClientsResponse>> {
+    let service = adguard_service(&state)?;
+    let clients = service
+        .get_clients()
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get clients: {}", e)))?;
+    Ok(Json(clients))
+}
+
+async fn update_client(
+    State(state): State<crate::AppState>,
+    Path(name): Path<String>,
+    Json(payload): Json<ClientSettingsUpdate>,
+) -> Result<Json<PersistentClient>> {
+    let service = adguard_service(&state)?;
+
+    if let Some(tags) = &payload.tags {
+        let clients = service
+            .get_clients()
+            .await
+            .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get clients: {}", e)))?;
+        if let Some(unknown) = tags
+            .iter()
+            .find(|tag| !clients.supported_tags.contains(tag))
+        {
+            return Err(AppError::Validation(format!(
+                "Unsupported tag '{}'",
+                unknown
+            )));
+        }
+    }
+    if let Some(ids) = &payload.blocked_services {
+        check_service_ids(service, ids).await?;
+    }
+
+    let client = service
+        .update_client(&name, &payload)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to update client: {}", e)))?
+        .ok_or_else(|| AppError::NotFound(format!("Client '{}' not found", name)))?;
+    Ok(Json(client))
+}
+
+async fn get_dhcp_status(State(state): State<crate::AppState>) -> Result<Json<DhcpStatus>> {
+    let service = adguard_service(&state)?;
     let status = service
-        .set_protection(false, Some(duration_ms))
+        .get_dhcp_status()
         .await
-        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to pause protection: {}", e)))?;
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get DHCP status: {}", e)))?;
     Ok(Json(status))
 }
 
+async fn set_dhcp_config(
+    State(state): State<crate::AppState>,
+    Json(payload): Json<DhcpConfig>,
+) -> Result<Json<DhcpConfig>> {
+    if let Some(v4) = &payload.v4 {
+        for (field, value) in [
+            ("gateway_ip", &v4.gateway_ip),
+            ("subnet_mask", &v4.subnet_mask),
+            ("range_start", &v4.range_start),
+            ("range_end", &v4.range_end),
+        ] {
+            validate_ipv4(field, value)?;
+        }
+    }
+    // AdGuard reports an empty range start when DHCPv6 is not configured.
+    if let Some(v6) = &payload.v6
+        && !v6.range_start.trim().is_empty()
+    {
+        validate_ipv6("v6.range_start", &v6.range_start)?;
+    }
+    let service = adguard_service(&state)?;
+    service
+        .set_dhcp_config(&payload)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to set DHCP config: {}", e)))?;
+    Ok(Json(payload))
+}
+
+async fn add_static_lease(
+    State(state): State<crate::AppState>,
+    Json(payload): Json<StaticLeaseRequest>,
+) -> Result<(StatusCode, Json<StaticLease>)> {
+    let lease = validate_static_lease(&payload.mac, &payload.ip, &payload.hostname)?;
+    let service = adguard_service(&state)?;
+    if find_static_lease(service, &lease.mac).await?.is_some() {
+        return Err(AppError::Conflict(format!(
+            "Static lease for {} already exists",
+            lease.mac
+        )));
+    }
+    service
+        .add_static_lease(&lease)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to add static lease: {}", e)))?;
+    Ok((StatusCode::CREATED, Json(lease)))
+}
+
+async fn update_static_lease(
+    State(state): State<crate::AppState>,
+    Path(mac): Path<String>,
+    Json(payload): Json<UpdateStaticLeaseRequest>,
+) -> Result<Json<StaticLease>> {
+    let lease = validate_static_lease(&mac, &payload.ip, &payload.hostname)?;
+    let service = adguard_service(&state)?;
+    if find_static_lease(service, &lease.mac).await?.is_none() {
+        return Err(AppError::NotFound(format!(
+            "Static lease for {} not found",
+            lease.mac
+        )));
+    }
+    service
+        .update_static_lease(&lease)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to update static lease: {}", e)))?;
+    Ok(Json(lease))
+}
+
+async fn remove_static_lease(
+    State(state): State<crate::AppState>,
+    Path(mac): Path<String>,
+) -> Result<StatusCode> {
+    let mac = adguard::normalize_mac(&mac).map_err(AppError::Validation)?;
+    let service = adguard_service(&state)?;
+    let lease = find_static_lease(service, &mac)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Static lease for {} not found", mac)))?;
+    service
+        .remove_static_lease(&lease)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to remove static lease: {}", e)))?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn find_static_lease(service: &AdguardService, mac: &str) -> Result<Option<StaticLease>> {
+    let status = service
+        .get_dhcp_status()
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get DHCP status: {}", e)))?;
+    Ok(status
+        .leases
+        .into_iter()
+        .find(|lease| lease.is_static && lease.mac.eq_ignore_ascii_case(mac))
+        .map(|lease| StaticLease {
+            mac: lease.mac,
+            ip: lease.ip,
+            hostname: lease.hostname,
+        }))
+}
+
+fn validate_static_lease(mac: &str, ip: &str, hostname: &str) -> Result<StaticLease> {
+    validate_ipv4("ip", ip)?;
+    Ok(StaticLease {
+        mac: adguard::normalize_mac(mac).map_err(AppError::Validation)?,
+        ip: ip.trim().to_string(),
+        hostname: adguard::normalize_hostname(hostname).map_err(AppError::Validation)?,
+    })
+}
+
+async fn get_upstreams(State(state): State<crate::AppState>) -> Result<Json<UpstreamConfig>> {
+    let service = adguard_service(&state)?;
+    let config = load_upstream_config(service).await?;
+    Ok(Json(config))
+}
+
+async fn set_upstreams(
+    State(state): State<crate::AppState>,
+    Json(payload): Json<UpstreamsRequest>,
+) -> Result<Json<UpstreamConfig>> {
+    let service = adguard_service(&state)?;
+    let config = merge_upstream_config(service, payload).await?;
+    service
+        .set_upstream_config(&config)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to set upstream DNS: {}", e)))?;
+    let config = load_upstream_config(service).await?;
+    Ok(Json(config))
+}
+
+async fn test_upstreams(
+    State(state): State<crate::AppState>,
+    Json(payload): Json<UpstreamsRequest>,
+) -> Result<Json<UpstreamTestResponse>> {
+    let service = adguard_service(&state)?;
+    let config = merge_upstream_config(service, payload).await?;
+    let results = service
+        .test_upstreams(&config)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to test upstream DNS: {}", e)))?;
+    Ok(Json(UpstreamTestResponse { results }))
+}
+
+async fn flush_dns_cache(State(state): State<crate::AppState>) -> Result<StatusCode> {
+    let service = adguard_service(&state)?;
+    service
+        .clear_dns_cache()
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to flush DNS cache: {}", e)))?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn get_filtering_policy(
+    State(state): State<crate::AppState>,
+) -> Result<Json<FilteringPolicy>> {
+    let service = adguard_service(&state)?;
+    let policy = load_filtering_policy(service).await?;
+    Ok(Json(policy))
+}
+
+async fn set_safe_browsing(
+    State(state): State<crate::AppState>,
+    Json(payload): Json<FeatureToggleRequest>,
+) -> Result<Json<FilteringPolicy>> {
+    let service = adguard_service(&state)?;
+    service
+        .set_safe_browsing(payload.enabled)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to set safe browsing: {}", e)))?;
+    let policy = load_filtering_policy(service).await?;
+    Ok(Json(policy))
+}
+
+async fn set_parental_control(
+    State(state): State<crate::AppState>,
+    Json(payload): Json<FeatureToggleRequest>,
+) -> Result<Json<FilteringPolicy>> {
+    let service = adguard_service(&state)?;
+    service
+        .set_parental_control(payload.enabled)
+        .await
+        .map_err(|e| {
+            AppError::Internal(anyhow::anyhow!("Failed to set parental control: {}", e))
+        })?;
+    let policy = load_filtering_policy(service).await?;
+    Ok(Json(policy))
+}
+
+async fn set_safe_search(
+    State(state): State<crate::AppState>,
+    Json(payload): Json<SafeSearchRequest>,
+) -> Result<Json<FilteringPolicy>> {
+    let service = adguard_service(&state)?;
+    let mut settings = service
+        .get_safe_search()
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get safe search: {}", e)))?;
+    payload.apply(&mut settings);
+    service
+        .set_safe_search(&settings)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to set safe search: {}", e)))?;
+    let policy = load_filtering_policy(service).await?;
+    Ok(Json(policy))
+}
+
+async fn load_filtering_policy(service: &AdguardService) -> Result<FilteringPolicy> {
+    service
+        .get_filtering_policy()
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get filtering policy: {}", e)))
+}
+
+async fn load_upstream_config(service: &AdguardService) -> Result<UpstreamConfig> {
+    service
+        .get_upstream_config()
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get upstream DNS: {}", e)))
+}
+
+/// Validates the provided fields and fills the rest from AdGuard's current settings.
+async fn merge_upstream_config(
+    service: &AdguardService,
+    payload: UpstreamsRequest,
+) -> Result<UpstreamConfig> {
+    let upstream_dns = payload
+        .upstream_dns
+        .map(|list| validate_upstreams("upstream_dns", list))
+        .transpose()?;
+    let bootstrap_dns = payload
+        .bootstrap_dns
+        .map(|list| validate_upstreams("bootstrap_dns", list))
+        .transpose()?;
+    let fallback_dns = payload
+        .fallback_dns
+        .map(|list| validate_upstreams("fallback_dns", list))
+        .transpose()?;
+    if upstream_dns.as_ref().is_some_and(Vec::is_empty) {
+        return Err(AppError::Validation(
+            "upstream_dns must contain at least one server".to_string(),
+        ));
+    }
+
+    let current = load_upstream_config(service).await?;
+    Ok(UpstreamConfig {
+        upstream_dns: upstream_dns.unwrap_or(current.upstream_dns),
+        bootstrap_dns: bootstrap_dns.unwrap_or(current.bootstrap_dns),
+        fallback_dns: fallback_dns.unwrap_or(current.fallback_dns),
+        upstream_mode: payload.upstream_mode.unwrap_or(current.upstream_mode),
+    })
+}
+
+/// Trims entries and drops blank lines. Entries are otherwise passed through as-is,
+/// since AdGuard has its own syntax for them (`[/domain/]upstream`, `# comments`).
+fn validate_upstreams(field: &str, list: Vec<String>) -> Result<Vec<String>> {
+    let entries: Vec<String> = list
+        .iter()
+        .map(|entry| entry.trim())
+        .filter(|entry| !entry.is_empty())
+        .map(str::to_string)
+        .collect();
+    if entries.len() > MAX_UPSTREAMS {
+        return Err(AppError::Validation(format!(
+            "{field} must not contain more than {MAX_UPSTREAMS} entries"
+        )));
+    }
+    if let Some(entry) = entries
+        .iter()
+        .find(|entry| entry.len() > MAX_UPSTREAM_LENGTH || entry.contains(['\n', '\r']))
+    {
+        return Err(AppError::Validation(format!(
+            "{field} contains an invalid entry: {entry}"
+        )));
+    }
+    Ok(entries)
+}
+
+fn validate_ipv4(field: &str, value: &str) -> Result<()> {
+    value
+        .trim()
+        .parse::<Ipv4Addr>()
+        .map(|_| ())
+        .map_err(|_| AppError::Validation(format!("{field} must be an IPv4 address")))
+}
+
+fn validate_ipv6(field: &str, value: &str) -> Result<()> {
+    value
+        .trim()
+        .parse::<Ipv6Addr>()
+        .map(|_| ())
+        .map_err(|_| AppError::Validation(format!("{field} must be an IPv6 address")))
+}
+
+fn validate_rule(raw: &str) -> Result<&str> {
+    let rule = raw.trim();
+    if rule.is_empty() {
+        return Err(AppError::Validation("rule is required".to_string()));
+    }
+    if rule.contains(['\n', '\r']) {
+        return Err(AppError::Validation(
+            "rule must be a single line".to_string(),
+        ));
+    }
+    if rule.len() > MAX_RULE_LENGTH {
+        return Err(AppError::Validation(format!(
+            "rule must be at most {MAX_RULE_LENGTH} characters"
+        )));
+    }
+    Ok(rule)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -104,6 +1200,7 @@ mod tests {
             docker_cache: std::sync::Arc::new(tokio::sync::Mutex::new(
                 crate::DockerCache::default(),
             )),
+            feed_fetcher: crate::services::feed::FeedFetcher::new().unwrap(),
         }
     }
 
@@ -121,7 +1218,7 @@ mod tests {
     async fn test_enable_protection_returns_503_when_service_not_configured() {
         let state = create_mock_state(None);
 
-        let result = enable_protection(State(state)).await;
+        let result = enable_protection(State(state), RequestIdentity::default()).await;
         assert!(result.is_err());
         let err = result.unwrap_err();
         assert_eq!(format!("{}", err), "Service unavailable");
@@ -131,7 +1228,7 @@ mod tests {
     async fn test_disable_protection_returns_503_when_service_not_configured() {
         let state = create_mock_state(None);
 
-        let result = disable_protection(State(state)).await;
+        let result = disable_protection(State(state), RequestIdentity::default()).await;
         assert!(result.is_err());
         let err = result.unwrap_err();
         assert_eq!(format!("{}", err), "Service unavailable");
@@ -141,8 +1238,12 @@ mod tests {
     async fn test_pause_protection_returns_503_when_service_not_configured() {
         let state = create_mock_state(None);
 
-        let payload = PauseRequest { minutes: 5 };
-        let result = pause_protection(State(state), Json(payload)).await;
+        let payload = PauseRequest {
+            minutes: Some(5),
+            ..Default::default()
+        };
+        let result =
+            pause_protection(State(state), RequestIdentity::default(), Json(payload)).await;
         assert!(result.is_err());
         let err = result.unwrap_err();
         assert_eq!(format!("{}", err), "Service unavailable");
@@ -216,7 +1317,9 @@ mod tests {
         let service = AdguardService::new(&mock_server.uri(), "test", "test", false).unwrap();
         let state = create_mock_state(Some(service));
 
-        let response = enable_protection(State(state)).await.unwrap();
+        let response = enable_protection(State(state), RequestIdentity::default())
+            .await
+            .unwrap();
         assert!(response.protection_enabled);
     }
 
@@ -255,7 +1358,9 @@ mod tests {
         let service = AdguardService::new(&mock_server.uri(), "test", "test", false).unwrap();
         let state = create_mock_state(Some(service));
 
-        let response = disable_protection(State(state)).await.unwrap();
+        let response = disable_protection(State(state), RequestIdentity::default())
+            .await
+            .unwrap();
         assert!(!response.protection_enabled);
     }
 
@@ -294,10 +1399,21 @@ mod tests {
         let service = AdguardService::new(&mock_server.uri(), "test", "test", false).unwrap();
         let state = create_mock_state(Some(service));
 
-        let payload = PauseRequest { minutes: 5 };
-        let response = pause_protection(State(state), Json(payload)).await.unwrap();
+        let payload = PauseRequest {
+            minutes: Some(5),
+            ..Default::default()
+        };
+        let response = pause_protection(State(state), RequestIdentity::default(), Json(payload))
+            .await
+            .unwrap();
         assert!(!response.protection_enabled);
-        assert_eq!(response.protection_disabled_duration, 300000);
+        assert!(matches!(
+            response.protection,
+            ProtectionState::Paused {
+                remaining_seconds: 300,
+                ..
+            }
+        ));
     }
 
     #[tokio::test]
@@ -331,7 +1447,7 @@ mod tests {
         // Verify all fields are populated
         assert!(response.running);
         assert_eq!(response.version, "v2.0.0");
-        assert_eq!(response.protection_disabled_duration, 0);
+        assert_eq!(response.protection, ProtectionState::Enabled);
         assert!(response.protection_disabled_until.is_none());
     }
 
@@ -345,7 +1461,7 @@ mod tests {
             "dns_addresses": [],
             "dns_port": 53,
             "http_port": 80,
-            "protection_disabled_duration": 600,
+            "protection_disabled_duration": 600000,
             "protection_enabled": false,
             "protection_disabled_until": "2024-01-20T15:00:00Z",
             "dhcp_available": false,
@@ -370,14 +1486,50 @@ mod tests {
         let service = AdguardService::new(&mock_server.uri(), "test", "test", false).unwrap();
         let state = create_mock_state(Some(service));
 
-        let response = disable_protection(State(state)).await.unwrap();
+        let response = disable_protection(State(state), RequestIdentity::default())
+            .await
+            .unwrap();
 
         // Verify disabled status fields
         assert!(!response.protection_enabled);
-        assert_eq!(response.protection_disabled_duration, 600);
+        assert!(matches!(
+            response.protection,
+            ProtectionState::Paused {
+                remaining_seconds: 600,
+                ..
+            }
+        ));
+        assert!(response.protection_disabled_until.is_some());
+    }
+
+    #[test]
+    fn test_pause_duration_accepts_each_unit() {
+        let now = DateTime::parse_from_rfc3339("2024-01-15T10:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let request = |seconds, minutes, until: Option<&str>| PauseRequest {
+            seconds,
+            minutes,
+            until: until.map(str::to_string),
+        };
+
+        assert_eq!(
+            pause_duration(&request(Some(90), None, None), now).unwrap(),
+            Duration::from_secs(90)
+        );
+        assert_eq!(
+            pause_duration(&request(None, Some(15), None), now).unwrap(),
+            Duration::from_secs(900)
+        );
         assert_eq!(
-            response.protection_disabled_until,
-            Some("2024-01-20T15:00:00Z".to_string())
+            pause_duration(&request(None, None, Some("2024-01-15T11:30:00+01:00")), now).unwrap(),
+            Duration::from_secs(1800)
         );
+        assert!(pause_duration(&request(None, None, None), now).is_err());
+        assert!(pause_duration(&request(Some(60), Some(1), None), now).is_err());
+        assert!(pause_duration(&request(Some(0), None, None), now).is_err());
+        assert!(pause_duration(&request(None, Some(1441), None), now).is_err());
+        assert!(pause_duration(&request(None, None, Some("2024-01-15T09:00:00Z")), now).is_err());
+        assert!(pause_duration(&request(None, None, Some("tomorrow")), now).is_err());
     }
 }
@@ -0,0 +1,151 @@
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::get,
+};
+use serde::Deserialize;
+
+use crate::error::{AppError, Result};
+use crate::services::adguard_schedule::{self, Schedule, ScheduleInput, ScheduleRun};
+
+#[derive(Debug, Deserialize)]
+pub struct RunsQuery {
+    pub limit: Option<i64>,
+}
+
+pub fn router() -> Router<crate::AppState> {
+    Router::new()
+        .route(
+            "/api/adguard/schedules",
+            get(list_schedules).post(create_schedule),
+        )
+        .route(
+            "/api/adguard/schedules/{id}",
+            get(get_schedule)
+                .put(update_schedule)
+                .delete(delete_schedule),
+        )
+        .route("/api/adguard/schedules/{id}/runs", get(list_runs))
+}
+
+async fn list_schedules(State(state): State<crate::AppState>) -> Result<Json<Vec<Schedule>>> {
+    let schedules = adguard_schedule::list_schedules(&state.db)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to list schedules: {}", e)))?;
+    Ok(Json(schedules))
+}
+
+async fn get_schedule(
+    State(state): State<crate::AppState>,
+    Path(id): Path<i64>,
+) -> Result<Json<Schedule>> {
+    let schedule = find_schedule(&state, id).await?;
+    Ok(Json(schedule))
+}
+
+async fn create_schedule(
+    State(state): State<crate::AppState>,
+    Json(payload): Json<ScheduleInput>,
+) -> Result<(StatusCode, Json<Schedule>)> {
+    let input = validate_input(&state, payload, None).await?;
+    let schedule = adguard_schedule::create_schedule(&state.db, &input)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to create schedule: {}", e)))?;
+    Ok((StatusCode::CREATED, Json(schedule)))
+}
+
+async fn update_schedule(
+    State(state): State<crate::AppState>,
+    Path(id): Path<i64>,
+    Json(payload): Json<ScheduleInput>,
+) -> Result<Json<Schedule>> {
+    let input = validate_input(&state, payload, Some(id)).await?;
+    let schedule = adguard_schedule::update_schedule(&state.db, id, &input)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to update schedule: {}", e)))?
+        .ok_or_else(|| AppError::NotFound(format!("Schedule with id {} not found", id)))?;
+    Ok(Json(schedule))
+}
+
+async fn delete_schedule(
+    State(state): State<crate::AppState>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode> {
+    let schedule = find_schedule(&state, id).await?;
+
+    // Undo an in-progress window so deleting a schedule never strands AdGuard in it.
+    if schedule.active
+        && let Some(service) = state.adguard_service.as_ref()
+    {
+        adguard_schedule::end_schedule(&state.db, service, &schedule)
+            .await
+            .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to end schedule: {}", e)))?;
+    }
+
+    adguard_schedule::delete_schedule(&state.db, id)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to delete schedule: {}", e)))?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn list_runs(
+    State(state): State<crate::AppState>,
+    Path(id): Path<i64>,
+    Query(query): Query<RunsQuery>,
+) -> Result<Json<Vec<ScheduleRun>>> {
+    find_schedule(&state, id).await?;
+    let limit = query.limit.map(|limit| limit.clamp(1, 200)).unwrap_or(50);
+    let runs = adguard_schedule::list_runs(&state.db, id, limit)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to list schedule runs: {}", e)))?;
+    Ok(Json(runs))
+}
+
+async fn find_schedule(state: &crate::AppState, id: i64) -> Result<Schedule> {
+    adguard_schedule::get_schedule(&state.db, id)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to get schedule: {}", e)))?
+        .ok_or_else(|| AppError::NotFound(format!("Schedule with id {} not found", id)))
+}
+
+/// Validates a schedule definition. `id` is the schedule being updated, which is
+/// not checked for overlaps with itself.
+async fn validate_input(
+    state: &crate::AppState,
+    payload: ScheduleInput,
+    id: Option<i64>,
+) -> Result<ScheduleInput> {
+    let input = payload.validate().map_err(AppError::Validation)?;
+
+    let schedules = adguard_schedule::list_schedules(&state.db)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to list schedules: {}", e)))?;
+    if let Some(other) = schedules
+        .iter()
+        .find(|other| Some(other.id) != id && input.overlaps(other))
+    {
+        return Err(AppError::Conflict(format!(
+            "Schedule overlaps schedule '{}' (id {}), which changes the same setting",
+            other.name, other.id
+        )));
+    }
+
+    // Service ids can only be checked against a reachable AdGuard; otherwise the
+    // first run records the error.
+    if !input.blocked_services.is_empty()
+        && let Some(service) = state.adguard_service.as_ref()
+        && let Ok(available) = service.get_available_services().await
+        && let Some(unknown) = input
+            .blocked_services
+            .iter()
+            .find(|id| !available.iter().any(|service| &service.id == *id))
+    {
+        return Err(AppError::Validation(format!(
+            "Unknown service id '{}'",
+            unknown
+        )));
+    }
+
+    Ok(input)
+}
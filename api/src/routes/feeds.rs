@@ -3,24 +3,114 @@ use std::net::IpAddr;
 use axum::{
     Json, Router,
     extract::{Path, State},
-    http::StatusCode,
-    routing::{delete, get, post},
+    http::{StatusCode, header},
+    response::IntoResponse,
+    response::Response,
+    routing::{get, post, put},
 };
 use serde::{Deserialize, Serialize};
-use url::Url;
+use sqlx::types::Json as SqlJson;
+use url::{Host, Url};
 
 use crate::error::{AppError, Result};
+use crate::services::feed::{self, FeedResult};
+use crate::services::feed_discovery::{self, FeedCandidate};
+use crate::services::opml::{self, OpmlFeed};
+use crate::services::ssrf::is_global_ip;
+
+const OPML_EXPORT_TITLE: &str = "OpenHome feeds";
+const MAX_CATEGORY_LENGTH: usize = 100;
+const MAX_TAG_LENGTH: usize = 50;
+const MAX_TAGS_PER_FEED: usize = 20;
+const MAX_CUSTOM_TITLE_LENGTH: usize = 200;
+const MIN_REFRESH_INTERVAL_MINUTES: i64 = 15;
+const MAX_REFRESH_INTERVAL_MINUTES: i64 = 7 * 24 * 60;
 
 #[derive(Debug, Serialize)]
 struct Feed {
     id: i64,
     url: String,
+    /// The custom title if one is set, otherwise the title from the feed itself.
     title: Option<String>,
+    custom_title: Option<String>,
+    category: Option<String>,
+    tags: SqlJson<Vec<String>>,
+    enabled: bool,
+    /// Fixed refresh interval; `None` lets the scheduler adapt to the feed.
+    refresh_interval_minutes: Option<i64>,
+    item_count: i64,
+    unread_count: i64,
+    last_fetched_at: Option<String>,
+    last_error: Option<String>,
+    next_fetch_at: Option<String>,
+}
+
+/// Partial update of a feed. For `custom_title` and `refresh_interval_minutes`,
+/// an explicit `null` clears the value while a missing field leaves it unchanged.
+#[derive(Debug, Deserialize)]
+struct UpdateFeed {
+    url: Option<String>,
+    #[serde(default, deserialize_with = "present")]
+    custom_title: Option<Option<String>>,
+    enabled: Option<bool>,
+    #[serde(default, deserialize_with = "present")]
+    refresh_interval_minutes: Option<Option<i64>>,
+}
+
+fn present<'de, D, T>(deserializer: D) -> std::result::Result<Option<Option<T>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+#[derive(Debug, Deserialize)]
+struct SetCategory {
+    category: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SetTags {
+    tags: Vec<String>,
+}
+
+/// A category or tag together with the number of feeds using it.
+#[derive(Debug, Serialize)]
+struct LabelCount {
+    name: String,
+    feeds: i64,
+}
+
+#[derive(Debug, Serialize)]
+struct ImportReport {
+    imported: Vec<Feed>,
+    duplicates: Vec<String>,
+    invalid: Vec<InvalidFeed>,
+}
+
+#[derive(Debug, Serialize)]
+struct InvalidFeed {
+    url: String,
+    error: String,
 }
 
 #[derive(Debug, Deserialize)]
 struct CreateFeed {
     url: String,
+    /// Treat `url` as a web page and subscribe to the feed it advertises.
+    #[serde(default)]
+    discover: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct DiscoverRequest {
+    url: String,
+}
+
+#[derive(Debug, Serialize)]
+struct DiscoverResponse {
+    candidates: Vec<FeedCandidate>,
 }
 
 fn validate_url(raw: &str) -> Result<Url> {
@@ -38,42 +128,29 @@ fn validate_url(raw: &str) -> Result<Url> {
         ));
     }
 
-    let Some(host) = url.host_str() else {
+    let Some(host) = url.host() else {
         return Err(AppError::Validation("URL missing host".to_string()));
     };
 
-    let host_lower = host.to_lowercase();
-    if host_lower == "localhost"
-        || host_lower.ends_with(".localhost")
-        || host_lower.ends_with(".local")
-    {
-        return Err(AppError::Validation(
-            "URL host is not allowed (localhost or .local)".to_string(),
-        ));
-    }
-
-    if let Ok(ip) = host.parse::<IpAddr>() {
-        let is_private = match ip {
-            IpAddr::V4(addr) => {
-                addr.is_private()
-                    || addr.is_loopback()
-                    || addr.is_link_local()
-                    || addr.is_multicast()
-                    || addr.is_unspecified()
-            }
-            IpAddr::V6(addr) => {
-                addr.is_loopback()
-                    || addr.is_unicast_link_local()
-                    || addr.is_unspecified()
-                    || addr.is_unique_local()
-                    || addr.is_multicast()
+    let ip = match host {
+        Host::Domain(domain) => {
+            let domain = domain.to_lowercase();
+            if domain == "localhost" || domain.ends_with(".localhost") || domain.ends_with(".local")
+            {
+                return Err(AppError::Validation(
+                    "URL host is not allowed (localhost or .local)".to_string(),
+                ));
             }
-        };
-        if is_private {
-            return Err(AppError::Validation(
-                "URL host is a private or reserved IP address".to_string(),
-            ));
+            None
         }
+        Host::Ipv4(ip) => Some(IpAddr::V4(ip)),
+        Host::Ipv6(ip) => Some(IpAddr::V6(ip)),
+    };
+
+    if ip.is_some_and(|ip| !is_global_ip(ip)) {
+        return Err(AppError::Validation(
+            "URL host is a private or reserved IP address".to_string(),
+        ));
     }
 
     Ok(url)
@@ -83,53 +160,416 @@ pub fn router() -> Router<crate::AppState> {
     Router::new()
         .route("/api/feeds", get(get_feeds))
         .route("/api/feeds", post(create_feed))
-        .route("/api/feeds/{id}", delete(delete_feed))
+        .route("/api/feeds/discover", post(discover_feeds))
+        .route("/api/feeds/import", post(import_feeds))
+        .route("/api/feeds/export", get(export_feeds))
+        .route("/api/feeds/categories", get(get_categories))
+        .route("/api/feeds/tags", get(get_tags))
+        .route(
+            "/api/feeds/{id}",
+            get(get_feed).patch(update_feed).delete(delete_feed),
+        )
+        .route("/api/feeds/{id}/refresh", post(refresh_feed))
+        .route("/api/feeds/{id}/category", put(set_category))
+        .route("/api/feeds/{id}/tags", put(set_tags))
 }
 
-async fn get_feeds(State(state): State<crate::AppState>) -> Result<Json<Vec<Feed>>> {
-    let feeds = sqlx::query_as!(
+fn normalize_category(category: Option<String>) -> Result<Option<String>> {
+    let Some(category) = category
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty())
+    else {
+        return Ok(None);
+    };
+    if category.chars().count() > MAX_CATEGORY_LENGTH {
+        return Err(AppError::Validation(format!(
+            "Category must be at most {} characters",
+            MAX_CATEGORY_LENGTH
+        )));
+    }
+    Ok(Some(category))
+}
+
+/// Trims and lowercases tags, dropping duplicates while keeping the given order.
+fn normalize_tags(tags: Vec<String>) -> Result<Vec<String>> {
+    let mut normalized: Vec<String> = Vec::with_capacity(tags.len());
+    for tag in tags {
+        let tag = tag.trim().to_lowercase();
+        if tag.is_empty() {
+            return Err(AppError::Validation("Tags must not be empty".to_string()));
+        }
+        if tag.chars().count() > MAX_TAG_LENGTH {
+            return Err(AppError::Validation(format!(
+                "Tags must be at most {} characters",
+                MAX_TAG_LENGTH
+            )));
+        }
+        if !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+    }
+    if normalized.len() > MAX_TAGS_PER_FEED {
+        return Err(AppError::Validation(format!(
+            "A feed can have at most {} tags",
+            MAX_TAGS_PER_FEED
+        )));
+    }
+    Ok(normalized)
+}
+
+/// Loads all feeds, or only the feed with `id` when given.
+async fn fetch_feeds(db: &sqlx::SqlitePool, id: Option<i64>) -> Result<Vec<Feed>> {
+    sqlx::query_as!(
         Feed,
         r#"
-        SELECT id, url, title
+        SELECT
+            id, url,
+            COALESCE(custom_title, title) as title,
+            custom_title,
+            category,
+            (SELECT json_group_array(tag) FROM (SELECT tag FROM feed_tags WHERE feed_id = feeds.id ORDER BY tag))
+                as "tags!: SqlJson<Vec<String>>",
+            COALESCE(enabled, TRUE) as "enabled!: bool",
+            refresh_interval_minutes,
+            (SELECT COUNT(*) FROM feed_items WHERE feed_id = feeds.id) as "item_count!: i64",
+            (SELECT COUNT(*) FROM feed_items WHERE feed_id = feeds.id AND read_at IS NULL)
+                as "unread_count!: i64",
+            CAST(last_fetched_at AS TEXT) as last_fetched_at,
+            last_error,
+            CAST(next_fetch_at AS TEXT) as next_fetch_at
         FROM feeds
-        "#
+        WHERE $1 IS NULL OR id = $1
+        ORDER BY id
+        "#,
+        id
     )
-    .fetch_all(&state.db)
+    .fetch_all(db)
     .await
-    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to fetch feeds: {}", e)))?;
+    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to fetch feeds: {}", e)))
+}
+
+async fn fetch_feed(db: &sqlx::SqlitePool, id: i64) -> Result<Feed> {
+    fetch_feeds(db, Some(id))
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| AppError::NotFound(format!("Feed with id {} not found", id)))
+}
 
-    Ok(Json(feeds))
+async fn get_feeds(State(state): State<crate::AppState>) -> Result<Json<Vec<Feed>>> {
+    Ok(Json(fetch_feeds(&state.db, None).await?))
 }
 
+async fn get_feed(State(state): State<crate::AppState>, Path(id): Path<i64>) -> Result<Json<Feed>> {
+    Ok(Json(fetch_feed(&state.db, id).await?))
+}
+
+/// Subscribes to a feed. With `discover` set, `url` may also be a web page: a single
+/// advertised feed is subscribed to directly, several are returned with
+/// `300 Multiple Choices` for the caller to pick from.
 async fn create_feed(
     State(state): State<crate::AppState>,
     Json(payload): Json<CreateFeed>,
-) -> Result<(StatusCode, Json<Feed>)> {
-    let validated_url = match validate_url(&payload.url) {
-        Ok(url) => url,
-        Err(e) => return Err(e),
-    };
+) -> Result<Response> {
+    let validated_url = validate_url(&payload.url)?;
+
+    if !payload.discover {
+        let feed = insert_feed(&state.db, validated_url.as_str()).await?;
+        return Ok((StatusCode::CREATED, Json(feed)).into_response());
+    }
+
+    let mut candidates = discover(&state, &validated_url).await?;
+    match candidates.len() {
+        0 => Err(AppError::Unprocessable(format!(
+            "No feeds found at {}",
+            validated_url
+        ))),
+        1 => {
+            let candidate = candidates.remove(0);
+            let url = validate_url(&candidate.url)?;
+            let feed = insert_feed(&state.db, url.as_str()).await?;
+            Ok((StatusCode::CREATED, Json(feed)).into_response())
+        }
+        _ => Ok((
+            StatusCode::MULTIPLE_CHOICES,
+            Json(DiscoverResponse { candidates }),
+        )
+            .into_response()),
+    }
+}
 
-    let feed_url = validated_url.as_str().to_string();
-    let feed = sqlx::query_as!(
+async fn insert_feed(db: &sqlx::SqlitePool, url: &str) -> Result<Feed> {
+    sqlx::query_as!(
         Feed,
         r#"
         INSERT INTO feeds (url)
         VALUES ($1)
-        RETURNING id, url, title
+        RETURNING id as "id!", url, title, custom_title, category,
+            '[]' as "tags!: SqlJson<Vec<String>>",
+            COALESCE(enabled, TRUE) as "enabled!: bool", refresh_interval_minutes,
+            0 as "item_count!: i64", 0 as "unread_count!: i64",
+            CAST(last_fetched_at AS TEXT) as last_fetched_at, last_error,
+            CAST(next_fetch_at AS TEXT) as next_fetch_at
         "#,
-        feed_url
+        url
     )
-    .fetch_one(&state.db)
+    .fetch_one(db)
     .await
     .map_err(|e| match e {
         sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
             AppError::Conflict("Feed with this URL already exists".to_string())
         }
         other => AppError::Internal(anyhow::anyhow!("Failed to create feed: {}", other)),
+    })
+}
+
+/// Lists the feeds a web page advertises without subscribing to any of them.
+async fn discover_feeds(
+    State(state): State<crate::AppState>,
+    Json(payload): Json<DiscoverRequest>,
+) -> Result<Json<DiscoverResponse>> {
+    let url = validate_url(&payload.url)?;
+    let candidates = discover(&state, &url).await?;
+    Ok(Json(DiscoverResponse { candidates }))
+}
+
+async fn discover(state: &crate::AppState, url: &Url) -> Result<Vec<FeedCandidate>> {
+    feed_discovery::discover_feeds(&state.feed_fetcher, url)
+        .await
+        .map_err(|e| AppError::Unprocessable(format!("Feed discovery failed: {}", e)))
+}
+
+/// Imports subscriptions from an OPML document. Folder outlines become categories;
+/// feeds that already exist (or repeat within the document) are reported, not updated.
+async fn import_feeds(
+    State(state): State<crate::AppState>,
+    body: String,
+) -> Result<Json<ImportReport>> {
+    let entries = opml::parse_opml(&body)
+        .map_err(|e| AppError::Validation(format!("Invalid OPML: {}", e)))?;
+
+    let mut report = ImportReport {
+        imported: Vec::new(),
+        duplicates: Vec::new(),
+        invalid: Vec::new(),
+    };
+    let mut tx = state
+        .db
+        .begin()
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to import feeds: {}", e)))?;
+
+    for entry in entries {
+        let validated = validate_url(&entry.url).and_then(|url| {
+            Ok((
+                url.as_str().to_string(),
+                normalize_category(entry.category)?,
+            ))
+        });
+        let (url, category) = match validated {
+            Ok(validated) => validated,
+            Err(e) => {
+                let error = match e {
+                    AppError::Validation(msg) => msg,
+                    other => other.to_string(),
+                };
+                report.invalid.push(InvalidFeed {
+                    url: entry.url,
+                    error,
+                });
+                continue;
+            }
+        };
+        let inserted = sqlx::query_as!(
+            Feed,
+            r#"
+            INSERT INTO feeds (url, title, category)
+            VALUES ($1, $2, $3)
+            ON CONFLICT(url) DO NOTHING
+            RETURNING id as "id!", url, title, custom_title, category,
+            '[]' as "tags!: SqlJson<Vec<String>>",
+            COALESCE(enabled, TRUE) as "enabled!: bool", refresh_interval_minutes,
+            0 as "item_count!: i64", 0 as "unread_count!: i64",
+            CAST(last_fetched_at AS TEXT) as last_fetched_at, last_error,
+            CAST(next_fetch_at AS TEXT) as next_fetch_at
+            "#,
+            url,
+            entry.title,
+            category
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to import feed: {}", e)))?;
+
+        match inserted {
+            Some(feed) => report.imported.push(feed),
+            None => report.duplicates.push(url),
+        }
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to import feeds: {}", e)))?;
+
+    Ok(Json(report))
+}
+
+async fn export_feeds(State(state): State<crate::AppState>) -> Result<impl IntoResponse> {
+    let feeds = sqlx::query_as!(
+        OpmlFeed,
+        r#"
+        SELECT url, COALESCE(custom_title, title) as title, category
+        FROM feeds
+        ORDER BY id
+        "#
+    )
+    .fetch_all(&state.db)
+    .await
+    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to fetch feeds: {}", e)))?;
+
+    let document = opml::write_opml(OPML_EXPORT_TITLE, &feeds)
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to write OPML: {}", e)))?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/x-opml; charset=utf-8"),
+            (
+                header::CONTENT_DISPOSITION,
+                "attachment; filename=\"feeds.opml\"",
+            ),
+        ],
+        document,
+    ))
+}
+
+async fn update_feed(
+    State(state): State<crate::AppState>,
+    Path(id): Path<i64>,
+    Json(payload): Json<UpdateFeed>,
+) -> Result<Json<Feed>> {
+    let current = sqlx::query!(
+        r#"
+        SELECT url, custom_title, COALESCE(enabled, TRUE) as "enabled!: bool", refresh_interval_minutes
+        FROM feeds
+        WHERE id = $1
+        "#,
+        id
+    )
+    .fetch_optional(&state.db)
+    .await
+    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to update feed: {}", e)))?
+    .ok_or_else(|| AppError::NotFound(format!("Feed with id {} not found", id)))?;
+
+    let url = match payload.url {
+        Some(url) => validate_url(&url)?.as_str().to_string(),
+        None => current.url.clone(),
+    };
+    let custom_title = match payload.custom_title {
+        Some(title) => normalize_custom_title(title)?,
+        None => current.custom_title,
+    };
+    let enabled = payload.enabled.unwrap_or(current.enabled);
+    let refresh_interval_minutes = match payload.refresh_interval_minutes {
+        Some(Some(minutes))
+            if !(MIN_REFRESH_INTERVAL_MINUTES..=MAX_REFRESH_INTERVAL_MINUTES)
+                .contains(&minutes) =>
+        {
+            return Err(AppError::Validation(format!(
+                "refresh_interval_minutes must be between {} and {}",
+                MIN_REFRESH_INTERVAL_MINUTES, MAX_REFRESH_INTERVAL_MINUTES
+            )));
+        }
+        Some(minutes) => minutes,
+        None => current.refresh_interval_minutes,
+    };
+    // A new URL is a different feed as far as caching and backoff are concerned.
+    let url_changed = url != current.url;
+    // A re-enabled feed is fetched on the next tick. A new interval reschedules a
+    // healthy feed from its last fetch, or for the next tick when the interval is
+    // cleared; a failing feed keeps its backoff.
+    let reenabled = enabled && !current.enabled;
+    let interval_changed = refresh_interval_minutes != current.refresh_interval_minutes;
+
+    sqlx::query!(
+        r#"
+        UPDATE feeds SET
+            url = $1,
+            custom_title = $2,
+            enabled = $3,
+            refresh_interval_minutes = $4,
+            etag = CASE WHEN $5 THEN NULL ELSE etag END,
+            last_modified = CASE WHEN $5 THEN NULL ELSE last_modified END,
+            last_error = CASE WHEN $5 THEN NULL ELSE last_error END,
+            error_count = CASE WHEN $5 THEN 0 ELSE error_count END,
+            next_fetch_at = CASE
+                WHEN $5 OR $7 THEN NULL
+                WHEN $8 AND error_count = 0 THEN
+                    datetime(last_fetched_at, '+' || $4 || ' minutes')
+                ELSE next_fetch_at
+            END
+        WHERE id = $6
+        "#,
+        url,
+        custom_title,
+        enabled,
+        refresh_interval_minutes,
+        url_changed,
+        id,
+        reenabled,
+        interval_changed
+    )
+    .execute(&state.db)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+            AppError::Conflict("Feed with this URL already exists".to_string())
+        }
+        other => AppError::Internal(anyhow::anyhow!("Failed to update feed: {}", other)),
     })?;
 
-    Ok((StatusCode::CREATED, Json(feed)))
+    Ok(Json(fetch_feed(&state.db, id).await?))
+}
+
+fn normalize_custom_title(title: Option<String>) -> Result<Option<String>> {
+    let Some(title) = title
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty())
+    else {
+        return Ok(None);
+    };
+    if title.chars().count() > MAX_CUSTOM_TITLE_LENGTH {
+        return Err(AppError::Validation(format!(
+            "Title must be at most {} characters",
+            MAX_CUSTOM_TITLE_LENGTH
+        )));
+    }
+    Ok(Some(title))
+}
+
+/// Refreshes a single feed right away, regardless of its schedule.
+async fn refresh_feed(
+    State(state): State<crate::AppState>,
+    Path(id): Path<i64>,
+) -> Result<Json<FeedResult>> {
+    let feed = sqlx::query!(
+        r#"SELECT url, COALESCE(enabled, TRUE) as "enabled!: bool" FROM feeds WHERE id = $1"#,
+        id
+    )
+    .fetch_optional(&state.db)
+    .await
+    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to refresh feed: {}", e)))?
+    .ok_or_else(|| AppError::NotFound(format!("Feed with id {} not found", id)))?;
+
+    if !feed.enabled {
+        return Err(AppError::Conflict(format!(
+            "Feed with id {} is disabled",
+            id
+        )));
+    }
+
+    Ok(Json(
+        feed::refresh_feed(&state.db, &state.feed_fetcher, id, &feed.url).await,
+    ))
 }
 
 async fn delete_feed(
@@ -153,3 +593,146 @@ async fn delete_feed(
 
     Ok(StatusCode::NO_CONTENT)
 }
+
+async fn set_category(
+    State(state): State<crate::AppState>,
+    Path(id): Path<i64>,
+    Json(payload): Json<SetCategory>,
+) -> Result<Json<Feed>> {
+    let category = normalize_category(payload.category)?;
+
+    let result = sqlx::query!(
+        r#"
+        UPDATE feeds SET category = $1
+        WHERE id = $2
+        "#,
+        category,
+        id
+    )
+    .execute(&state.db)
+    .await
+    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to update feed category: {}", e)))?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound(format!("Feed with id {} not found", id)));
+    }
+
+    Ok(Json(fetch_feed(&state.db, id).await?))
+}
+
+/// Replaces the feed's tags with the given set.
+async fn set_tags(
+    State(state): State<crate::AppState>,
+    Path(id): Path<i64>,
+    Json(payload): Json<SetTags>,
+) -> Result<Json<Feed>> {
+    let tags = normalize_tags(payload.tags)?;
+
+    let mut tx =
+        state.db.begin().await.map_err(|e| {
+            AppError::Internal(anyhow::anyhow!("Failed to update feed tags: {}", e))
+        })?;
+
+    let exists = sqlx::query_scalar!("SELECT 1 as found FROM feeds WHERE id = $1", id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to update feed tags: {}", e)))?
+        .is_some();
+    if !exists {
+        return Err(AppError::NotFound(format!("Feed with id {} not found", id)));
+    }
+
+    sqlx::query!("DELETE FROM feed_tags WHERE feed_id = $1", id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to update feed tags: {}", e)))?;
+
+    for tag in &tags {
+        sqlx::query!(
+            "INSERT INTO feed_tags (feed_id, tag) VALUES ($1, $2)",
+            id,
+            tag
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to update feed tags: {}", e)))?;
+    }
+
+    tx.commit()
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to update feed tags: {}", e)))?;
+
+    Ok(Json(fetch_feed(&state.db, id).await?))
+}
+
+async fn get_categories(State(state): State<crate::AppState>) -> Result<Json<Vec<LabelCount>>> {
+    let categories = sqlx::query_as!(
+        LabelCount,
+        r#"
+        SELECT category as "name!", COUNT(*) as "feeds!: i64"
+        FROM feeds
+        WHERE category IS NOT NULL
+        GROUP BY category
+        ORDER BY category
+        "#
+    )
+    .fetch_all(&state.db)
+    .await
+    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to fetch categories: {}", e)))?;
+
+    Ok(Json(categories))
+}
+
+async fn get_tags(State(state): State<crate::AppState>) -> Result<Json<Vec<LabelCount>>> {
+    let tags = sqlx::query_as!(
+        LabelCount,
+        r#"
+        SELECT tag as "name!", COUNT(*) as "feeds!: i64"
+        FROM feed_tags
+        GROUP BY tag
+        ORDER BY tag
+        "#
+    )
+    .fetch_all(&state.db)
+    .await
+    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to fetch tags: {}", e)))?;
+
+    Ok(Json(tags))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_tags_trims_lowercases_and_dedups() {
+        let tags = normalize_tags(vec![
+            " Rust ".to_string(),
+            "news".to_string(),
+            "rust".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(tags, vec!["rust", "news"]);
+    }
+
+    #[test]
+    fn test_normalize_tags_rejects_invalid_tags() {
+        assert!(normalize_tags(vec!["  ".to_string()]).is_err());
+        assert!(normalize_tags(vec!["x".repeat(MAX_TAG_LENGTH + 1)]).is_err());
+        let too_many = (0..=MAX_TAGS_PER_FEED)
+            .map(|i| format!("tag{}", i))
+            .collect();
+        assert!(normalize_tags(too_many).is_err());
+    }
+
+    #[test]
+    fn test_normalize_category_treats_blank_as_none() {
+        assert_eq!(normalize_category(Some("  ".to_string())).unwrap(), None);
+        assert_eq!(normalize_category(None).unwrap(), None);
+        assert_eq!(
+            normalize_category(Some(" Tech ".to_string())).unwrap(),
+            Some("Tech".to_string())
+        );
+        assert!(normalize_category(Some("x".repeat(MAX_CATEGORY_LENGTH + 1))).is_err());
+    }
+}
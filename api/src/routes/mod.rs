@@ -1,4 +1,5 @@
 pub mod adguard;
+pub mod adguard_schedules;
 pub mod docker;
 pub mod facts;
 pub mod feeds;
@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+
 use crate::error::{AppError, Result};
 use crate::services::feed;
 use axum::{
@@ -6,8 +8,17 @@ use axum::{
     http::StatusCode,
     routing::{get, post},
 };
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use sqlx::FromRow;
+use sqlx::sqlite::SqliteRow;
+use sqlx::{FromRow, SqlitePool};
+
+const MAX_FEED_FILTER_IDS: usize = 100;
+const MAX_BULK_ITEM_IDS: usize = 500;
+const MAX_SEARCH_QUERY_LENGTH: usize = 200;
+/// Markers passed to FTS `highlight()`/`snippet()`, replaced by `render_highlight`.
+const HIGHLIGHT_START: &str = "\u{2}";
+const HIGHLIGHT_END: &str = "\u{3}";
 
 #[derive(Debug, Serialize, FromRow)]
 struct FeedItemResponse {
@@ -19,6 +30,7 @@ struct FeedItemResponse {
     link: String,
     pub_date: Option<String>,
     read_at: Option<String>,
+    starred_at: Option<String>,
 }
 
 #[derive(Debug, Serialize, FromRow)]
@@ -40,11 +52,16 @@ enum TimelineResponse {
 struct TimelineQuery {
     limit: Option<i64>,
     unread: Option<bool>,
+    starred: Option<bool>,
     before_id: Option<i64>,
     view: Option<String>,
+    /// One feed id or a comma-separated list, e.g. `feed_id=3,7`.
+    feed_id: Option<String>,
+    category: Option<String>,
+    tag: Option<String>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 enum TimelineView {
     Full,
     Compact,
@@ -59,6 +76,106 @@ impl TimelineView {
     }
 }
 
+#[derive(Debug, Deserialize)]
+struct SearchQuery {
+    q: Option<String>,
+    limit: Option<i64>,
+    cursor: Option<String>,
+}
+
+#[derive(Debug, FromRow)]
+struct SearchRow {
+    id: i64,
+    feed_id: i64,
+    feed_title: Option<String>,
+    title: String,
+    link: String,
+    pub_date: Option<String>,
+    read_at: Option<String>,
+    starred_at: Option<String>,
+    title_highlight: String,
+    snippet: Option<String>,
+    rank: f64,
+}
+
+/// A search hit. `title_highlight` and `snippet` are HTML-escaped with matched terms
+/// wrapped in `<mark>` tags; lower `rank` values are better matches.
+#[derive(Debug, Serialize)]
+struct SearchResult {
+    cursor: String,
+    id: i64,
+    feed_id: i64,
+    feed_title: Option<String>,
+    title: String,
+    link: String,
+    pub_date: Option<String>,
+    read_at: Option<String>,
+    starred_at: Option<String>,
+    title_highlight: String,
+    snippet: Option<String>,
+    rank: f64,
+}
+
+/// Position in a search: the newest item id the search covers, and the rank and id
+/// of the last result returned. Encoded as an opaque string for clients.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct SearchCursor {
+    max_id: i64,
+    after: Option<(f64, i64)>,
+}
+
+impl SearchCursor {
+    fn encode(&self) -> String {
+        match self.after {
+            Some((rank, id)) => format!("{}.{:016x}.{}", self.max_id, rank.to_bits(), id),
+            None => self.max_id.to_string(),
+        }
+    }
+
+    fn decode(raw: &str) -> Option<Self> {
+        let mut parts = raw.split('.');
+        let max_id = parts.next()?.parse().ok()?;
+        let rank = f64::from_bits(u64::from_str_radix(parts.next()?, 16).ok()?);
+        let id = parts.next()?.parse().ok()?;
+        if parts.next().is_some() || !rank.is_finite() {
+            return None;
+        }
+        Some(Self {
+            max_id,
+            after: Some((rank, id)),
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct BulkReadRequest {
+    ids: Vec<i64>,
+    #[serde(default = "default_read")]
+    read: bool,
+}
+
+fn default_read() -> bool {
+    true
+}
+
+#[derive(Debug, Serialize)]
+struct BulkReadResponse {
+    updated: u64,
+    not_found: Vec<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MarkAllReadRequest {
+    feed_id: Option<i64>,
+    category: Option<String>,
+    older_than: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+struct MarkAllReadResponse {
+    updated: u64,
+}
+
 #[derive(Debug, Serialize)]
 struct RefreshSummary {
     feeds_processed: usize,
@@ -77,23 +194,89 @@ struct FeedRefreshError {
 pub fn router() -> Router<crate::AppState> {
     Router::new()
         .route("/api/timeline", get(get_timeline))
-        .route("/api/items/{id}/read", post(mark_read))
+        .route("/api/timeline/search", get(search_timeline))
+        .route("/api/items/read", post(bulk_mark_read))
+        .route("/api/items/read-all", post(mark_all_read))
+        .route("/api/items/{id}/read", post(mark_read).delete(mark_unread))
+        .route("/api/items/{id}/star", post(star_item).delete(unstar_item))
         .route("/api/feeds/refresh", post(refresh_feeds))
 }
 
-fn build_timeline_query(view: TimelineView, with_unread_filter: bool, with_cursor: bool) -> String {
+/// Optional timeline filters. Values are bound after the cursor and limit, in
+/// field order, by `fetch_timeline`.
+#[derive(Debug, Default)]
+struct TimelineFilters {
+    unread: bool,
+    starred: bool,
+    feed_ids: Vec<i64>,
+    category: Option<String>,
+    tag: Option<String>,
+}
+
+impl TimelineFilters {
+    fn from_query(query: &TimelineQuery) -> Result<Self> {
+        let non_empty = |value: &Option<String>| {
+            value
+                .as_deref()
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(str::to_string)
+        };
+        Ok(TimelineFilters {
+            unread: query.unread == Some(true),
+            starred: query.starred == Some(true),
+            feed_ids: parse_feed_ids(query.feed_id.as_deref())?,
+            category: non_empty(&query.category),
+            tag: non_empty(&query.tag).map(|tag| tag.to_lowercase()),
+        })
+    }
+}
+
+fn parse_feed_ids(raw: Option<&str>) -> Result<Vec<i64>> {
+    let Some(raw) = raw else {
+        return Ok(Vec::new());
+    };
+    let mut ids = Vec::new();
+    let mut seen = HashSet::new();
+    for part in raw.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let id = part
+            .parse::<i64>()
+            .map_err(|_| AppError::Validation(format!("Invalid feed_id: {}", part)))?;
+        if seen.insert(id) {
+            ids.push(id);
+        }
+        // Checked as we go so an oversized list is rejected without parsing all of it.
+        if ids.len() > MAX_FEED_FILTER_IDS {
+            return Err(AppError::Validation(format!(
+                "At most {} feed ids can be given",
+                MAX_FEED_FILTER_IDS
+            )));
+        }
+    }
+    Ok(ids)
+}
+
+/// Builds the timeline query. Parameters are numbered: with a cursor `?1` is the
+/// cursor item id and `?2` the limit, otherwise `?1` is the limit; filter values
+/// follow in `TimelineFilters` field order.
+fn build_timeline_query(
+    view: TimelineView,
+    filters: &TimelineFilters,
+    with_cursor: bool,
+) -> String {
     let select_clause = match view {
         TimelineView::Full => {
             r#"
             SELECT
                 fi.id,
                 fi.feed_id,
-                f.title as feed_title,
+                COALESCE(f.custom_title, f.title) as feed_title,
                 fi.title,
                 fi.description,
                 fi.link,
                 CAST(fi.pub_date AS TEXT) as pub_date,
-                CAST(fi.read_at AS TEXT) as read_at
+                CAST(fi.read_at AS TEXT) as read_at,
+                CAST(fi.starred_at AS TEXT) as starred_at
         "#
         }
         TimelineView::Compact => {
@@ -107,80 +290,106 @@ fn build_timeline_query(view: TimelineView, with_unread_filter: bool, with_curso
         }
     };
 
-    let from_clause = match view {
-        TimelineView::Full => {
-            r#"
-        FROM feed_items fi
-        JOIN feeds f ON f.id = fi.feed_id
-        "#
-        }
-        TimelineView::Compact => {
-            r#"
-        FROM feed_items fi
-        "#
-        }
-    };
-
-    let base_query = format!(
-        r#"
-        {select_clause}
-        {from_clause}
-        "#
-    );
+    let (limit_param, mut next_param) = if with_cursor { (2, 3) } else { (1, 2) };
+    let mut conditions: Vec<String> = Vec::new();
 
     if with_cursor {
-        format!(
-            r#"
-            WITH cursor AS (
-                SELECT pub_date FROM feed_items WHERE id = ?1
-            )
-            {base_query}, cursor
-            WHERE
-                {where_clause}
-            ORDER BY (fi.pub_date IS NULL) ASC, fi.pub_date DESC, fi.id DESC
-            LIMIT ?2
-            "#,
-            where_clause = if with_unread_filter {
-                r#"
-                fi.read_at IS NULL
-                AND (
-                    (cursor.pub_date IS NULL AND fi.pub_date IS NULL AND fi.id < ?1)
-                    OR (cursor.pub_date IS NOT NULL AND (
-                        fi.pub_date < cursor.pub_date
-                        OR (fi.pub_date = cursor.pub_date AND fi.id < ?1)
-                        OR fi.pub_date IS NULL
-                    ))
-                )
-                "#
-            } else {
-                r#"
-                (
+        conditions.push(
+            r#"(
                     (cursor.pub_date IS NULL AND fi.pub_date IS NULL AND fi.id < ?1)
                     OR (cursor.pub_date IS NOT NULL AND (
                         fi.pub_date < cursor.pub_date
                         OR (fi.pub_date = cursor.pub_date AND fi.id < ?1)
                         OR fi.pub_date IS NULL
                     ))
-                )
-                "#
-            }
-        )
+                )"#
+            .to_string(),
+        );
+    }
+    if filters.unread {
+        conditions.push("fi.read_at IS NULL".to_string());
+    }
+    if filters.starred {
+        conditions.push("fi.starred_at IS NOT NULL".to_string());
+    }
+    if !filters.feed_ids.is_empty() {
+        let params: Vec<String> = (0..filters.feed_ids.len())
+            .map(|i| format!("?{}", next_param + i))
+            .collect();
+        conditions.push(format!("fi.feed_id IN ({})", params.join(", ")));
+        next_param += filters.feed_ids.len();
+    }
+    if filters.category.is_some() {
+        conditions.push(format!("f.category = ?{next_param}"));
+        next_param += 1;
+    }
+    if filters.tag.is_some() {
+        conditions.push(format!(
+            "EXISTS (SELECT 1 FROM feed_tags ft WHERE ft.feed_id = fi.feed_id AND ft.tag = ?{next_param})"
+        ));
+    }
+
+    let where_clause = if conditions.is_empty() {
+        "1 = 1".to_string()
     } else {
-        format!(
+        conditions.join("\n                AND ")
+    };
+
+    let (cursor_cte, cursor_join) = if with_cursor {
+        (
             r#"
-            {base_query}
-            WHERE
-                {where_clause}
-            ORDER BY (fi.pub_date IS NULL) ASC, fi.pub_date DESC, fi.id DESC
-            LIMIT ?
-            "#,
-            where_clause = if with_unread_filter {
-                "fi.read_at IS NULL"
-            } else {
-                "1 = 1"
-            }
+            WITH cursor AS (
+                SELECT pub_date FROM feed_items WHERE id = ?1
+            )"#,
+            ", cursor",
         )
+    } else {
+        ("", "")
+    };
+
+    format!(
+        r#"
+        {cursor_cte}
+        {select_clause}
+        FROM feed_items fi
+        JOIN feeds f ON f.id = fi.feed_id{cursor_join}
+        WHERE
+            {where_clause}
+        ORDER BY (fi.pub_date IS NULL) ASC, fi.pub_date DESC, fi.id DESC
+        LIMIT ?{limit_param}
+        "#
+    )
+}
+
+async fn fetch_timeline<T>(
+    db: &SqlitePool,
+    view: TimelineView,
+    filters: &TimelineFilters,
+    before_id: Option<i64>,
+    limit: i64,
+) -> Result<Vec<T>>
+where
+    T: for<'r> FromRow<'r, SqliteRow> + Send + Unpin,
+{
+    let query_str = build_timeline_query(view, filters, before_id.is_some());
+    let mut query = sqlx::query_as::<_, T>(&query_str);
+    if let Some(before_id) = before_id {
+        query = query.bind(before_id);
     }
+    query = query.bind(limit);
+    for feed_id in &filters.feed_ids {
+        query = query.bind(feed_id);
+    }
+    if let Some(category) = &filters.category {
+        query = query.bind(category);
+    }
+    if let Some(tag) = &filters.tag {
+        query = query.bind(tag);
+    }
+    query
+        .fetch_all(db)
+        .await
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to fetch timeline: {}", e)))
 }
 
 async fn get_timeline(
@@ -189,8 +398,9 @@ async fn get_timeline(
 ) -> Result<Json<TimelineResponse>> {
     let limit = query.limit.map(|limit| limit.clamp(1, 200)).unwrap_or(50);
     let view = TimelineView::from_query(&query.view);
+    let filters = TimelineFilters::from_query(&query)?;
 
-    let items = if let Some(before_id) = query.before_id {
+    if let Some(before_id) = query.before_id {
         let before_id_exists = sqlx::query_scalar!(
             "SELECT 1 as found FROM feed_items WHERE id = ? LIMIT 1",
             before_id
@@ -206,110 +416,174 @@ async fn get_timeline(
                 before_id
             )));
         }
+    }
 
-        if view == TimelineView::Compact {
-            let query_str =
-                build_timeline_query(TimelineView::Compact, query.unread == Some(true), true);
-            let compact_items = sqlx::query_as::<_, TimelineCompactItem>(&query_str)
-                .bind(before_id)
-                .bind(limit)
-                .fetch_all(&state.db)
-                .await
-                .map_err(|e| {
-                    AppError::Internal(anyhow::anyhow!("Failed to fetch timeline: {}", e))
-                })?;
-            TimelineResponse::Compact(compact_items)
-        } else {
-            #[derive(Debug, FromRow)]
-            struct FeedItemRow {
-                id: i64,
-                feed_id: i64,
-                feed_title: Option<String>,
-                title: String,
-                description: Option<String>,
-                link: String,
-                pub_date: Option<String>,
-                read_at: Option<String>,
-            }
+    let items = match view {
+        TimelineView::Compact => TimelineResponse::Compact(
+            fetch_timeline(&state.db, view, &filters, query.before_id, limit).await?,
+        ),
+        TimelineView::Full => TimelineResponse::Full(
+            fetch_timeline(&state.db, view, &filters, query.before_id, limit).await?,
+        ),
+    };
 
-            let query_str =
-                build_timeline_query(TimelineView::Full, query.unread == Some(true), true);
-            let full_items: Vec<FeedItemResponse> = sqlx::query_as::<_, FeedItemRow>(&query_str)
-                .bind(before_id)
-                .bind(limit)
-                .fetch_all(&state.db)
+    Ok(Json(items))
+}
+
+/// Turns user input into an FTS5 query matching every word, so quotes and FTS
+/// operators in the input are searched for literally instead of parsed.
+fn build_match_query(input: &str) -> Option<String> {
+    let terms: Vec<String> = input
+        .split_whitespace()
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect();
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+/// Ranked full-text search over item titles and descriptions. Pass the `cursor` of
+/// the last result to get the next page.
+///
+/// Pages are keyed by the rank and id of the last result. Later pages only consider
+/// items that existed when the first page was loaded, and compare against the last
+/// result's current rank (its rank when returned, if it has since been deleted), so
+/// a refresh in between neither shifts results across pages nor breaks the cursor.
+async fn search_timeline(
+    State(state): State<crate::AppState>,
+    Query(query): Query<SearchQuery>,
+) -> Result<Json<Vec<SearchResult>>> {
+    let input = query.q.as_deref().unwrap_or_default().trim();
+    if input.chars().count() > MAX_SEARCH_QUERY_LENGTH {
+        return Err(AppError::Validation(format!(
+            "Search query must be at most {} characters",
+            MAX_SEARCH_QUERY_LENGTH
+        )));
+    }
+    let Some(match_query) = build_match_query(input) else {
+        return Err(AppError::Validation(
+            "Search query must not be empty".to_string(),
+        ));
+    };
+    let limit = query.limit.map(|limit| limit.clamp(1, 200)).unwrap_or(50);
+
+    let cursor = match query.cursor.as_deref() {
+        Some(raw) => SearchCursor::decode(raw)
+            .ok_or_else(|| AppError::Validation("Invalid search cursor".to_string()))?,
+        None => {
+            let max_id = sqlx::query_scalar!("SELECT MAX(id) FROM feed_items")
+                .fetch_one(&state.db)
                 .await
                 .map_err(|e| {
-                    AppError::Internal(anyhow::anyhow!("Failed to fetch timeline: {}", e))
+                    AppError::Internal(anyhow::anyhow!("Failed to search timeline: {}", e))
                 })?
-                .into_iter()
-                .map(|row| FeedItemResponse {
-                    id: row.id,
-                    feed_id: row.feed_id,
-                    feed_title: row.feed_title,
-                    title: row.title,
-                    description: row.description,
-                    link: row.link,
-                    pub_date: row.pub_date,
-                    read_at: row.read_at,
-                })
-                .collect();
-            TimelineResponse::Full(full_items)
-        }
-    } else if view == TimelineView::Compact {
-        let query_str =
-            build_timeline_query(TimelineView::Compact, query.unread == Some(true), false);
-        let compact_items = sqlx::query_as::<_, TimelineCompactItem>(&query_str)
-            .bind(limit)
-            .fetch_all(&state.db)
-            .await
-            .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to fetch timeline: {}", e)))?;
-        TimelineResponse::Compact(compact_items)
-    } else {
-        #[derive(Debug, FromRow)]
-        struct FeedItemRow {
-            id: i64,
-            feed_id: i64,
-            feed_title: Option<String>,
-            title: String,
-            description: Option<String>,
-            link: String,
-            pub_date: Option<String>,
-            read_at: Option<String>,
+                .unwrap_or_default();
+            SearchCursor {
+                max_id,
+                after: None,
+            }
         }
-
-        let query_str = build_timeline_query(TimelineView::Full, query.unread == Some(true), false);
-        let full_items: Vec<FeedItemResponse> = sqlx::query_as::<_, FeedItemRow>(&query_str)
-            .bind(limit)
-            .fetch_all(&state.db)
-            .await
-            .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to fetch timeline: {}", e)))?
-            .into_iter()
-            .map(|row| FeedItemResponse {
-                id: row.id,
-                feed_id: row.feed_id,
-                feed_title: row.feed_title,
-                title: row.title,
-                description: row.description,
-                link: row.link,
-                pub_date: row.pub_date,
-                read_at: row.read_at,
-            })
-            .collect();
-        TimelineResponse::Full(full_items)
     };
+    let (after_rank, after_id) = cursor.after.unzip();
 
-    Ok(Json(items))
+    let rows = sqlx::query_as::<_, SearchRow>(
+        r#"
+        WITH hits AS (
+            SELECT
+                fi.id,
+                fi.feed_id,
+                COALESCE(f.custom_title, f.title) as feed_title,
+                fi.title,
+                fi.link,
+                CAST(fi.pub_date AS TEXT) as pub_date,
+                CAST(fi.read_at AS TEXT) as read_at,
+                CAST(fi.starred_at AS TEXT) as starred_at,
+                highlight(feed_items_fts, 0, ?4, ?5) as title_highlight,
+                snippet(feed_items_fts, 1, ?4, ?5, '…', 16) as snippet,
+                feed_items_fts.rank as rank
+            FROM feed_items_fts
+            JOIN feed_items fi ON fi.id = feed_items_fts.rowid
+            JOIN feeds f ON f.id = fi.feed_id
+            WHERE feed_items_fts MATCH ?1 AND feed_items_fts.rowid <= ?3
+        )
+        , cursor AS (
+            SELECT COALESCE((SELECT rank FROM hits WHERE id = ?7), ?6) as rank
+        )
+        SELECT hits.* FROM hits, cursor
+        WHERE ?7 IS NULL
+           OR hits.rank > cursor.rank
+           OR (hits.rank = cursor.rank AND hits.id < ?7)
+        ORDER BY hits.rank ASC, hits.id DESC
+        LIMIT ?2
+        "#,
+    )
+    .bind(&match_query)
+    .bind(limit)
+    .bind(cursor.max_id)
+    .bind(HIGHLIGHT_START)
+    .bind(HIGHLIGHT_END)
+    .bind(after_rank)
+    .bind(after_id)
+    .fetch_all(&state.db)
+    .await
+    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to search timeline: {}", e)))?;
+
+    let results = rows
+        .into_iter()
+        .map(|row| SearchResult {
+            cursor: SearchCursor {
+                max_id: cursor.max_id,
+                after: Some((row.rank, row.id)),
+            }
+            .encode(),
+            id: row.id,
+            feed_id: row.feed_id,
+            feed_title: row.feed_title,
+            title: row.title,
+            link: row.link,
+            pub_date: row.pub_date,
+            read_at: row.read_at,
+            starred_at: row.starred_at,
+            title_highlight: render_highlight(&row.title_highlight),
+            snippet: row.snippet.as_deref().map(render_highlight),
+            rank: row.rank,
+        })
+        .collect();
+
+    Ok(Json(results))
+}
+
+/// Escapes highlighted FTS output for HTML, then turns the highlight markers into
+/// `<mark>` tags. Feed titles and descriptions are untrusted, so only the tags added
+/// here are ever markup.
+fn render_highlight(text: &str) -> String {
+    let mut html = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\u{2}' => html.push_str("<mark>"),
+            '\u{3}' => html.push_str("</mark>"),
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#39;"),
+            c => html.push(c),
+        }
+    }
+    html
 }
 
+/// Marks an item read. Items that are already read keep their original `read_at`.
 async fn mark_read(
     State(state): State<crate::AppState>,
     Path(id): Path<i64>,
 ) -> Result<StatusCode> {
     let result = sqlx::query!(
         r#"
-        UPDATE feed_items SET read_at = datetime('now')
-        WHERE id = $1 AND read_at IS NULL
+        UPDATE feed_items SET read_at = COALESCE(read_at, datetime('now'))
+        WHERE id = $1
         "#,
         id
     )
@@ -318,17 +592,175 @@ async fn mark_read(
     .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to mark read: {}", e)))?;
 
     if result.rows_affected() == 0 {
-        return Err(AppError::NotFound(format!(
-            "Item with id {} not found or already read",
-            id
+        return Err(AppError::NotFound(format!("Item with id {} not found", id)));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn mark_unread(
+    State(state): State<crate::AppState>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE feed_items SET read_at = NULL
+        WHERE id = $1
+        "#,
+        id
+    )
+    .execute(&state.db)
+    .await
+    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to mark unread: {}", e)))?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound(format!("Item with id {} not found", id)));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Sets the read state of several items at once. Unknown ids are reported rather
+/// than failing the request, and `updated` only counts items whose state changed.
+async fn bulk_mark_read(
+    State(state): State<crate::AppState>,
+    Json(payload): Json<BulkReadRequest>,
+) -> Result<Json<BulkReadResponse>> {
+    if payload.ids.len() > MAX_BULK_ITEM_IDS {
+        return Err(AppError::Validation(format!(
+            "At most {} item ids can be given",
+            MAX_BULK_ITEM_IDS
         )));
     }
+    let ids = serde_json::to_string(&payload.ids)
+        .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to encode item ids: {}", e)))?;
+
+    let result = if payload.read {
+        sqlx::query!(
+            r#"
+            UPDATE feed_items SET read_at = datetime('now')
+            WHERE id IN (SELECT value FROM json_each($1)) AND read_at IS NULL
+            "#,
+            ids
+        )
+        .execute(&state.db)
+        .await
+    } else {
+        sqlx::query!(
+            r#"
+            UPDATE feed_items SET read_at = NULL
+            WHERE id IN (SELECT value FROM json_each($1)) AND read_at IS NOT NULL
+            "#,
+            ids
+        )
+        .execute(&state.db)
+        .await
+    }
+    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to update read state: {}", e)))?;
+
+    let existing = sqlx::query_scalar!(
+        r#"
+        SELECT id as "id!" FROM feed_items
+        WHERE id IN (SELECT value FROM json_each($1))
+        "#,
+        ids
+    )
+    .fetch_all(&state.db)
+    .await
+    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to update read state: {}", e)))?;
+
+    let mut not_found: Vec<i64> = payload
+        .ids
+        .into_iter()
+        .filter(|id| !existing.contains(id))
+        .collect();
+    not_found.sort_unstable();
+    not_found.dedup();
+
+    Ok(Json(BulkReadResponse {
+        updated: result.rows_affected(),
+        not_found,
+    }))
+}
+
+/// Marks every unread item read, optionally limited to one feed, one category and
+/// items published before `older_than`. Items without a publication date are
+/// skipped when a cutoff is given.
+async fn mark_all_read(
+    State(state): State<crate::AppState>,
+    Json(payload): Json<MarkAllReadRequest>,
+) -> Result<Json<MarkAllReadResponse>> {
+    let older_than = payload
+        .older_than
+        .map(|cutoff| cutoff.format("%Y-%m-%d %H:%M:%S").to_string());
+
+    let result = sqlx::query!(
+        r#"
+        UPDATE feed_items SET read_at = datetime('now')
+        WHERE read_at IS NULL
+            AND ($1 IS NULL OR feed_id = $1)
+            AND ($2 IS NULL OR feed_id IN (SELECT id FROM feeds WHERE category = $2))
+            AND ($3 IS NULL OR (pub_date IS NOT NULL AND pub_date < $3))
+        "#,
+        payload.feed_id,
+        payload.category,
+        older_than
+    )
+    .execute(&state.db)
+    .await
+    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to mark all read: {}", e)))?;
+
+    Ok(Json(MarkAllReadResponse {
+        updated: result.rows_affected(),
+    }))
+}
+
+async fn star_item(
+    State(state): State<crate::AppState>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE feed_items SET starred_at = COALESCE(starred_at, datetime('now'))
+        WHERE id = $1
+        "#,
+        id
+    )
+    .execute(&state.db)
+    .await
+    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to star item: {}", e)))?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound(format!("Item with id {} not found", id)));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn unstar_item(
+    State(state): State<crate::AppState>,
+    Path(id): Path<i64>,
+) -> Result<StatusCode> {
+    let result = sqlx::query!(
+        r#"
+        UPDATE feed_items SET starred_at = NULL
+        WHERE id = $1
+        "#,
+        id
+    )
+    .execute(&state.db)
+    .await
+    .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to unstar item: {}", e)))?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound(format!("Item with id {} not found", id)));
+    }
 
     Ok(StatusCode::NO_CONTENT)
 }
 
 async fn refresh_feeds(State(state): State<crate::AppState>) -> Result<Json<RefreshSummary>> {
-    let results = feed::refresh_all_feeds(&state.db)
+    let results = feed::refresh_all_feeds(&state.db, &state.feed_fetcher)
         .await
         .map_err(|e| AppError::Internal(anyhow::anyhow!("Failed to refresh feeds: {}", e)))?;
 
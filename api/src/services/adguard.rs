@@ -1,14 +1,24 @@
+use std::net::IpAddr;
 use std::time::Duration;
 
 use base64::Engine;
+use chrono::{DateTime, SecondsFormat, Utc};
 use reqwest::header::HeaderMap;
 use reqwest::{Client, ClientBuilder};
 use serde::{Deserialize, Serialize};
 
+/// Filter list id AdGuard reports for rules coming from the user's custom rules.
+const USER_RULES_FILTER_ID: i64 = 0;
+
+/// Instance name used for the AdGuard configured through `ADGUARD_HOST`.
+pub const PRIMARY_INSTANCE_NAME: &str = "primary";
+
 #[derive(Debug, Clone)]
 pub struct AdguardService {
     client: Client,
     base_url: String,
+    name: String,
+    replicas: Vec<AdguardService>,
 }
 
 impl AdguardService {
@@ -29,319 +39,2583 @@ impl AdguardService {
             .map_err(|e| anyhow::anyhow!("Invalid AdGuard auth header: {e}"))?;
         headers.insert(reqwest::header::AUTHORIZATION, auth_value);
 
-        let mut client_builder = ClientBuilder::new()
-            .timeout(Duration::from_secs(30))
-            .default_headers(headers);
+        let mut client_builder = ClientBuilder::new()
+            .timeout(Duration::from_secs(30))
+            .default_headers(headers);
+
+        if insecure_tls {
+            client_builder = client_builder.danger_accept_invalid_certs(true);
+        }
+
+        let client = client_builder.build()?;
+
+        let base_url = host.trim_end_matches('/').to_string();
+
+        Ok(Self {
+            client,
+            base_url,
+            name: PRIMARY_INSTANCE_NAME.to_string(),
+            replicas: Vec::new(),
+        })
+    }
+
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    /// Attaches replica instances that mirror this one. Protection toggles fan out to
+    /// replicas; other settings reach them through `adguard_sync`.
+    pub fn with_replicas(mut self, replicas: Vec<AdguardService>) -> Self {
+        self.replicas = replicas;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn replicas(&self) -> &[AdguardService] {
+        &self.replicas
+    }
+
+    /// Reports status for this instance followed by each replica.
+    pub async fn get_instance_statuses(&self) -> Vec<InstanceStatus> {
+        let instances = std::iter::once(self).chain(&self.replicas);
+        futures_util::future::join_all(instances.map(|instance| async move {
+            let (status, error) = match instance.get_status().await {
+                Ok(status) => (Some(status), None),
+                Err(e) => (None, Some(e.to_string())),
+            };
+            InstanceStatus {
+                name: instance.name.clone(),
+                primary: std::ptr::eq(instance, self),
+                reachable: status.is_some(),
+                status,
+                error,
+            }
+        }))
+        .await
+    }
+
+    pub async fn get_status(&self) -> Result<AdguardStatusResponse, anyhow::Error> {
+        let raw: RawAdguardStatusResponse = self.get_json("/control/status").await?;
+        let protection = ProtectionState::from_raw(&raw, Utc::now());
+        let protection_disabled_until = match &protection {
+            ProtectionState::Paused { until, .. } => Some(until.clone()),
+            _ => None,
+        };
+        Ok(AdguardStatusResponse {
+            // Agrees with `protection` once a pause has run out, even before AdGuard
+            // has flipped its own flag back.
+            protection_enabled: protection == ProtectionState::Enabled,
+            protection,
+            protection_disabled_until,
+            version: raw.version,
+            running: raw.running,
+            replicas: Vec::new(),
+        })
+    }
+
+    /// Turns protection on or off. With `enabled = false` and a `duration`, AdGuard
+    /// re-enables protection by itself once the duration has passed.
+    pub async fn set_protection(
+        &self,
+        enabled: bool,
+        duration: Option<Duration>,
+    ) -> Result<AdguardStatusResponse, anyhow::Error> {
+        let mut body = serde_json::json!({
+            "enabled": enabled
+        });
+        if let Some(duration) = duration {
+            // AdGuard takes the pause duration in milliseconds.
+            body["duration"] = u64::try_from(duration.as_millis())?.into();
+        }
+
+        self.post_json("/control/protection", &body).await?;
+
+        let mut status = self.get_status().await?;
+        status.replicas = futures_util::future::join_all(
+            self.replicas
+                .iter()
+                .map(|replica| replica.apply_replica_protection(&body)),
+        )
+        .await;
+        Ok(status)
+    }
+
+    async fn apply_replica_protection(&self, body: &serde_json::Value) -> ReplicaProtectionResult {
+        let outcome = match self.post_json("/control/protection", body).await {
+            Ok(()) => self.get_status().await,
+            Err(e) => Err(e),
+        };
+        match outcome {
+            Ok(status) => ReplicaProtectionResult {
+                name: self.name.clone(),
+                protection_enabled: Some(status.protection_enabled),
+                error: None,
+            },
+            Err(e) => {
+                tracing::warn!(replica = %self.name, error = %e, "Failed to set protection on AdGuard replica");
+                ReplicaProtectionResult {
+                    name: self.name.clone(),
+                    protection_enabled: None,
+                    error: Some(e.to_string()),
+                }
+            }
+        }
+    }
+
+    pub async fn get_user_rules(&self) -> Result<Vec<String>, anyhow::Error> {
+        let raw: RawFilteringStatus = self.get_json("/control/filtering/status").await?;
+        Ok(raw
+            .user_rules
+            .into_iter()
+            .filter(|rule| !rule.trim().is_empty())
+            .collect())
+    }
+
+    pub async fn set_user_rules(&self, rules: &[String]) -> Result<(), anyhow::Error> {
+        let body = serde_json::json!({ "rules": rules });
+        self.post_json("/control/filtering/set_rules", &body).await
+    }
+
+    /// Appends `rule` to the user rules unless an identical rule is already present.
+    pub async fn add_user_rule(&self, rule: &str) -> Result<Vec<String>, anyhow::Error> {
+        let mut rules = self.get_user_rules().await?;
+        if !rules.iter().any(|existing| existing == rule) {
+            rules.push(rule.to_string());
+            self.set_user_rules(&rules).await?;
+        }
+        Ok(rules)
+    }
+
+    /// Removes every occurrence of `rule` from the user rules.
+    ///
+    /// Returns `None` when the rule was not present, leaving AdGuard untouched.
+    pub async fn remove_user_rule(&self, rule: &str) -> Result<Option<Vec<String>>, anyhow::Error> {
+        let mut rules = self.get_user_rules().await?;
+        let before = rules.len();
+        rules.retain(|existing| existing != rule);
+        if rules.len() == before {
+            return Ok(None);
+        }
+        self.set_user_rules(&rules).await?;
+        Ok(Some(rules))
+    }
+
+    /// Blocks `domain` and its subdomains, dropping any allow rule for the same domain.
+    pub async fn block_domain(&self, domain: &str) -> Result<Vec<String>, anyhow::Error> {
+        self.replace_domain_rule(&block_rule(domain), &allow_rule(domain))
+            .await
+    }
+
+    /// Allows `domain` and its subdomains, dropping any block rule for the same domain.
+    pub async fn allow_domain(&self, domain: &str) -> Result<Vec<String>, anyhow::Error> {
+        self.replace_domain_rule(&allow_rule(domain), &block_rule(domain))
+            .await
+    }
+
+    async fn replace_domain_rule(
+        &self,
+        rule: &str,
+        opposite: &str,
+    ) -> Result<Vec<String>, anyhow::Error> {
+        let mut rules = self.get_user_rules().await?;
+        let before = rules.clone();
+        rules.retain(|existing| existing != opposite);
+        if !rules.iter().any(|existing| existing == rule) {
+            rules.push(rule.to_string());
+        }
+        if rules != before {
+            self.set_user_rules(&rules).await?;
+        }
+        Ok(rules)
+    }
+
+    pub async fn get_filter_lists(&self) -> Result<Vec<FilterList>, anyhow::Error> {
+        let raw: RawFilteringStatus = self.get_json("/control/filtering/status").await?;
+        let blocklists = raw.filters.into_iter().map(|f| f.into_filter_list(false));
+        let allowlists = raw
+            .whitelist_filters
+            .into_iter()
+            .map(|f| f.into_filter_list(true));
+        Ok(blocklists.chain(allowlists).collect())
+    }
+
+    pub async fn find_filter_list(&self, id: i64) -> Result<Option<FilterList>, anyhow::Error> {
+        Ok(self
+            .get_filter_lists()
+            .await?
+            .into_iter()
+            .find(|list| list.id == id))
+    }
+
+    pub async fn add_filter_list(
+        &self,
+        name: &str,
+        url: &str,
+        whitelist: bool,
+    ) -> Result<(), anyhow::Error> {
+        let body = serde_json::json!({
+            "name": name,
+            "url": url,
+            "whitelist": whitelist
+        });
+        self.post_json("/control/filtering/add_url", &body).await
+    }
+
+    pub async fn update_filter_list(
+        &self,
+        list: &FilterList,
+        name: &str,
+        enabled: bool,
+    ) -> Result<(), anyhow::Error> {
+        let body = serde_json::json!({
+            "url": list.url,
+            "whitelist": list.whitelist,
+            "data": {
+                "name": name,
+                "url": list.url,
+                "enabled": enabled
+            }
+        });
+        self.post_json("/control/filtering/set_url", &body).await
+    }
+
+    pub async fn remove_filter_list(&self, list: &FilterList) -> Result<(), anyhow::Error> {
+        let body = serde_json::json!({
+            "url": list.url,
+            "whitelist": list.whitelist
+        });
+        self.post_json("/control/filtering/remove_url", &body).await
+    }
+
+    /// Forces AdGuard to re-download its filter lists, returning how many changed.
+    pub async fn refresh_filter_lists(&self, whitelist: bool) -> Result<u64, anyhow::Error> {
+        let body = serde_json::json!({ "whitelist": whitelist });
+        let raw: RawFilterRefreshResponse = self
+            .post_json_for("/control/filtering/refresh", &body)
+            .await?;
+        Ok(raw.updated)
+    }
+
+    /// Asks AdGuard how it would filter `name`, resolving matched rules to list names.
+    pub async fn check_host(&self, name: &str) -> Result<CheckHostResponse, anyhow::Error> {
+        let mut url =
+            reqwest::Url::parse(&format!("{}/control/filtering/check_host", self.base_url))?;
+        url.query_pairs_mut().append_pair("name", name);
+        let raw: RawCheckHostResponse = self
+            .client
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let lists = self.get_filter_lists().await?;
+
+        Ok(CheckHostResponse {
+            name: name.to_string(),
+            blocked: is_blocked_reason(&raw.reason),
+            rules: resolve_rules(raw.rules, &lists),
+            reason: raw.reason,
+            service_name: raw.service_name.filter(|value| !value.is_empty()),
+            cname: raw.cname.filter(|value| !value.is_empty()),
+            ip_addrs: raw.ip_addrs.unwrap_or_default(),
+        })
+    }
+
+    /// Returns recent DNS queries, newest first, with the rules that decided each one.
+    pub async fn get_query_log(
+        &self,
+        query: &QueryLogQuery,
+    ) -> Result<QueryLogResponse, anyhow::Error> {
+        let mut url = reqwest::Url::parse(&format!("{}/control/querylog", self.base_url))?;
+        {
+            let mut pairs = url.query_pairs_mut();
+            pairs.append_pair("limit", &query.limit.to_string());
+            if let Some(older_than) = &query.older_than {
+                pairs.append_pair("older_than", older_than);
+            }
+            if let Some(search) = &query.search {
+                pairs.append_pair("search", search);
+            }
+            if query.blocked_only {
+                pairs.append_pair("response_status", "blocked");
+            }
+        }
+        let raw: RawQueryLogResponse = self
+            .client
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let lists = self.get_filter_lists().await?;
+
+        let entries = raw
+            .data
+            .into_iter()
+            .map(|entry| QueryLogEntry {
+                time: entry.time,
+                name: entry.question.name,
+                query_type: entry.question.query_type,
+                client: entry.client,
+                blocked: is_blocked_reason(&entry.reason),
+                rules: resolve_rules(entry.rules, &lists),
+                reason: entry.reason,
+                elapsed_ms: entry.elapsed_ms,
+            })
+            .collect();
+
+        Ok(QueryLogResponse {
+            entries,
+            oldest: raw.oldest.filter(|value| !value.is_empty()),
+        })
+    }
+
+    pub async fn get_rewrites(&self) -> Result<Vec<RewriteEntry>, anyhow::Error> {
+        let raw: Option<Vec<RewriteEntry>> = self.get_json("/control/rewrite/list").await?;
+        Ok(raw.unwrap_or_default())
+    }
+
+    pub async fn add_rewrite(&self, entry: &RewriteEntry) -> Result<(), anyhow::Error> {
+        let body = serde_json::to_value(entry)?;
+        self.post_json("/control/rewrite/add", &body).await
+    }
+
+    pub async fn update_rewrite(
+        &self,
+        target: &RewriteEntry,
+        update: &RewriteEntry,
+    ) -> Result<(), anyhow::Error> {
+        let body = serde_json::json!({ "target": target, "update": update });
+        self.put_json("/control/rewrite/update", &body).await
+    }
+
+    pub async fn delete_rewrite(&self, entry: &RewriteEntry) -> Result<(), anyhow::Error> {
+        let body = serde_json::to_value(entry)?;
+        self.post_json("/control/rewrite/delete", &body).await
+    }
+
+    pub async fn get_available_services(&self) -> Result<Vec<BlockableService>, anyhow::Error> {
+        let raw: RawBlockableServices = self.get_json("/control/blocked_services/all").await?;
+        Ok(raw.blocked_services)
+    }
+
+    pub async fn get_blocked_services(&self) -> Result<BlockedServices, anyhow::Error> {
+        let mut raw: serde_json::Value = self.get_json("/control/blocked_services/get").await?;
+        if let Some(schedule) = raw.get_mut("schedule") {
+            convert_schedule_ranges(schedule, RangeUnit::Seconds);
+        }
+        let mut blocked: BlockedServices = serde_json::from_value(raw)?;
+        blocked.ids.sort();
+        Ok(blocked)
+    }
+
+    pub async fn set_blocked_services(
+        &self,
+        blocked: &BlockedServices,
+    ) -> Result<(), anyhow::Error> {
+        let mut body = serde_json::to_value(blocked)?;
+        if let Some(schedule) = body.get_mut("schedule") {
+            convert_schedule_ranges(schedule, RangeUnit::Millis);
+        }
+        self.put_json("/control/blocked_services/update", &body)
+            .await
+    }
+
+    pub async fn get_clients(&self) -> Result<ClientsResponse, anyhow::Error> {
+        let raw: RawClientsResponse = self.get_json("/control/clients").await?;
+        let clients = raw
+            .clients
+            .unwrap_or_default()
+            .into_iter()
+            .map(serde_json::from_value)
+            .collect::<Result<_, _>>()?;
+        Ok(ClientsResponse {
+            clients,
+            runtime_clients: raw.auto_clients.unwrap_or_default(),
+            supported_tags: raw.supported_tags.unwrap_or_default(),
+        })
+    }
+
+    /// Applies `update` to the persistent client called `name`.
+    ///
+    /// AdGuard replaces the whole client on update, so the current client is fetched
+    /// and only the requested fields are changed. Returns `None` if no client matches.
+    pub async fn update_client(
+        &self,
+        name: &str,
+        update: &ClientSettingsUpdate,
+    ) -> Result<Option<PersistentClient>, anyhow::Error> {
+        let Some(mut data) = self
+            .get_raw_clients()
+            .await?
+            .into_iter()
+            .find(|client| client["name"] == name)
+        else {
+            return Ok(None);
+        };
+
+        update.apply(&mut data);
+        self.replace_client(name, &data).await?;
+
+        Ok(Some(serde_json::from_value(data)?))
+    }
+
+    /// Persistent clients exactly as AdGuard returns them, for lossless copying.
+    pub async fn get_raw_clients(&self) -> Result<Vec<serde_json::Value>, anyhow::Error> {
+        let raw: RawClientsResponse = self.get_json("/control/clients").await?;
+        Ok(raw.clients.unwrap_or_default())
+    }
+
+    pub async fn add_client(&self, data: &serde_json::Value) -> Result<(), anyhow::Error> {
+        self.post_json("/control/clients/add", data).await
+    }
+
+    pub async fn replace_client(
+        &self,
+        name: &str,
+        data: &serde_json::Value,
+    ) -> Result<(), anyhow::Error> {
+        let body = serde_json::json!({ "name": name, "data": data });
+        self.post_json("/control/clients/update", &body).await
+    }
+
+    pub async fn delete_client(&self, name: &str) -> Result<(), anyhow::Error> {
+        let body = serde_json::json!({ "name": name });
+        self.post_json("/control/clients/delete", &body).await
+    }
+
+    /// Returns DHCP server state with each lease annotated by its persistent client name.
+    pub async fn get_dhcp_status(&self) -> Result<DhcpStatus, anyhow::Error> {
+        let raw: RawDhcpStatus = self.get_json("/control/dhcp/status").await?;
+        let clients = self.get_clients().await?.clients;
+        let client_name = |mac: &str, ip: &str| {
+            clients
+                .iter()
+                .find(|client| {
+                    client
+                        .ids
+                        .iter()
+                        .any(|id| id.eq_ignore_ascii_case(mac) || id == ip)
+                })
+                .map(|client| client.name.clone())
+        };
+
+        let leases = raw
+            .leases
+            .unwrap_or_default()
+            .into_iter()
+            .map(|lease| DhcpLease {
+                client_name: client_name(&lease.mac, &lease.ip),
+                mac: lease.mac,
+                ip: lease.ip,
+                hostname: lease.hostname,
+                expires: lease.expires.filter(|value| !value.is_empty()),
+                is_static: false,
+            })
+            .chain(
+                raw.static_leases
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|lease| DhcpLease {
+                        client_name: client_name(&lease.mac, &lease.ip),
+                        mac: lease.mac,
+                        ip: lease.ip,
+                        hostname: lease.hostname,
+                        expires: None,
+                        is_static: true,
+                    }),
+            )
+            .collect();
+
+        Ok(DhcpStatus {
+            config: DhcpConfig {
+                enabled: raw.enabled,
+                interface_name: raw.interface_name,
+                v4: raw.v4,
+                v6: raw.v6,
+            },
+            leases,
+        })
+    }
+
+    pub async fn set_dhcp_config(&self, config: &DhcpConfig) -> Result<(), anyhow::Error> {
+        let mut body = serde_json::to_value(config)?;
+        // AdGuard calls the field `lease_duration`; the unit is already seconds.
+        for family in ["v4", "v6"] {
+            if let Some(settings) = body.get_mut(family).and_then(|v| v.as_object_mut())
+                && let Some(duration) = settings.remove("lease_duration_seconds")
+            {
+                settings.insert("lease_duration".to_string(), duration);
+            }
+        }
+        self.post_json("/control/dhcp/set_config", &body).await
+    }
+
+    pub async fn add_static_lease(&self, lease: &StaticLease) -> Result<(), anyhow::Error> {
+        let body = serde_json::to_value(lease)?;
+        self.post_json("/control/dhcp/add_static_lease", &body)
+            .await
+    }
+
+    pub async fn update_static_lease(&self, lease: &StaticLease) -> Result<(), anyhow::Error> {
+        let body = serde_json::to_value(lease)?;
+        self.post_json("/control/dhcp/update_static_lease", &body)
+            .await
+    }
+
+    pub async fn remove_static_lease(&self, lease: &StaticLease) -> Result<(), anyhow::Error> {
+        let body = serde_json::to_value(lease)?;
+        self.post_json("/control/dhcp/remove_static_lease", &body)
+            .await
+    }
+
+    pub async fn get_upstream_config(&self) -> Result<UpstreamConfig, anyhow::Error> {
+        self.get_json("/control/dns_info").await
+    }
+
+    pub async fn set_upstream_config(&self, config: &UpstreamConfig) -> Result<(), anyhow::Error> {
+        let mut body = serde_json::to_value(config)?;
+        body["upstream_mode"] = config.upstream_mode.as_adguard_value().into();
+        self.post_json("/control/dns_config", &body).await
+    }
+
+    /// Has AdGuard resolve a probe query through each upstream without saving anything.
+    pub async fn test_upstreams(
+        &self,
+        config: &UpstreamConfig,
+    ) -> Result<Vec<UpstreamTestResult>, anyhow::Error> {
+        let body = serde_json::json!({
+            "upstream_dns": config.upstream_dns,
+            "bootstrap_dns": config.bootstrap_dns,
+            "fallback_dns": config.fallback_dns,
+            "private_upstream": []
+        });
+        let raw: std::collections::BTreeMap<String, String> = self
+            .post_json_for("/control/test_upstream_dns", &body)
+            .await?;
+        Ok(raw
+            .into_iter()
+            .map(|(upstream, result)| {
+                let ok = result == "OK";
+                UpstreamTestResult {
+                    upstream,
+                    ok,
+                    error: (!ok).then_some(result),
+                }
+            })
+            .collect())
+    }
+
+    pub async fn clear_dns_cache(&self) -> Result<(), anyhow::Error> {
+        self.post_json("/control/cache_clear", &serde_json::json!({}))
+            .await
+    }
+
+    pub async fn get_filtering_policy(&self) -> Result<FilteringPolicy, anyhow::Error> {
+        let (safe_browsing, parental_control, safe_search) = tokio::try_join!(
+            self.get_json::<RawFeatureStatus>("/control/safebrowsing/status"),
+            self.get_json::<RawFeatureStatus>("/control/parental/status"),
+            self.get_safe_search(),
+        )?;
+        Ok(FilteringPolicy {
+            safe_browsing: safe_browsing.enabled,
+            parental_control: parental_control.enabled,
+            safe_search,
+        })
+    }
+
+    pub async fn set_safe_browsing(&self, enabled: bool) -> Result<(), anyhow::Error> {
+        self.set_feature("/control/safebrowsing", enabled).await
+    }
+
+    pub async fn set_parental_control(&self, enabled: bool) -> Result<(), anyhow::Error> {
+        self.set_feature("/control/parental", enabled).await
+    }
+
+    pub async fn get_safe_search(&self) -> Result<SafeSearchSettings, anyhow::Error> {
+        self.get_json("/control/safesearch/status").await
+    }
+
+    pub async fn set_safe_search(
+        &self,
+        settings: &SafeSearchSettings,
+    ) -> Result<(), anyhow::Error> {
+        let body = serde_json::to_value(settings)?;
+        self.put_json("/control/safesearch/settings", &body).await
+    }
+
+    /// Toggles an AdGuard feature exposed as `<prefix>/enable` and `<prefix>/disable`.
+    async fn set_feature(&self, prefix: &str, enabled: bool) -> Result<(), anyhow::Error> {
+        let action = if enabled { "enable" } else { "disable" };
+        self.post_json(&format!("{prefix}/{action}"), &serde_json::json!({}))
+            .await
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(
+        &self,
+        path: &str,
+    ) -> Result<T, anyhow::Error> {
+        let url = format!("{}{}", self.base_url, path);
+        let value = self
+            .client
+            .get(&url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(value)
+    }
+
+    async fn post_json(&self, path: &str, body: &serde_json::Value) -> Result<(), anyhow::Error> {
+        self.send_json(reqwest::Method::POST, path, body).await?;
+        Ok(())
+    }
+
+    async fn put_json(&self, path: &str, body: &serde_json::Value) -> Result<(), anyhow::Error> {
+        self.send_json(reqwest::Method::PUT, path, body).await?;
+        Ok(())
+    }
+
+    async fn post_json_for<T: serde::de::DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &serde_json::Value,
+    ) -> Result<T, anyhow::Error> {
+        Ok(self
+            .send_json(reqwest::Method::POST, path, body)
+            .await?
+            .json()
+            .await?)
+    }
+
+    async fn send_json(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        body: &serde_json::Value,
+    ) -> Result<reqwest::Response, anyhow::Error> {
+        let url = format!("{}{}", self.base_url, path);
+        let response = self.client.request(method, &url).json(body).send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let response_body = response.text().await?;
+            return Err(anyhow::anyhow!(
+                "AdGuard returned status {}: {}",
+                status,
+                response_body
+            ));
+        }
+        Ok(response)
+    }
+}
+
+/// Normalizes and validates a bare domain name such as `ads.example.com`.
+///
+/// Returns the lowercased domain without a trailing dot, or a human-readable reason
+/// the input was rejected.
+pub fn normalize_domain(input: &str) -> Result<String, String> {
+    normalize_domain_labels(input, true)
+}
+
+fn normalize_domain_labels(input: &str, require_dot: bool) -> Result<String, String> {
+    let domain = input.trim().trim_end_matches('.').to_ascii_lowercase();
+    if domain.is_empty() {
+        return Err("domain is required".to_string());
+    }
+    if domain.len() > 253 {
+        return Err("domain must be at most 253 characters".to_string());
+    }
+    if require_dot && !domain.contains('.') {
+        return Err("domain must contain at least one dot".to_string());
+    }
+    for label in domain.split('.') {
+        if label.is_empty() || label.len() > 63 {
+            return Err(format!("invalid domain label in '{domain}'"));
+        }
+        if label.starts_with('-') || label.ends_with('-') {
+            return Err(format!(
+                "domain labels must not start or end with '-' in '{domain}'"
+            ));
+        }
+        if !label
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(format!("domain contains invalid characters: '{domain}'"));
+        }
+    }
+    if domain
+        .rsplit('.')
+        .next()
+        .is_some_and(|tld| tld.chars().all(|c| c.is_ascii_digit()))
+    {
+        return Err(format!(
+            "domain must not end with a numeric label: '{domain}'"
+        ));
+    }
+    Ok(domain)
+}
+
+/// Validates a rewrite domain, allowing a single leading `*.` wildcard label.
+///
+/// Unlike [`normalize_domain`], single-label names such as `nas` are accepted since
+/// they are common on home networks.
+pub fn normalize_rewrite_domain(input: &str) -> Result<String, String> {
+    let trimmed = input.trim();
+    match trimmed.strip_prefix("*.") {
+        Some(rest) => normalize_domain_labels(rest, false).map(|domain| format!("*.{domain}")),
+        None if trimmed.contains('*') => {
+            Err("wildcards are only allowed as a leading '*.' label".to_string())
+        }
+        None => normalize_domain_labels(trimmed, false),
+    }
+}
+
+/// Validates a rewrite answer: an IP address, a CNAME target domain, or the
+/// special `A`/`AAAA` values that keep the upstream records of that type.
+pub fn normalize_rewrite_answer(input: &str) -> Result<String, String> {
+    let answer = input.trim();
+    if answer.is_empty() {
+        return Err("answer is required".to_string());
+    }
+    if answer == "A" || answer == "AAAA" {
+        return Ok(answer.to_string());
+    }
+    if let Ok(ip) = answer.parse::<IpAddr>() {
+        return Ok(ip.to_string());
+    }
+    normalize_domain_labels(answer, false)
+        .map_err(|_| format!("answer must be an IP address or a domain name: '{answer}'"))
+}
+
+/// Normalizes a MAC address to lowercase, colon-separated form.
+pub fn normalize_mac(input: &str) -> Result<String, String> {
+    let octets: Vec<&str> = input.trim().split([':', '-']).collect();
+    if octets.len() != 6
+        || octets
+            .iter()
+            .any(|octet| octet.len() != 2 || !octet.chars().all(|c| c.is_ascii_hexdigit()))
+    {
+        return Err(format!("invalid MAC address: '{}'", input.trim()));
+    }
+    Ok(octets.join(":").to_ascii_lowercase())
+}
+
+/// Validates a DHCP hostname: a single DNS label.
+pub fn normalize_hostname(input: &str) -> Result<String, String> {
+    let hostname = input.trim().to_ascii_lowercase();
+    if hostname.is_empty() || hostname.len() > 63 {
+        return Err("hostname must be between 1 and 63 characters".to_string());
+    }
+    if hostname.starts_with('-')
+        || hostname.ends_with('-')
+        || !hostname
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-')
+    {
+        return Err(format!("invalid hostname: '{hostname}'"));
+    }
+    Ok(hostname)
+}
+
+/// Whether an AdGuard filtering reason means the request was blocked.
+///
+/// Reasons are `NotFiltered*`, `Filtered*` or `Rewrite*`; only `Filtered*` blocks.
+fn is_blocked_reason(reason: &str) -> bool {
+    reason.starts_with("Filtered")
+}
+
+fn resolve_rules(rules: Option<Vec<RawMatchedRule>>, lists: &[FilterList]) -> Vec<MatchedRule> {
+    rules
+        .unwrap_or_default()
+        .into_iter()
+        .map(|rule| {
+            let filter_list_name = if rule.filter_list_id == USER_RULES_FILTER_ID {
+                Some("Custom filtering rules".to_string())
+            } else {
+                lists
+                    .iter()
+                    .find(|list| list.id == rule.filter_list_id)
+                    .map(|list| list.name.clone())
+            };
+            MatchedRule {
+                text: rule.text,
+                filter_list_id: rule.filter_list_id,
+                filter_list_name,
+            }
+        })
+        .collect()
+}
+
+/// AdGuard rule blocking `domain` and all of its subdomains.
+pub fn block_rule(domain: &str) -> String {
+    format!("||{domain}^")
+}
+
+/// AdGuard exception rule unblocking `domain` and all of its subdomains.
+pub fn allow_rule(domain: &str) -> String {
+    format!("@@||{domain}^")
+}
+
+#[derive(Debug, Serialize)]
+pub struct AdguardStatusResponse {
+    pub protection_enabled: bool,
+    pub protection: ProtectionState,
+    /// End of the current pause as RFC 3339 UTC, if protection is paused.
+    pub protection_disabled_until: Option<String>,
+    pub version: String,
+    pub running: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub replicas: Vec<ReplicaProtectionResult>,
+}
+
+/// Protection state with pause times normalized to seconds and UTC timestamps.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum ProtectionState {
+    Enabled,
+    DisabledIndefinitely,
+    Paused {
+        until: String,
+        remaining_seconds: u64,
+    },
+}
+
+impl ProtectionState {
+    fn from_raw(raw: &RawAdguardStatusResponse, now: DateTime<Utc>) -> Self {
+        if raw.protection_enabled {
+            return ProtectionState::Enabled;
+        }
+        // AdGuard reports the remaining pause in milliseconds; prefer it over
+        // `protection_disabled_until` so clock skew between hosts does not matter.
+        let (remaining_ms, until) = match raw.protection_disabled_duration {
+            ms if ms > 0 => (ms, now + chrono::Duration::milliseconds(ms)),
+            _ => match raw
+                .protection_disabled_until
+                .as_deref()
+                .and_then(|value| DateTime::parse_from_rfc3339(value).ok())
+            {
+                Some(until) => {
+                    let until = until.with_timezone(&Utc);
+                    ((until - now).num_milliseconds(), until)
+                }
+                None => return ProtectionState::DisabledIndefinitely,
+            },
+        };
+        // A pause that has run out is about to be lifted by AdGuard.
+        if remaining_ms <= 0 {
+            return ProtectionState::Enabled;
+        }
+        let remaining_seconds = (remaining_ms as u64).div_ceil(1000);
+        ProtectionState::Paused {
+            until: until.to_rfc3339_opts(SecondsFormat::Secs, true),
+            remaining_seconds,
+        }
+    }
+}
+
+/// Outcome of fanning a protection change out to one replica.
+#[derive(Debug, Serialize)]
+pub struct ReplicaProtectionResult {
+    pub name: String,
+    pub protection_enabled: Option<bool>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InstancesResponse {
+    pub instances: Vec<InstanceStatus>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InstanceStatus {
+    pub name: String,
+    pub primary: bool,
+    pub reachable: bool,
+    pub status: Option<AdguardStatusResponse>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserRulesResponse {
+    pub rules: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FilterList {
+    pub id: i64,
+    pub name: String,
+    pub url: String,
+    pub enabled: bool,
+    pub whitelist: bool,
+    pub rules_count: u64,
+    pub last_updated: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FilterListsResponse {
+    pub filters: Vec<FilterList>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FilterRefreshResponse {
+    pub updated: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawFilteringStatus {
+    #[serde(default)]
+    filters: Vec<RawFilterList>,
+    #[serde(default)]
+    whitelist_filters: Vec<RawFilterList>,
+    #[serde(default)]
+    user_rules: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawFilterList {
+    id: i64,
+    #[serde(default)]
+    name: String,
+    url: String,
+    enabled: bool,
+    #[serde(default)]
+    rules_count: u64,
+    last_updated: Option<String>,
+}
+
+impl RawFilterList {
+    fn into_filter_list(self, whitelist: bool) -> FilterList {
+        FilterList {
+            id: self.id,
+            name: self.name,
+            url: self.url,
+            enabled: self.enabled,
+            whitelist,
+            rules_count: self.rules_count,
+            last_updated: self.last_updated.filter(|value| !value.is_empty()),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RawFilterRefreshResponse {
+    #[serde(default)]
+    updated: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MatchedRule {
+    pub text: String,
+    pub filter_list_id: i64,
+    pub filter_list_name: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CheckHostResponse {
+    pub name: String,
+    pub blocked: bool,
+    pub reason: String,
+    pub rules: Vec<MatchedRule>,
+    pub service_name: Option<String>,
+    pub cname: Option<String>,
+    pub ip_addrs: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct QueryLogQuery {
+    pub limit: u32,
+    pub older_than: Option<String>,
+    pub search: Option<String>,
+    pub blocked_only: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QueryLogEntry {
+    pub time: String,
+    pub name: String,
+    pub query_type: String,
+    pub client: String,
+    pub blocked: bool,
+    pub reason: String,
+    pub rules: Vec<MatchedRule>,
+    pub elapsed_ms: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QueryLogResponse {
+    pub entries: Vec<QueryLogEntry>,
+    pub oldest: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RewriteEntry {
+    pub domain: String,
+    pub answer: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RewritesResponse {
+    pub rewrites: Vec<RewriteEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockableService {
+    pub id: String,
+    pub name: String,
+    /// Base64-encoded SVG icon as provided by AdGuard.
+    #[serde(default)]
+    pub icon_svg: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BlockableServicesResponse {
+    pub services: Vec<BlockableService>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BlockedServices {
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub ids: Vec<String>,
+    #[serde(default)]
+    pub schedule: BlockedServicesSchedule,
+}
+
+/// Weekly windows during which service blocking is paused.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BlockedServicesSchedule {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_zone: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mon: Option<DayRange>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tue: Option<DayRange>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wed: Option<DayRange>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thu: Option<DayRange>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fri: Option<DayRange>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sat: Option<DayRange>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sun: Option<DayRange>,
+}
+
+impl BlockedServicesSchedule {
+    pub fn days(&self) -> [(&'static str, Option<&DayRange>); 7] {
+        [
+            ("mon", self.mon.as_ref()),
+            ("tue", self.tue.as_ref()),
+            ("wed", self.wed.as_ref()),
+            ("thu", self.thu.as_ref()),
+            ("fri", self.fri.as_ref()),
+            ("sat", self.sat.as_ref()),
+            ("sun", self.sun.as_ref()),
+        ]
+    }
+}
+
+/// Offsets from midnight in seconds. AdGuard stores them in milliseconds as
+/// `start` and `end`; see [`convert_schedule_ranges`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DayRange {
+    pub start_seconds: u64,
+    pub end_seconds: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum RangeUnit {
+    /// Our `start_seconds`/`end_seconds`.
+    Seconds,
+    /// AdGuard's `start`/`end` in milliseconds.
+    Millis,
+}
+
+/// Rewrites the day ranges of a blocked services schedule in place to `unit`.
+fn convert_schedule_ranges(schedule: &mut serde_json::Value, unit: RangeUnit) {
+    const DAYS: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+    const FIELDS: [(&str, &str); 2] = [("start_seconds", "start"), ("end_seconds", "end")];
+
+    for day in DAYS {
+        let Some(range) = schedule
+            .get_mut(day)
+            .and_then(|range| range.as_object_mut())
+        else {
+            continue;
+        };
+        for (seconds_key, millis_key) in FIELDS {
+            match unit {
+                RangeUnit::Seconds => {
+                    if let Some(millis) = range.remove(millis_key).and_then(|v| v.as_u64()) {
+                        range.insert(seconds_key.to_string(), (millis / 1000).into());
+                    }
+                }
+                RangeUnit::Millis => {
+                    if let Some(seconds) = range.remove(seconds_key).and_then(|v| v.as_u64()) {
+                        range.insert(millis_key.to_string(), (seconds * 1000).into());
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RawBlockableServices {
+    #[serde(default)]
+    blocked_services: Vec<BlockableService>,
+}
+
+fn null_as_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SafeSearchSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub bing: bool,
+    #[serde(default)]
+    pub duckduckgo: bool,
+    #[serde(default)]
+    pub ecosia: bool,
+    #[serde(default)]
+    pub google: bool,
+    #[serde(default)]
+    pub pixabay: bool,
+    #[serde(default)]
+    pub yandex: bool,
+    #[serde(default)]
+    pub youtube: bool,
+}
+
+/// Household-wide filtering features beyond the block lists.
+#[derive(Debug, Serialize)]
+pub struct FilteringPolicy {
+    pub safe_browsing: bool,
+    pub parental_control: bool,
+    pub safe_search: SafeSearchSettings,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawFeatureStatus {
+    enabled: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PersistentClient {
+    pub name: String,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub ids: Vec<String>,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub use_global_settings: bool,
+    #[serde(default)]
+    pub filtering_enabled: bool,
+    #[serde(default)]
+    pub parental_enabled: bool,
+    #[serde(default)]
+    pub safebrowsing_enabled: bool,
+    #[serde(default)]
+    pub safe_search: SafeSearchSettings,
+    #[serde(default)]
+    pub use_global_blocked_services: bool,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub blocked_services: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuntimeClient {
+    pub name: String,
+    pub ip: String,
+    #[serde(default)]
+    pub source: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClientsResponse {
+    pub clients: Vec<PersistentClient>,
+    pub runtime_clients: Vec<RuntimeClient>,
+    pub supported_tags: Vec<String>,
+}
+
+/// Partial update of a persistent client's settings.
+///
+/// Setting any filtering flag without `use_global_settings` switches the client to its
+/// own settings, since AdGuard ignores per-client flags while global settings are used.
+/// `blocked_services` likewise implies `use_global_blocked_services = false`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ClientSettingsUpdate {
+    pub use_global_settings: Option<bool>,
+    pub filtering_enabled: Option<bool>,
+    pub parental_enabled: Option<bool>,
+    pub safebrowsing_enabled: Option<bool>,
+    pub safe_search_enabled: Option<bool>,
+    pub use_global_blocked_services: Option<bool>,
+    pub blocked_services: Option<Vec<String>>,
+    pub tags: Option<Vec<String>>,
+}
+
+impl ClientSettingsUpdate {
+    fn apply(&self, data: &mut serde_json::Value) {
+        let touches_settings = self.filtering_enabled.is_some()
+            || self.parental_enabled.is_some()
+            || self.safebrowsing_enabled.is_some()
+            || self.safe_search_enabled.is_some();
+        let use_global_settings = self
+            .use_global_settings
+            .or(touches_settings.then_some(false));
+        let use_global_blocked_services = self
+            .use_global_blocked_services
+            .or(self.blocked_services.as_ref().map(|_| false));
+
+        let fields = [
+            ("use_global_settings", use_global_settings),
+            ("filtering_enabled", self.filtering_enabled),
+            ("parental_enabled", self.parental_enabled),
+            ("safebrowsing_enabled", self.safebrowsing_enabled),
+            ("use_global_blocked_services", use_global_blocked_services),
+        ];
+        for (key, value) in fields {
+            if let Some(value) = value {
+                data[key] = value.into();
+            }
+        }
+        if let Some(enabled) = self.safe_search_enabled {
+            data["safesearch_enabled"] = enabled.into();
+            if !data["safe_search"].is_object() {
+                data["safe_search"] = serde_json::json!({});
+            }
+            data["safe_search"]["enabled"] = enabled.into();
+        }
+        if let Some(blocked_services) = &self.blocked_services {
+            data["blocked_services"] = serde_json::json!(blocked_services);
+        }
+        if let Some(tags) = &self.tags {
+            data["tags"] = serde_json::json!(tags);
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RawClientsResponse {
+    clients: Option<Vec<serde_json::Value>>,
+    auto_clients: Option<Vec<RuntimeClient>>,
+    supported_tags: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DhcpConfig {
+    pub enabled: bool,
+    #[serde(default)]
+    pub interface_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub v4: Option<DhcpV4Config>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub v6: Option<DhcpV6Config>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DhcpV4Config {
+    #[serde(default)]
+    pub gateway_ip: String,
+    #[serde(default)]
+    pub subnet_mask: String,
+    #[serde(default)]
+    pub range_start: String,
+    #[serde(default)]
+    pub range_end: String,
+    #[serde(default, alias = "lease_duration")]
+    pub lease_duration_seconds: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DhcpV6Config {
+    #[serde(default)]
+    pub range_start: String,
+    #[serde(default, alias = "lease_duration")]
+    pub lease_duration_seconds: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DhcpLease {
+    pub mac: String,
+    pub ip: String,
+    pub hostname: String,
+    pub expires: Option<String>,
+    pub is_static: bool,
+    pub client_name: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DhcpStatus {
+    #[serde(flatten)]
+    pub config: DhcpConfig,
+    pub leases: Vec<DhcpLease>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StaticLease {
+    pub mac: String,
+    pub ip: String,
+    pub hostname: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpstreamMode {
+    /// Older AdGuard releases report load balancing as an empty string.
+    #[default]
+    #[serde(alias = "")]
+    LoadBalance,
+    Parallel,
+    FastestAddr,
+}
+
+impl UpstreamMode {
+    /// The value sent to AdGuard. Only recent releases understand `load_balance`,
+    /// while the empty string means load balancing in all of them.
+    fn as_adguard_value(self) -> &'static str {
+        match self {
+            UpstreamMode::LoadBalance => "",
+            UpstreamMode::Parallel => "parallel",
+            UpstreamMode::FastestAddr => "fastest_addr",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpstreamConfig {
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub upstream_dns: Vec<String>,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub bootstrap_dns: Vec<String>,
+    #[serde(default, deserialize_with = "null_as_default")]
+    pub fallback_dns: Vec<String>,
+    #[serde(default)]
+    pub upstream_mode: UpstreamMode,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UpstreamTestResult {
+    pub upstream: String,
+    pub ok: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UpstreamTestResponse {
+    pub results: Vec<UpstreamTestResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawDhcpStatus {
+    enabled: bool,
+    #[serde(default)]
+    interface_name: String,
+    v4: Option<DhcpV4Config>,
+    v6: Option<DhcpV6Config>,
+    leases: Option<Vec<RawDhcpLease>>,
+    static_leases: Option<Vec<RawDhcpLease>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawDhcpLease {
+    mac: String,
+    ip: String,
+    #[serde(default)]
+    hostname: String,
+    expires: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawMatchedRule {
+    #[serde(default)]
+    text: String,
+    #[serde(default)]
+    filter_list_id: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawCheckHostResponse {
+    reason: String,
+    rules: Option<Vec<RawMatchedRule>>,
+    service_name: Option<String>,
+    cname: Option<String>,
+    ip_addrs: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawQueryLogResponse {
+    #[serde(default)]
+    data: Vec<RawQueryLogEntry>,
+    oldest: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawQueryLogEntry {
+    time: String,
+    question: RawQueryLogQuestion,
+    #[serde(default)]
+    client: String,
+    reason: String,
+    rules: Option<Vec<RawMatchedRule>>,
+    #[serde(rename = "elapsedMs")]
+    elapsed_ms: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawQueryLogQuestion {
+    name: String,
+    #[serde(rename = "type")]
+    query_type: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct RawAdguardStatusResponse {
+    version: String,
+    #[serde(default)]
+    protection_disabled_duration: i64,
+    protection_enabled: bool,
+    protection_disabled_until: Option<String>,
+    running: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use wiremock::matchers::{body_json, header, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn create_test_service_with_url(base_url: &str) -> AdguardService {
+        AdguardService::new(base_url, "test", "test", false).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_get_status_parses_response_correctly() {
+        let mock_server = MockServer::start().await;
+        let raw_response = json!({
+            "version": "v1.5.0",
+            "language": "en",
+            "dns_addresses": ["8.8.8.8", "8.8.4.4"],
+            "dns_port": 53,
+            "http_port": 80,
+            "protection_disabled_duration": 0,
+            "protection_enabled": true,
+            "protection_disabled_until": null,
+            "dhcp_available": true,
+            "running": true
+        });
+
+        Mock::given(method("GET"))
+            .and(path("/control/status"))
+            .and(header("Authorization", "Basic dGVzdDp0ZXN0"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(raw_response))
+            .mount(&mock_server)
+            .await;
+
+        let service = create_test_service_with_url(&mock_server.uri());
+        let status = service.get_status().await.unwrap();
+
+        assert!(status.protection_enabled);
+        assert_eq!(status.version, "v1.5.0");
+        assert!(status.running);
+        assert_eq!(status.protection, ProtectionState::Enabled);
+        assert!(status.protection_disabled_until.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_get_status_reports_expired_pause_as_enabled() {
+        let mock_server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/control/status"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "version": "v0.107.0",
+                "protection_disabled_duration": 0,
+                "protection_enabled": false,
+                "protection_disabled_until": "2024-01-15T10:30:00Z",
+                "running": true
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let service = create_test_service_with_url(&mock_server.uri());
+        let status = service.get_status().await.unwrap();
+
+        assert!(status.protection_enabled);
+        assert_eq!(status.protection, ProtectionState::Enabled);
+        assert_eq!(status.protection_disabled_until, None);
+    }
+
+    #[tokio::test]
+    async fn test_get_status_handles_disabled_state() {
+        let mock_server = MockServer::start().await;
+        let raw_response = json!({
+            "version": "v1.5.0",
+            "language": "en",
+            "dns_addresses": ["8.8.8.8"],
+            "dns_port": 53,
+            "http_port": 80,
+            "protection_disabled_duration": 300000,
+            "protection_enabled": false,
+            "protection_disabled_until": "2024-01-15T10:30:00Z",
+            "dhcp_available": false,
+            "running": true
+        });
+
+        Mock::given(method("GET"))
+            .and(path("/control/status"))
+            .and(header("Authorization", "Basic dGVzdDp0ZXN0"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(raw_response))
+            .mount(&mock_server)
+            .await;
+
+        let service = create_test_service_with_url(&mock_server.uri());
+        let before = Utc::now();
+        let status = service.get_status().await.unwrap();
+
+        assert!(!status.protection_enabled);
+        let ProtectionState::Paused {
+            until,
+            remaining_seconds,
+        } = &status.protection
+        else {
+            panic!("expected a paused state, got {:?}", status.protection);
+        };
+        assert_eq!(*remaining_seconds, 300);
+        assert_eq!(
+            status.protection_disabled_until.as_deref(),
+            Some(until.as_str())
+        );
+        // The end time follows AdGuard's remaining duration, not its clock.
+        let until = DateTime::parse_from_rfc3339(until).unwrap();
+        assert!(until > before + chrono::Duration::seconds(290));
+    }
+
+    #[tokio::test]
+    async fn test_set_protection_builds_correct_enable_body() {
+        let mock_server = MockServer::start().await;
+
+        let status_response = json!({
+            "version": "v1.5.0",
+            "language": "en",
+            "dns_addresses": [],
+            "dns_port": 53,
+            "http_port": 80,
+            "protection_disabled_duration": 0,
+            "protection_enabled": true,
+            "protection_disabled_until": null,
+            "dhcp_available": false,
+            "running": true
+        });
+
+        // Mock both the POST and the subsequent GET
+        Mock::given(method("POST"))
+            .and(path("/control/protection"))
+            .and(header("Authorization", "Basic dGVzdDp0ZXN0"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(status_response.clone()))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/control/status"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(status_response))
+            .mount(&mock_server)
+            .await;
+
+        let service = create_test_service_with_url(&mock_server.uri());
+        let result = service.set_protection(true, None).await.unwrap();
+
+        assert!(result.protection_enabled);
+    }
+
+    #[tokio::test]
+    async fn test_set_protection_builds_correct_disable_body() {
+        let mock_server = MockServer::start().await;
+
+        let status_response = json!({
+            "version": "v1.5.0",
+            "language": "en",
+            "dns_addresses": [],
+            "dns_port": 53,
+            "http_port": 80,
+            "protection_disabled_duration": 0,
+            "protection_enabled": false,
+            "protection_disabled_until": null,
+            "dhcp_available": false,
+            "running": true
+        });
+
+        // Mock both the POST and the subsequent GET
+        Mock::given(method("POST"))
+            .and(path("/control/protection"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(status_response.clone()))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/control/status"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(status_response))
+            .mount(&mock_server)
+            .await;
+
+        let service = create_test_service_with_url(&mock_server.uri());
+        let result = service.set_protection(false, None).await.unwrap();
+
+        assert!(!result.protection_enabled);
+    }
+
+    #[tokio::test]
+    async fn test_set_protection_with_duration() {
+        let mock_server = MockServer::start().await;
+
+        let status_response = json!({
+            "version": "v1.5.0",
+            "language": "en",
+            "dns_addresses": [],
+            "dns_port": 53,
+            "http_port": 80,
+            "protection_disabled_duration": 300000,
+            "protection_enabled": false,
+            "protection_disabled_until": "2024-01-15T10:30:00Z",
+            "dhcp_available": false,
+            "running": true
+        });
+
+        // Mock both the POST and the subsequent GET
+        Mock::given(method("POST"))
+            .and(path("/control/protection"))
+            .and(body_json(json!({ "enabled": false, "duration": 300000 })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(status_response.clone()))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/control/status"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(status_response))
+            .mount(&mock_server)
+            .await;
+
+        let service = create_test_service_with_url(&mock_server.uri());
+        let result = service
+            .set_protection(false, Some(Duration::from_secs(300)))
+            .await
+            .unwrap();
+
+        assert!(!result.protection_enabled);
+        assert!(matches!(
+            result.protection,
+            ProtectionState::Paused {
+                remaining_seconds: 300,
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_set_protection_handles_error_response() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/control/protection"))
+            .respond_with(ResponseTemplate::new(500).set_body_string("Internal Server Error"))
+            .mount(&mock_server)
+            .await;
+
+        let service = create_test_service_with_url(&mock_server.uri());
+        let result = service.set_protection(true, None).await;
+
+        assert!(result.is_err());
+        let err = result.unwrap_err();
+        assert!(err.to_string().contains("500"));
+    }
+
+    #[tokio::test]
+    async fn test_new_strips_trailing_slash() {
+        let service = AdguardService::new("http://localhost:3000/", "user", "pass", false).unwrap();
+        assert_eq!(service.base_url, "http://localhost:3000");
+    }
+
+    #[tokio::test]
+    async fn test_get_status_handles_json_error() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/control/status"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("not valid json"))
+            .mount(&mock_server)
+            .await;
+
+        let service = create_test_service_with_url(&mock_server.uri());
+        let result = service.get_status().await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_status_handles_network_error() {
+        let service = create_test_service_with_url("http://nonexistent:9999");
+        let result = service.get_status().await;
+
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_normalize_domain_lowercases_and_strips_trailing_dot() {
+        assert_eq!(
+            normalize_domain(" Ads.Example.COM. "),
+            Ok("ads.example.com".to_string())
+        );
+    }
+
+    #[test]
+    fn test_normalize_domain_rejects_invalid_input() {
+        assert!(normalize_domain("").is_err());
+        assert!(normalize_domain("localhost").is_err());
+        assert!(normalize_domain("bad..example.com").is_err());
+        assert!(normalize_domain("-bad.example.com").is_err());
+        assert!(normalize_domain("||example.com^").is_err());
+        assert!(normalize_domain("https://example.com").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_allow_domain_replaces_block_rule() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/control/filtering/status"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "enabled": true,
+                "interval": 24,
+                "filters": [],
+                "user_rules": ["||tracker.example^", "||ads.example.com^"]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("POST"))
+            .and(path("/control/filtering/set_rules"))
+            .and(body_json(json!({
+                "rules": ["||tracker.example^", "@@||ads.example.com^"]
+            })))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let service = create_test_service_with_url(&mock_server.uri());
+        let rules = service.allow_domain("ads.example.com").await.unwrap();
+
+        assert_eq!(rules, vec!["||tracker.example^", "@@||ads.example.com^"]);
+    }
+
+    #[tokio::test]
+    async fn test_add_user_rule_skips_existing_rule() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/control/filtering/status"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "user_rules": ["||ads.example.com^"]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("POST"))
+            .and(path("/control/filtering/set_rules"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&mock_server)
+            .await;
+
+        let service = create_test_service_with_url(&mock_server.uri());
+        let rules = service.add_user_rule("||ads.example.com^").await.unwrap();
+
+        assert_eq!(rules, vec!["||ads.example.com^"]);
+    }
+
+    #[tokio::test]
+    async fn test_get_filter_lists_merges_block_and_allow_lists() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/control/filtering/status"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "enabled": true,
+                "filters": [{
+                    "id": 1,
+                    "name": "AdGuard DNS filter",
+                    "url": "https://adguardteam.github.io/HostlistsRegistry/assets/filter_1.txt",
+                    "enabled": true,
+                    "rules_count": 52311,
+                    "last_updated": "2024-01-15T10:30:00Z"
+                }],
+                "whitelist_filters": [{
+                    "id": 7,
+                    "name": "Allowlist",
+                    "url": "https://example.com/allow.txt",
+                    "enabled": false,
+                    "rules_count": 0,
+                    "last_updated": ""
+                }],
+                "user_rules": []
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let service = create_test_service_with_url(&mock_server.uri());
+        let lists = service.get_filter_lists().await.unwrap();
+
+        assert_eq!(lists.len(), 2);
+        assert_eq!(lists[0].id, 1);
+        assert!(!lists[0].whitelist);
+        assert_eq!(lists[0].rules_count, 52311);
+        assert_eq!(
+            lists[0].last_updated,
+            Some("2024-01-15T10:30:00Z".to_string())
+        );
+        assert_eq!(lists[1].id, 7);
+        assert!(lists[1].whitelist);
+        assert!(lists[1].last_updated.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_update_filter_list_sends_set_url_body() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/control/filtering/set_url"))
+            .and(body_json(json!({
+                "url": "https://example.com/list.txt",
+                "whitelist": false,
+                "data": {
+                    "name": "Renamed",
+                    "url": "https://example.com/list.txt",
+                    "enabled": false
+                }
+            })))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let service = create_test_service_with_url(&mock_server.uri());
+        let list = FilterList {
+            id: 3,
+            name: "List".to_string(),
+            url: "https://example.com/list.txt".to_string(),
+            enabled: true,
+            whitelist: false,
+            rules_count: 10,
+            last_updated: None,
+        };
+
+        service
+            .update_filter_list(&list, "Renamed", false)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_check_host_reports_blocking_rule_and_list_name() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/control/filtering/check_host"))
+            .and(query_param("name", "ads.example.com"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "reason": "FilteredBlackList",
+                "filter_id": 1,
+                "rule": "||ads.example.com^",
+                "rules": [{ "filter_list_id": 1, "text": "||ads.example.com^" }],
+                "service_name": "",
+                "cname": "",
+                "ip_addrs": null
+            })))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/control/filtering/status"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "filters": [{
+                    "id": 1,
+                    "name": "AdGuard DNS filter",
+                    "url": "https://example.com/filter.txt",
+                    "enabled": true,
+                    "rules_count": 10,
+                    "last_updated": "2024-01-15T10:30:00Z"
+                }],
+                "user_rules": []
+            })))
+            .mount(&mock_server)
+            .await;
+
+        let service = create_test_service_with_url(&mock_server.uri());
+        let result = service.check_host("ads.example.com").await.unwrap();
+
+        assert!(result.blocked);
+        assert_eq!(result.reason, "FilteredBlackList");
+        assert_eq!(result.rules.len(), 1);
+        assert_eq!(result.rules[0].text, "||ads.example.com^");
+        assert_eq!(
+            result.rules[0].filter_list_name,
+            Some("AdGuard DNS filter".to_string())
+        );
+        assert!(result.service_name.is_none());
+        assert!(result.ip_addrs.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_check_host_marks_allowlisted_host_as_not_blocked() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/control/filtering/check_host"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "reason": "NotFilteredWhiteList",
+                "rules": [{ "filter_list_id": 0, "text": "@@||ads.example.com^" }]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/control/filtering/status"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "filters": [] })))
+            .mount(&mock_server)
+            .await;
+
+        let service = create_test_service_with_url(&mock_server.uri());
+        let result = service.check_host("ads.example.com").await.unwrap();
+
+        assert!(!result.blocked);
+        assert_eq!(
+            result.rules[0].filter_list_name,
+            Some("Custom filtering rules".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_query_log_requests_blocked_entries() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/control/querylog"))
+            .and(query_param("limit", "10"))
+            .and(query_param("response_status", "blocked"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "oldest": "2024-01-15T10:29:00Z",
+                "data": [{
+                    "time": "2024-01-15T10:30:00Z",
+                    "question": { "name": "ads.example.com", "type": "A", "class": "IN" },
+                    "client": "192.168.1.20",
+                    "reason": "FilteredBlackList",
+                    "rules": [{ "filter_list_id": 0, "text": "||ads.example.com^" }],
+                    "elapsedMs": "0.42"
+                }]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("GET"))
+            .and(path("/control/filtering/status"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "filters": [] })))
+            .mount(&mock_server)
+            .await;
+
+        let service = create_test_service_with_url(&mock_server.uri());
+        let log = service
+            .get_query_log(&QueryLogQuery {
+                limit: 10,
+                older_than: None,
+                search: None,
+                blocked_only: true,
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(log.entries.len(), 1);
+        assert!(log.entries[0].blocked);
+        assert_eq!(log.entries[0].name, "ads.example.com");
+        assert_eq!(log.entries[0].query_type, "A");
+        assert_eq!(log.entries[0].rules[0].text, "||ads.example.com^");
+        assert_eq!(log.oldest, Some("2024-01-15T10:29:00Z".to_string()));
+    }
+
+    #[test]
+    fn test_normalize_rewrite_domain_accepts_leading_wildcard() {
+        assert_eq!(
+            normalize_rewrite_domain("*.Lab.Home.arpa"),
+            Ok("*.lab.home.arpa".to_string())
+        );
+        assert!(normalize_rewrite_domain("nas.*.home.arpa").is_err());
+        assert!(normalize_rewrite_domain("*").is_err());
+    }
+
+    #[test]
+    fn test_normalize_rewrite_domain_accepts_single_label_names() {
+        assert_eq!(normalize_rewrite_domain("NAS"), Ok("nas".to_string()));
+        assert_eq!(normalize_rewrite_answer("router"), Ok("router".to_string()));
+        assert!(normalize_rewrite_domain("-nas").is_err());
+        assert!(normalize_rewrite_domain("1234").is_err());
+    }
+
+    #[test]
+    fn test_normalize_rewrite_answer_accepts_ips_cnames_and_record_types() {
+        assert_eq!(
+            normalize_rewrite_answer("192.168.1.10"),
+            Ok("192.168.1.10".to_string())
+        );
+        assert_eq!(
+            normalize_rewrite_answer("fd00::10"),
+            Ok("fd00::10".to_string())
+        );
+        assert_eq!(
+            normalize_rewrite_answer("NAS.home.arpa"),
+            Ok("nas.home.arpa".to_string())
+        );
+        assert_eq!(normalize_rewrite_answer("AAAA"), Ok("AAAA".to_string()));
+        assert!(normalize_rewrite_answer("192.168.1").is_err());
+        assert!(normalize_rewrite_answer("").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_update_rewrite_sends_put_with_target_and_update() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("PUT"))
+            .and(path("/control/rewrite/update"))
+            .and(body_json(json!({
+                "target": { "domain": "nas.home.arpa", "answer": "192.168.1.10" },
+                "update": { "domain": "nas.home.arpa", "answer": "192.168.1.11" }
+            })))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let service = create_test_service_with_url(&mock_server.uri());
+        service
+            .update_rewrite(
+                &RewriteEntry {
+                    domain: "nas.home.arpa".to_string(),
+                    answer: "192.168.1.10".to_string(),
+                },
+                &RewriteEntry {
+                    domain: "nas.home.arpa".to_string(),
+                    answer: "192.168.1.11".to_string(),
+                },
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_get_rewrites_handles_null_list() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("GET"))
+            .and(path("/control/rewrite/list"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("null"))
+            .mount(&mock_server)
+            .await;
+
+        let service = create_test_service_with_url(&mock_server.uri());
+        let rewrites = service.get_rewrites().await.unwrap();
 
-        if insecure_tls {
-            client_builder = client_builder.danger_accept_invalid_certs(true);
-        }
+        assert!(rewrites.is_empty());
+    }
 
-        let client = client_builder.build()?;
+    #[tokio::test]
+    async fn test_get_blocked_services_handles_null_ids() {
+        let mock_server = MockServer::start().await;
 
-        let base_url = host.trim_end_matches('/').to_string();
+        Mock::given(method("GET"))
+            .and(path("/control/blocked_services/get"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "ids": null,
+                "schedule": { "time_zone": "Local" }
+            })))
+            .mount(&mock_server)
+            .await;
 
-        Ok(Self { client, base_url })
-    }
+        let service = create_test_service_with_url(&mock_server.uri());
+        let blocked = service.get_blocked_services().await.unwrap();
 
-    pub async fn get_status(&self) -> Result<AdguardStatusResponse, anyhow::Error> {
-        let url = format!("{}/control/status", self.base_url);
-        let raw: RawAdguardStatusResponse = self
-            .client
-            .get(&url)
-            .send()
-            .await?
-            .error_for_status()?
-            .json()
-            .await?;
-        Ok(AdguardStatusResponse {
-            protection_enabled: raw.protection_enabled,
-            protection_disabled_duration: raw.protection_disabled_duration,
-            protection_disabled_until: raw.protection_disabled_until,
-            version: raw.version,
-            running: raw.running,
-        })
+        assert!(blocked.ids.is_empty());
+        assert_eq!(blocked.schedule.time_zone, Some("Local".to_string()));
+        assert!(blocked.schedule.mon.is_none());
     }
 
-    pub async fn set_protection(
-        &self,
-        enabled: bool,
-        duration_ms: Option<u64>,
-    ) -> Result<AdguardStatusResponse, anyhow::Error> {
-        let url = format!("{}/control/protection", self.base_url);
-        let mut body = serde_json::json!({
-            "enabled": enabled
-        });
-        if let Some(duration) = duration_ms {
-            body["duration"] = duration.into();
-        }
+    #[tokio::test]
+    async fn test_get_blocked_services_converts_schedule_to_seconds() {
+        let mock_server = MockServer::start().await;
 
-        let response = self.client.post(&url).json(&body).send().await?;
-        let status = response.status();
-        if !status.is_success() {
-            let response_body = response.text().await?;
-            return Err(anyhow::anyhow!(
-                "AdGuard returned status {}: {}",
-                status,
-                response_body
-            ));
-        }
+        Mock::given(method("GET"))
+            .and(path("/control/blocked_services/get"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "ids": ["youtube"],
+                "schedule": { "mon": { "start": 68400000, "end": 72000000 } }
+            })))
+            .mount(&mock_server)
+            .await;
 
-        self.get_status().await
-    }
-}
+        let service = create_test_service_with_url(&mock_server.uri());
+        let blocked = service.get_blocked_services().await.unwrap();
 
-#[derive(Debug, Serialize)]
-pub struct AdguardStatusResponse {
-    pub protection_enabled: bool,
-    pub protection_disabled_duration: i64,
-    pub protection_disabled_until: Option<String>,
-    pub version: String,
-    pub running: bool,
-}
+        assert_eq!(
+            blocked.schedule.mon,
+            Some(DayRange {
+                start_seconds: 68_400,
+                end_seconds: 72_000,
+            })
+        );
+    }
 
-#[derive(Debug, Deserialize)]
-struct RawAdguardStatusResponse {
-    version: String,
-    protection_disabled_duration: i64,
-    protection_enabled: bool,
-    protection_disabled_until: Option<String>,
-    running: bool,
-}
+    #[tokio::test]
+    async fn test_set_blocked_services_sends_ids_and_schedule() {
+        let mock_server = MockServer::start().await;
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use serde_json::json;
-    use wiremock::matchers::{header, method, path};
-    use wiremock::{Mock, MockServer, ResponseTemplate};
+        Mock::given(method("PUT"))
+            .and(path("/control/blocked_services/update"))
+            .and(body_json(json!({
+                "ids": ["tiktok", "youtube"],
+                "schedule": {
+                    "time_zone": "Europe/Copenhagen",
+                    "sat": { "start": 36000000, "end": 43200000 }
+                }
+            })))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
 
-    fn create_test_service_with_url(base_url: &str) -> AdguardService {
-        AdguardService::new(base_url, "test", "test", false).unwrap()
+        let service = create_test_service_with_url(&mock_server.uri());
+        service
+            .set_blocked_services(&BlockedServices {
+                ids: vec!["tiktok".to_string(), "youtube".to_string()],
+                schedule: BlockedServicesSchedule {
+                    time_zone: Some("Europe/Copenhagen".to_string()),
+                    sat: Some(DayRange {
+                        start_seconds: 36_000,
+                        end_seconds: 43_200,
+                    }),
+                    ..Default::default()
+                },
+            })
+            .await
+            .unwrap();
     }
 
     #[tokio::test]
-    async fn test_get_status_parses_response_correctly() {
+    async fn test_update_client_preserves_unrelated_fields() {
         let mock_server = MockServer::start().await;
-        let raw_response = json!({
-            "version": "v1.5.0",
-            "language": "en",
-            "dns_addresses": ["8.8.8.8", "8.8.4.4"],
-            "dns_port": 53,
-            "http_port": 80,
-            "protection_disabled_duration": 0,
-            "protection_enabled": true,
-            "protection_disabled_until": null,
-            "dhcp_available": true,
-            "running": true
-        });
 
         Mock::given(method("GET"))
-            .and(path("/control/status"))
-            .and(header("Authorization", "Basic dGVzdDp0ZXN0"))
-            .respond_with(ResponseTemplate::new(200).set_body_json(raw_response))
+            .and(path("/control/clients"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "clients": [{
+                    "name": "Living room TV",
+                    "ids": ["192.168.1.50"],
+                    "tags": ["device_tv"],
+                    "use_global_settings": true,
+                    "filtering_enabled": true,
+                    "parental_enabled": false,
+                    "safebrowsing_enabled": false,
+                    "safe_search": { "enabled": false, "google": true },
+                    "use_global_blocked_services": true,
+                    "blocked_services": null,
+                    "upstreams": ["https://dns.quad9.net/dns-query"],
+                    "ignore_querylog": false
+                }],
+                "auto_clients": [],
+                "supported_tags": ["device_tv"]
+            })))
+            .mount(&mock_server)
+            .await;
+
+        Mock::given(method("POST"))
+            .and(path("/control/clients/update"))
+            .and(body_json(json!({
+                "name": "Living room TV",
+                "data": {
+                    "name": "Living room TV",
+                    "ids": ["192.168.1.50"],
+                    "tags": ["device_tv"],
+                    "use_global_settings": false,
+                    "filtering_enabled": false,
+                    "parental_enabled": false,
+                    "safebrowsing_enabled": false,
+                    "safe_search": { "enabled": false, "google": true },
+                    "use_global_blocked_services": true,
+                    "blocked_services": null,
+                    "upstreams": ["https://dns.quad9.net/dns-query"],
+                    "ignore_querylog": false
+                }
+            })))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
             .mount(&mock_server)
             .await;
 
         let service = create_test_service_with_url(&mock_server.uri());
-        let status = service.get_status().await.unwrap();
+        let update = ClientSettingsUpdate {
+            filtering_enabled: Some(false),
+            ..Default::default()
+        };
+        let client = service
+            .update_client("Living room TV", &update)
+            .await
+            .unwrap()
+            .unwrap();
 
-        assert!(status.protection_enabled);
-        assert_eq!(status.version, "v1.5.0");
-        assert!(status.running);
-        assert_eq!(status.protection_disabled_duration, 0);
-        assert!(status.protection_disabled_until.is_none());
+        assert!(!client.use_global_settings);
+        assert!(!client.filtering_enabled);
+        assert!(client.blocked_services.is_empty());
     }
 
     #[tokio::test]
-    async fn test_get_status_handles_disabled_state() {
+    async fn test_update_client_returns_none_for_unknown_client() {
         let mock_server = MockServer::start().await;
-        let raw_response = json!({
-            "version": "v1.5.0",
-            "language": "en",
-            "dns_addresses": ["8.8.8.8"],
-            "dns_port": 53,
-            "http_port": 80,
-            "protection_disabled_duration": 300,
-            "protection_enabled": false,
-            "protection_disabled_until": "2024-01-15T10:30:00Z",
-            "dhcp_available": false,
-            "running": true
-        });
 
         Mock::given(method("GET"))
-            .and(path("/control/status"))
-            .and(header("Authorization", "Basic dGVzdDp0ZXN0"))
-            .respond_with(ResponseTemplate::new(200).set_body_json(raw_response))
+            .and(path("/control/clients"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "clients": null,
+                "auto_clients": [{ "name": "phone", "ip": "192.168.1.20", "source": "ARP" }]
+            })))
             .mount(&mock_server)
             .await;
 
         let service = create_test_service_with_url(&mock_server.uri());
-        let status = service.get_status().await.unwrap();
+        let result = service
+            .update_client("TV", &ClientSettingsUpdate::default())
+            .await
+            .unwrap();
 
-        assert!(!status.protection_enabled);
-        assert_eq!(status.protection_disabled_duration, 300);
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_normalize_mac_accepts_common_formats() {
         assert_eq!(
-            status.protection_disabled_until,
-            Some("2024-01-15T10:30:00Z".to_string())
+            normalize_mac("AA-BB-CC-00-11-22"),
+            Ok("aa:bb:cc:00:11:22".to_string())
         );
+        assert!(normalize_mac("aa:bb:cc:00:11").is_err());
+        assert!(normalize_mac("zz:bb:cc:00:11:22").is_err());
     }
 
     #[tokio::test]
-    async fn test_set_protection_builds_correct_enable_body() {
+    async fn test_get_dhcp_status_annotates_leases_with_client_names() {
         let mock_server = MockServer::start().await;
 
-        let status_response = json!({
-            "version": "v1.5.0",
-            "language": "en",
-            "dns_addresses": [],
-            "dns_port": 53,
-            "http_port": 80,
-            "protection_disabled_duration": 0,
-            "protection_enabled": true,
-            "protection_disabled_until": null,
-            "dhcp_available": false,
-            "running": true
-        });
-
-        // Mock both the POST and the subsequent GET
-        Mock::given(method("POST"))
-            .and(path("/control/protection"))
-            .and(header("Authorization", "Basic dGVzdDp0ZXN0"))
-            .respond_with(ResponseTemplate::new(200).set_body_json(status_response.clone()))
+        Mock::given(method("GET"))
+            .and(path("/control/dhcp/status"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "enabled": true,
+                "interface_name": "eth0",
+                "v4": {
+                    "gateway_ip": "192.168.1.1",
+                    "subnet_mask": "255.255.255.0",
+                    "range_start": "192.168.1.100",
+                    "range_end": "192.168.1.200",
+                    "lease_duration": 86400
+                },
+                "leases": [{
+                    "mac": "aa:bb:cc:00:11:22",
+                    "ip": "192.168.1.120",
+                    "hostname": "phone",
+                    "expires": "2024-01-16T10:30:00Z"
+                }],
+                "static_leases": [{
+                    "mac": "aa:bb:cc:00:11:33",
+                    "ip": "192.168.1.50",
+                    "hostname": "tv"
+                }]
+            })))
             .mount(&mock_server)
             .await;
 
         Mock::given(method("GET"))
-            .and(path("/control/status"))
-            .respond_with(ResponseTemplate::new(200).set_body_json(status_response))
+            .and(path("/control/clients"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "clients": [{ "name": "Living room TV", "ids": ["192.168.1.50"] }],
+                "auto_clients": []
+            })))
             .mount(&mock_server)
             .await;
 
         let service = create_test_service_with_url(&mock_server.uri());
-        let result = service.set_protection(true, None).await.unwrap();
+        let status = service.get_dhcp_status().await.unwrap();
 
-        assert!(result.protection_enabled);
+        assert!(status.config.enabled);
+        assert_eq!(status.config.v4.unwrap().lease_duration_seconds, 86400);
+        assert_eq!(status.leases.len(), 2);
+        assert!(!status.leases[0].is_static);
+        assert!(status.leases[0].client_name.is_none());
+        assert!(status.leases[1].is_static);
+        assert_eq!(
+            status.leases[1].client_name,
+            Some("Living room TV".to_string())
+        );
     }
 
     #[tokio::test]
-    async fn test_set_protection_builds_correct_disable_body() {
-        let mock_server = MockServer::start().await;
+    async fn test_set_protection_fans_out_to_replicas() {
+        let primary_server = MockServer::start().await;
+        let replica_server = MockServer::start().await;
 
         let status_response = json!({
             "version": "v1.5.0",
-            "language": "en",
-            "dns_addresses": [],
-            "dns_port": 53,
-            "http_port": 80,
             "protection_disabled_duration": 0,
             "protection_enabled": false,
             "protection_disabled_until": null,
-            "dhcp_available": false,
             "running": true
         });
 
-        // Mock both the POST and the subsequent GET
         Mock::given(method("POST"))
             .and(path("/control/protection"))
-            .respond_with(ResponseTemplate::new(200).set_body_json(status_response.clone()))
-            .mount(&mock_server)
+            .and(body_json(json!({ "enabled": false })))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&primary_server)
             .await;
 
         Mock::given(method("GET"))
             .and(path("/control/status"))
             .respond_with(ResponseTemplate::new(200).set_body_json(status_response))
-            .mount(&mock_server)
+            .mount(&primary_server)
             .await;
 
-        let service = create_test_service_with_url(&mock_server.uri());
-        let result = service.set_protection(false, None).await.unwrap();
+        Mock::given(method("POST"))
+            .and(path("/control/protection"))
+            .respond_with(ResponseTemplate::new(503).set_body_string("starting"))
+            .expect(1)
+            .mount(&replica_server)
+            .await;
 
-        assert!(!result.protection_enabled);
+        let replica = create_test_service_with_url(&replica_server.uri()).with_name("replica");
+        let service =
+            create_test_service_with_url(&primary_server.uri()).with_replicas(vec![replica]);
+        let status = service.set_protection(false, None).await.unwrap();
+
+        assert!(!status.protection_enabled);
+        assert_eq!(status.replicas.len(), 1);
+        assert_eq!(status.replicas[0].name, "replica");
+        assert!(status.replicas[0].protection_enabled.is_none());
+        assert!(status.replicas[0].error.as_ref().unwrap().contains("503"));
     }
 
     #[tokio::test]
-    async fn test_set_protection_with_duration() {
+    async fn test_get_upstream_config_maps_legacy_mode() {
         let mock_server = MockServer::start().await;
 
-        let status_response = json!({
-            "version": "v1.5.0",
-            "language": "en",
-            "dns_addresses": [],
-            "dns_port": 53,
-            "http_port": 80,
-            "protection_disabled_duration": 300000,
-            "protection_enabled": false,
-            "protection_disabled_until": "2024-01-15T10:30:00Z",
-            "dhcp_available": false,
-            "running": true
-        });
-
-        // Mock both the POST and the subsequent GET
-        Mock::given(method("POST"))
-            .and(path("/control/protection"))
-            .respond_with(ResponseTemplate::new(200).set_body_json(status_response.clone()))
+        Mock::given(method("GET"))
+            .and(path("/control/dns_info"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "upstream_dns": ["https://dns10.quad9.net/dns-query"],
+                "bootstrap_dns": ["9.9.9.10"],
+                "fallback_dns": null,
+                "upstream_mode": "",
+                "ratelimit": 20
+            })))
             .mount(&mock_server)
             .await;
 
-        Mock::given(method("GET"))
-            .and(path("/control/status"))
-            .respond_with(ResponseTemplate::new(200).set_body_json(status_response))
+        let service = create_test_service_with_url(&mock_server.uri());
+        let config = service.get_upstream_config().await.unwrap();
+
+        assert_eq!(
+            config.upstream_dns,
+            vec!["https://dns10.quad9.net/dns-query"]
+        );
+        assert_eq!(config.bootstrap_dns, vec!["9.9.9.10"]);
+        assert!(config.fallback_dns.is_empty());
+        assert_eq!(config.upstream_mode, UpstreamMode::LoadBalance);
+    }
+
+    #[tokio::test]
+    async fn test_set_upstream_config_sends_legacy_load_balance_mode() {
+        let mock_server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/control/dns_config"))
+            .and(body_json(json!({
+                "upstream_dns": ["1.1.1.1"],
+                "bootstrap_dns": [],
+                "fallback_dns": [],
+                "upstream_mode": ""
+            })))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
             .mount(&mock_server)
             .await;
 
         let service = create_test_service_with_url(&mock_server.uri());
-        let result = service.set_protection(false, Some(300000)).await.unwrap();
-
-        assert!(!result.protection_enabled);
-        assert_eq!(result.protection_disabled_duration, 300000);
+        let config = UpstreamConfig {
+            upstream_dns: vec!["1.1.1.1".to_string()],
+            bootstrap_dns: Vec::new(),
+            fallback_dns: Vec::new(),
+            upstream_mode: UpstreamMode::LoadBalance,
+        };
+        service.set_upstream_config(&config).await.unwrap();
     }
 
     #[tokio::test]
-    async fn test_set_protection_handles_error_response() {
+    async fn test_test_upstreams_reports_failures() {
         let mock_server = MockServer::start().await;
 
         Mock::given(method("POST"))
-            .and(path("/control/protection"))
-            .respond_with(ResponseTemplate::new(500).set_body_string("Internal Server Error"))
+            .and(path("/control/test_upstream_dns"))
+            .and(body_json(json!({
+                "upstream_dns": ["1.1.1.1", "10.0.0.53"],
+                "bootstrap_dns": [],
+                "fallback_dns": [],
+                "private_upstream": []
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "1.1.1.1": "OK",
+                "10.0.0.53": "couldn't communicate with upstream: i/o timeout"
+            })))
             .mount(&mock_server)
             .await;
 
         let service = create_test_service_with_url(&mock_server.uri());
-        let result = service.set_protection(true, None).await;
+        let config = UpstreamConfig {
+            upstream_dns: vec!["1.1.1.1".to_string(), "10.0.0.53".to_string()],
+            bootstrap_dns: Vec::new(),
+            fallback_dns: Vec::new(),
+            upstream_mode: UpstreamMode::Parallel,
+        };
+        let results = service.test_upstreams(&config).await.unwrap();
 
-        assert!(result.is_err());
-        let err = result.unwrap_err();
-        assert!(err.to_string().contains("500"));
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].upstream, "1.1.1.1");
+        assert!(results[0].ok);
+        assert!(results[0].error.is_none());
+        assert_eq!(results[1].upstream, "10.0.0.53");
+        assert!(!results[1].ok);
+        assert!(results[1].error.as_ref().unwrap().contains("timeout"));
     }
 
-    #[tokio::test]
-    async fn test_new_strips_trailing_slash() {
-        let service = AdguardService::new("http://localhost:3000/", "user", "pass", false).unwrap();
-        assert_eq!(service.base_url, "http://localhost:3000");
+    fn raw_status(
+        enabled: bool,
+        duration_ms: i64,
+        until: Option<&str>,
+    ) -> RawAdguardStatusResponse {
+        RawAdguardStatusResponse {
+            version: "v0.107.0".to_string(),
+            protection_disabled_duration: duration_ms,
+            protection_enabled: enabled,
+            protection_disabled_until: until.map(str::to_string),
+            running: true,
+        }
+    }
+
+    #[test]
+    fn test_protection_state_from_raw() {
+        let now = DateTime::parse_from_rfc3339("2024-01-15T10:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        assert_eq!(
+            ProtectionState::from_raw(&raw_status(true, 0, None), now),
+            ProtectionState::Enabled
+        );
+        assert_eq!(
+            ProtectionState::from_raw(&raw_status(false, 0, None), now),
+            ProtectionState::DisabledIndefinitely
+        );
+        assert_eq!(
+            ProtectionState::from_raw(&raw_status(false, 299_500, None), now),
+            ProtectionState::Paused {
+                until: "2024-01-15T10:04:59Z".to_string(),
+                remaining_seconds: 300,
+            }
+        );
+        assert_eq!(
+            ProtectionState::from_raw(
+                &raw_status(false, 0, Some("2024-01-15T11:30:00+01:00")),
+                now
+            ),
+            ProtectionState::Paused {
+                until: "2024-01-15T10:30:00Z".to_string(),
+                remaining_seconds: 1800,
+            }
+        );
+        assert_eq!(
+            ProtectionState::from_raw(&raw_status(false, 0, Some("2024-01-15T09:00:00Z")), now),
+            ProtectionState::Enabled
+        );
     }
 
     #[tokio::test]
-    async fn test_get_status_handles_json_error() {
+    async fn test_get_filtering_policy_combines_features() {
         let mock_server = MockServer::start().await;
 
         Mock::given(method("GET"))
-            .and(path("/control/status"))
-            .respond_with(ResponseTemplate::new(200).set_body_string("not valid json"))
+            .and(path("/control/safebrowsing/status"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "enabled": true })))
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/control/parental/status"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "enabled": false, "sensitivity": 13 })),
+            )
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/control/safesearch/status"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "enabled": true,
+                "google": true,
+                "youtube": true
+            })))
             .mount(&mock_server)
             .await;
 
         let service = create_test_service_with_url(&mock_server.uri());
-        let result = service.get_status().await;
+        let policy = service.get_filtering_policy().await.unwrap();
 
-        assert!(result.is_err());
+        assert!(policy.safe_browsing);
+        assert!(!policy.parental_control);
+        assert!(policy.safe_search.enabled);
+        assert!(policy.safe_search.google);
+        assert!(policy.safe_search.youtube);
+        assert!(!policy.safe_search.bing);
     }
 
     #[tokio::test]
-    async fn test_get_status_handles_network_error() {
-        let service = create_test_service_with_url("http://nonexistent:9999");
-        let result = service.get_status().await;
+    async fn test_set_parental_control_uses_toggle_endpoint() {
+        let mock_server = MockServer::start().await;
 
-        assert!(result.is_err());
+        Mock::given(method("POST"))
+            .and(path("/control/parental/disable"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+
+        let service = create_test_service_with_url(&mock_server.uri());
+        service.set_parental_control(false).await.unwrap();
     }
 }
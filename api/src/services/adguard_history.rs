@@ -0,0 +1,107 @@
+use serde::Serialize;
+use sqlx::SqlitePool;
+
+use crate::services::adguard::AdguardStatusResponse;
+
+/// Actor recorded for changes requested through the API.
+pub const API_ACTOR: &str = "api";
+/// Actor recorded for changes made by protection schedules.
+pub const SCHEDULER_ACTOR: &str = "scheduler";
+
+/// One protection change requested through the API or made by a schedule,
+/// successful or not.
+#[derive(Debug, Serialize)]
+pub struct ProtectionChange {
+    pub id: i64,
+    pub created_at: String,
+    pub actor: String,
+    pub action: String,
+    pub duration_seconds: Option<i64>,
+    pub client_ip: Option<String>,
+    pub forwarded_for: Option<String>,
+    pub success: bool,
+    pub error: Option<String>,
+    pub protection_enabled: Option<bool>,
+    pub protection_disabled_until: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct NewProtectionChange<'a> {
+    pub actor: &'a str,
+    pub action: &'a str,
+    pub duration_seconds: Option<i64>,
+    pub client_ip: Option<&'a str>,
+    pub forwarded_for: Option<&'a str>,
+}
+
+/// Records a protection change along with the resulting AdGuard status, or the
+/// error if the change failed.
+pub async fn record_protection_change(
+    pool: &SqlitePool,
+    change: &NewProtectionChange<'_>,
+    outcome: &anyhow::Result<AdguardStatusResponse>,
+) -> anyhow::Result<()> {
+    let success = outcome.is_ok();
+    let (protection_enabled, disabled_until, error) = match outcome {
+        Ok(status) => (
+            Some(status.protection_enabled),
+            status.protection_disabled_until.clone(),
+            None,
+        ),
+        Err(e) => (None, None, Some(e.to_string())),
+    };
+    sqlx::query!(
+        r#"
+        INSERT INTO adguard_protection_history
+            (actor, action, duration_seconds, client_ip, forwarded_for, success, error,
+             protection_enabled, protection_disabled_until)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        "#,
+        change.actor,
+        change.action,
+        change.duration_seconds,
+        change.client_ip,
+        change.forwarded_for,
+        success,
+        error,
+        protection_enabled,
+        disabled_until
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Lists protection changes newest first, optionally only those older than `before_id`.
+pub async fn list_protection_changes(
+    pool: &SqlitePool,
+    before_id: Option<i64>,
+    limit: i64,
+) -> anyhow::Result<Vec<ProtectionChange>> {
+    let changes = sqlx::query_as!(
+        ProtectionChange,
+        r#"
+        SELECT
+            id as "id!",
+            CAST(created_at AS TEXT) as "created_at!: String",
+            actor,
+            action,
+            duration_seconds,
+            client_ip,
+            forwarded_for,
+            success as "success: bool",
+            error,
+            protection_enabled as "protection_enabled: bool",
+            protection_disabled_until
+        FROM adguard_protection_history
+        WHERE ($1 IS NULL OR id < $1)
+        ORDER BY id DESC
+        LIMIT $2
+        "#,
+        before_id,
+        limit
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(changes)
+}
@@ -0,0 +1,961 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use chrono::{DateTime, Datelike, NaiveDateTime, NaiveTime, Timelike, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use crate::services::adguard::{
+    AdguardService, AdguardStatusResponse, BlockedServices, ClientSettingsUpdate, ProtectionState,
+};
+use crate::services::adguard_history::{self, NewProtectionChange};
+
+/// Minimum delay before retrying a schedule phase that failed.
+const RETRY_AFTER_FAILURE_MINUTES: i64 = 5;
+const MINUTES_PER_DAY: i64 = 24 * 60;
+const MINUTES_PER_WEEK: i64 = 7 * MINUTES_PER_DAY;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduleAction {
+    /// Turns protection off (globally, or filtering for one client) during the window.
+    DisableProtection,
+    /// Adds `blocked_services` to the blocked set (globally, or for one client) during the window.
+    BlockServices,
+}
+
+impl ScheduleAction {
+    fn as_str(self) -> &'static str {
+        match self {
+            ScheduleAction::DisableProtection => "disable_protection",
+            ScheduleAction::BlockServices => "block_services",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "disable_protection" => Some(ScheduleAction::DisableProtection),
+            "block_services" => Some(ScheduleAction::BlockServices),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Schedule {
+    pub id: i64,
+    pub name: String,
+    pub client_name: Option<String>,
+    pub action: ScheduleAction,
+    pub blocked_services: Vec<String>,
+    pub days: Vec<String>,
+    pub start_time: String,
+    pub end_time: String,
+    pub enabled: bool,
+    pub active: bool,
+    pub created_at: String,
+    #[serde(skip)]
+    restore_state: Option<String>,
+}
+
+impl Schedule {
+    /// Whether `now` (local time) falls inside one of this schedule's weekly windows.
+    ///
+    /// Windows whose end is before their start run past midnight into the next day;
+    /// `days` lists the days on which a window starts.
+    pub fn is_within_window(&self, now: NaiveDateTime) -> bool {
+        let (Ok(start), Ok(end)) = (parse_time(&self.start_time), parse_time(&self.end_time))
+        else {
+            return false;
+        };
+        let days: Vec<Weekday> = self
+            .days
+            .iter()
+            .filter_map(|day| Weekday::from_str(day).ok())
+            .collect();
+        let today = now.weekday();
+        let time = now.time();
+
+        if start < end {
+            days.contains(&today) && time >= start && time < end
+        } else {
+            (days.contains(&today) && time >= start) || (days.contains(&today.pred()) && time < end)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScheduleInput {
+    pub name: String,
+    pub client_name: Option<String>,
+    pub action: ScheduleAction,
+    #[serde(default)]
+    pub blocked_services: Vec<String>,
+    pub days: Vec<String>,
+    pub start_time: String,
+    pub end_time: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl ScheduleInput {
+    /// Validates the input and normalizes names, days and times.
+    pub fn validate(mut self) -> Result<Self, String> {
+        self.name = self.name.trim().to_string();
+        if self.name.is_empty() {
+            return Err("name is required".to_string());
+        }
+        self.client_name = self
+            .client_name
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty());
+
+        let mut days = Vec::with_capacity(self.days.len());
+        for day in &self.days {
+            let weekday = Weekday::from_str(day.trim())
+                .map_err(|_| format!("invalid day '{}'", day.trim()))?;
+            if !days.contains(&weekday) {
+                days.push(weekday);
+            }
+        }
+        if days.is_empty() {
+            return Err("days must contain at least one day".to_string());
+        }
+        days.sort_by_key(|day| day.num_days_from_monday());
+        self.days = days.iter().map(|day| weekday_name(*day)).collect();
+
+        let start = parse_time(&self.start_time)
+            .map_err(|_| "start_time must be formatted as HH:MM".to_string())?;
+        let end = parse_time(&self.end_time)
+            .map_err(|_| "end_time must be formatted as HH:MM".to_string())?;
+        if start == end {
+            return Err("start_time and end_time must differ".to_string());
+        }
+        self.start_time = start.format("%H:%M").to_string();
+        self.end_time = end.format("%H:%M").to_string();
+
+        self.blocked_services = self
+            .blocked_services
+            .iter()
+            .map(|id| id.trim().to_string())
+            .filter(|id| !id.is_empty())
+            .collect();
+        self.blocked_services.sort();
+        self.blocked_services.dedup();
+        match self.action {
+            ScheduleAction::BlockServices if self.blocked_services.is_empty() => {
+                return Err("blocked_services is required for block_services".to_string());
+            }
+            ScheduleAction::DisableProtection if !self.blocked_services.is_empty() => {
+                return Err("blocked_services is only allowed for block_services".to_string());
+            }
+            _ => {}
+        }
+
+        Ok(self)
+    }
+
+    /// Whether this schedule and `other` are both enabled and change the same AdGuard
+    /// setting in overlapping windows. Each window restores what it found when it
+    /// started, so overlapping windows would undo each other's changes.
+    pub fn overlaps(&self, other: &Schedule) -> bool {
+        if !self.enabled
+            || !other.enabled
+            || self.action != other.action
+            || self.client_name != other.client_name
+        {
+            return false;
+        }
+        if self.action == ScheduleAction::BlockServices
+            && !self
+                .blocked_services
+                .iter()
+                .any(|id| other.blocked_services.contains(id))
+        {
+            return false;
+        }
+        let ours = weekly_windows(&self.days, &self.start_time, &self.end_time);
+        let theirs = weekly_windows(&other.days, &other.start_time, &other.end_time);
+        ours.iter().any(|&(start, end)| {
+            theirs.iter().any(|&(other_start, other_end)| {
+                // Windows near the end of the week wrap into the next one.
+                [-MINUTES_PER_WEEK, 0, MINUTES_PER_WEEK]
+                    .iter()
+                    .any(|shift| start < other_end + shift && other_start + shift < end)
+            })
+        })
+    }
+}
+
+/// Start and end of each weekly window, in minutes since Monday 00:00. Windows that
+/// run past midnight end on the next day, after `MINUTES_PER_WEEK` for Sunday.
+fn weekly_windows(days: &[String], start_time: &str, end_time: &str) -> Vec<(i64, i64)> {
+    let (Ok(start), Ok(end)) = (parse_time(start_time), parse_time(end_time)) else {
+        return Vec::new();
+    };
+    let minutes = |time: NaiveTime| i64::from(time.hour() * 60 + time.minute());
+    let length = (minutes(end) - minutes(start)).rem_euclid(MINUTES_PER_DAY);
+    days.iter()
+        .filter_map(|day| Weekday::from_str(day).ok())
+        .map(|day| {
+            let start = i64::from(day.num_days_from_monday()) * MINUTES_PER_DAY + minutes(start);
+            (start, start + length)
+        })
+        .collect()
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScheduleRun {
+    pub id: i64,
+    pub schedule_id: i64,
+    pub phase: String,
+    pub ran_at: String,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+/// AdGuard state captured when a schedule window starts, restored when it ends.
+///
+/// Blocked services only remember which ids the window added, so services blocked
+/// or unblocked by someone else during the window are left as they are.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum RestoreState {
+    Protection {
+        enabled: bool,
+        /// When a pause that was running at the start of the window would have ended.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        paused_until: Option<DateTime<Utc>>,
+    },
+    ClientFiltering {
+        client_name: String,
+        use_global_settings: bool,
+        filtering_enabled: bool,
+    },
+    BlockedServices {
+        added: Vec<String>,
+    },
+    ClientBlockedServices {
+        client_name: String,
+        use_global_blocked_services: bool,
+        added: Vec<String>,
+    },
+}
+
+struct ScheduleRow {
+    id: i64,
+    name: String,
+    client_name: Option<String>,
+    action: String,
+    blocked_services: Option<String>,
+    days: String,
+    start_time: String,
+    end_time: String,
+    enabled: bool,
+    active: bool,
+    restore_state: Option<String>,
+    created_at: String,
+}
+
+impl TryFrom<ScheduleRow> for Schedule {
+    type Error = anyhow::Error;
+
+    fn try_from(row: ScheduleRow) -> Result<Self, Self::Error> {
+        let action = ScheduleAction::parse(&row.action)
+            .ok_or_else(|| anyhow::anyhow!("Unknown schedule action '{}'", row.action))?;
+        let blocked_services = match row.blocked_services {
+            Some(json) => serde_json::from_str(&json)?,
+            None => Vec::new(),
+        };
+        Ok(Schedule {
+            id: row.id,
+            name: row.name,
+            client_name: row.client_name,
+            action,
+            blocked_services,
+            days: row.days.split(',').map(ToString::to_string).collect(),
+            start_time: row.start_time,
+            end_time: row.end_time,
+            enabled: row.enabled,
+            active: row.active,
+            created_at: row.created_at,
+            restore_state: row.restore_state,
+        })
+    }
+}
+
+pub async fn list_schedules(pool: &SqlitePool) -> anyhow::Result<Vec<Schedule>> {
+    let rows = sqlx::query_as!(
+        ScheduleRow,
+        r#"
+        SELECT
+            id as "id!",
+            name,
+            client_name,
+            action,
+            blocked_services,
+            days,
+            start_time,
+            end_time,
+            enabled as "enabled: bool",
+            active as "active: bool",
+            restore_state,
+            CAST(created_at AS TEXT) as "created_at!: String"
+        FROM adguard_schedules
+        ORDER BY id
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    rows.into_iter().map(Schedule::try_from).collect()
+}
+
+pub async fn get_schedule(pool: &SqlitePool, id: i64) -> anyhow::Result<Option<Schedule>> {
+    let row = sqlx::query_as!(
+        ScheduleRow,
+        r#"
+        SELECT
+            id as "id!",
+            name,
+            client_name,
+            action,
+            blocked_services,
+            days,
+            start_time,
+            end_time,
+            enabled as "enabled: bool",
+            active as "active: bool",
+            restore_state,
+            CAST(created_at AS TEXT) as "created_at!: String"
+        FROM adguard_schedules
+        WHERE id = $1
+        "#,
+        id
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    row.map(Schedule::try_from).transpose()
+}
+
+pub async fn create_schedule(pool: &SqlitePool, input: &ScheduleInput) -> anyhow::Result<Schedule> {
+    let action = input.action.as_str();
+    let blocked_services = blocked_services_json(input)?;
+    let days = input.days.join(",");
+    let id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO adguard_schedules
+            (name, client_name, action, blocked_services, days, start_time, end_time, enabled)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING id
+        "#,
+        input.name,
+        input.client_name,
+        action,
+        blocked_services,
+        days,
+        input.start_time,
+        input.end_time,
+        input.enabled
+    )
+    .fetch_one(pool)
+    .await?;
+
+    get_schedule(pool, id)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Schedule {} disappeared after insert", id))
+}
+
+/// Replaces a schedule's definition. An active window keeps its captured state, so
+/// the next scheduler tick restores AdGuard if the new definition is no longer due.
+pub async fn update_schedule(
+    pool: &SqlitePool,
+    id: i64,
+    input: &ScheduleInput,
+) -> anyhow::Result<Option<Schedule>> {
+    let action = input.action.as_str();
+    let blocked_services = blocked_services_json(input)?;
+    let days = input.days.join(",");
+    let result = sqlx::query!(
+        r#"
+        UPDATE adguard_schedules SET
+            name = $1,
+            client_name = $2,
+            action = $3,
+            blocked_services = $4,
+            days = $5,
+            start_time = $6,
+            end_time = $7,
+            enabled = $8
+        WHERE id = $9
+        "#,
+        input.name,
+        input.client_name,
+        action,
+        blocked_services,
+        days,
+        input.start_time,
+        input.end_time,
+        input.enabled,
+        id
+    )
+    .execute(pool)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Ok(None);
+    }
+    get_schedule(pool, id).await
+}
+
+pub async fn delete_schedule(pool: &SqlitePool, id: i64) -> anyhow::Result<bool> {
+    let result = sqlx::query!("DELETE FROM adguard_schedules WHERE id = $1", id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn list_runs(
+    pool: &SqlitePool,
+    schedule_id: i64,
+    limit: i64,
+) -> anyhow::Result<Vec<ScheduleRun>> {
+    let runs = sqlx::query_as!(
+        ScheduleRun,
+        r#"
+        SELECT
+            id as "id!",
+            schedule_id,
+            phase,
+            CAST(ran_at AS TEXT) as "ran_at!: String",
+            success as "success: bool",
+            error
+        FROM adguard_schedule_runs
+        WHERE schedule_id = $1
+        ORDER BY id DESC
+        LIMIT $2
+        "#,
+        schedule_id,
+        limit
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(runs)
+}
+
+/// Starts schedules whose window has begun and ends those whose window has passed.
+///
+/// `now` is wall-clock time in the configured schedule time zone. Each start or end is recorded as a run; a phase
+/// that failed is retried on later calls after a short delay. An error with one
+/// schedule is logged and does not hold up the others.
+pub async fn run_due_schedules(
+    pool: &SqlitePool,
+    service: &AdguardService,
+    now: NaiveDateTime,
+) -> anyhow::Result<()> {
+    for schedule in list_schedules(pool).await? {
+        let due = schedule.enabled && schedule.is_within_window(now);
+        if due == schedule.active {
+            continue;
+        }
+        let phase = if due { "start" } else { "end" };
+        if let Err(e) = run_phase(pool, service, &schedule, phase).await {
+            tracing::warn!(schedule_id = schedule.id, phase, error = %e, "AdGuard schedule run failed");
+        }
+    }
+    Ok(())
+}
+
+async fn run_phase(
+    pool: &SqlitePool,
+    service: &AdguardService,
+    schedule: &Schedule,
+    phase: &str,
+) -> anyhow::Result<()> {
+    if failed_recently(pool, schedule.id, phase).await? {
+        return Ok(());
+    }
+    if phase == "start" {
+        start_schedule(pool, service, schedule).await
+    } else {
+        end_schedule(pool, service, schedule).await
+    }
+}
+
+/// Ends an active schedule immediately, restoring the AdGuard state it replaced.
+pub async fn end_schedule(
+    pool: &SqlitePool,
+    service: &AdguardService,
+    schedule: &Schedule,
+) -> anyhow::Result<()> {
+    let outcome = match &schedule.restore_state {
+        Some(json) => match serde_json::from_str::<RestoreState>(json) {
+            Ok(state) => restore(pool, service, state).await,
+            Err(e) => Err(anyhow::anyhow!("Invalid restore state: {}", e)),
+        },
+        None => Ok(()),
+    };
+
+    if outcome.is_ok() {
+        sqlx::query!(
+            "UPDATE adguard_schedules SET active = FALSE, restore_state = NULL WHERE id = $1",
+            schedule.id
+        )
+        .execute(pool)
+        .await?;
+    }
+    record_run(pool, schedule.id, "end", outcome.err()).await
+}
+
+async fn start_schedule(
+    pool: &SqlitePool,
+    service: &AdguardService,
+    schedule: &Schedule,
+) -> anyhow::Result<()> {
+    let error = match plan(service, schedule).await {
+        Ok((state, change)) => {
+            // Saved before AdGuard is changed, so a change that went through can
+            // always be undone when the window ends.
+            let restore_state = serde_json::to_string(&state)?;
+            sqlx::query!(
+                "UPDATE adguard_schedules SET active = TRUE, restore_state = $1 WHERE id = $2",
+                restore_state,
+                schedule.id
+            )
+            .execute(pool)
+            .await?;
+            let outcome = apply(pool, service, change).await;
+            if outcome.is_err() {
+                sqlx::query!(
+                    "UPDATE adguard_schedules SET active = FALSE, restore_state = NULL WHERE id = $1",
+                    schedule.id
+                )
+                .execute(pool)
+                .await?;
+            }
+            outcome.err()
+        }
+        Err(e) => Some(e),
+    };
+    record_run(pool, schedule.id, "start", error).await
+}
+
+/// A change a schedule window makes to AdGuard.
+enum Change {
+    DisableProtection,
+    UpdateClient {
+        client_name: String,
+        update: ClientSettingsUpdate,
+    },
+    SetBlockedServices(BlockedServices),
+}
+
+/// Works out what `schedule`'s window changes from the current AdGuard state, and
+/// the state to restore when the window ends. Nothing is changed yet.
+async fn plan(
+    service: &AdguardService,
+    schedule: &Schedule,
+) -> anyhow::Result<(RestoreState, Change)> {
+    match (schedule.action, &schedule.client_name) {
+        (ScheduleAction::DisableProtection, None) => {
+            let status = service.get_status().await?;
+            let (enabled, paused_until) = match &status.protection {
+                ProtectionState::Enabled => (true, None),
+                ProtectionState::DisabledIndefinitely => (false, None),
+                ProtectionState::Paused { until, .. } => (
+                    false,
+                    Some(DateTime::parse_from_rfc3339(until)?.with_timezone(&Utc)),
+                ),
+            };
+            let state = RestoreState::Protection {
+                enabled,
+                paused_until,
+            };
+            Ok((state, Change::DisableProtection))
+        }
+        (ScheduleAction::DisableProtection, Some(client_name)) => {
+            let client = find_client(service, client_name).await?;
+            let change = Change::UpdateClient {
+                client_name: client_name.clone(),
+                update: ClientSettingsUpdate {
+                    filtering_enabled: Some(false),
+                    ..Default::default()
+                },
+            };
+            let state = RestoreState::ClientFiltering {
+                client_name: client.name,
+                use_global_settings: client.use_global_settings,
+                filtering_enabled: client.filtering_enabled,
+            };
+            Ok((state, change))
+        }
+        (ScheduleAction::BlockServices, None) => {
+            let current = service.get_blocked_services().await?;
+            let state = RestoreState::BlockedServices {
+                added: added_ids(&current.ids, &schedule.blocked_services),
+            };
+            let change = Change::SetBlockedServices(BlockedServices {
+                ids: merge_ids(&current.ids, &schedule.blocked_services),
+                schedule: current.schedule,
+            });
+            Ok((state, change))
+        }
+        (ScheduleAction::BlockServices, Some(client_name)) => {
+            let client = find_client(service, client_name).await?;
+            let change = Change::UpdateClient {
+                client_name: client_name.clone(),
+                update: ClientSettingsUpdate {
+                    blocked_services: Some(merge_ids(
+                        &client.blocked_services,
+                        &schedule.blocked_services,
+                    )),
+                    ..Default::default()
+                },
+            };
+            let state = RestoreState::ClientBlockedServices {
+                added: added_ids(&client.blocked_services, &schedule.blocked_services),
+                client_name: client.name,
+                use_global_blocked_services: client.use_global_blocked_services,
+            };
+            Ok((state, change))
+        }
+    }
+}
+
+async fn apply(pool: &SqlitePool, service: &AdguardService, change: Change) -> anyhow::Result<()> {
+    match change {
+        Change::DisableProtection => {
+            let result = service.set_protection(false, None).await;
+            record_protection_change(pool, "disable", None, &result).await;
+            result?;
+        }
+        Change::UpdateClient {
+            client_name,
+            update,
+        } => {
+            service.update_client(&client_name, &update).await?;
+        }
+        Change::SetBlockedServices(blocked) => {
+            service.set_blocked_services(&blocked).await?;
+        }
+    }
+    Ok(())
+}
+
+async fn restore(
+    pool: &SqlitePool,
+    service: &AdguardService,
+    state: RestoreState,
+) -> anyhow::Result<()> {
+    match state {
+        RestoreState::Protection {
+            enabled,
+            paused_until,
+        } => {
+            // Resume a pause that was interrupted by the window for whatever is left
+            // of it, or re-enable protection if it would have ended already.
+            let remaining = paused_until.and_then(|until| (until - Utc::now()).to_std().ok());
+            let (action, enabled, duration) = match remaining {
+                Some(remaining) => ("pause", false, Some(round_up_to_seconds(remaining))),
+                None if paused_until.is_some() => ("enable", true, None),
+                None => (if enabled { "enable" } else { "disable" }, enabled, None),
+            };
+            let result = service.set_protection(enabled, duration).await;
+            record_protection_change(pool, action, duration, &result).await;
+            result?;
+        }
+        RestoreState::ClientFiltering {
+            client_name,
+            use_global_settings,
+            filtering_enabled,
+        } => {
+            let update = ClientSettingsUpdate {
+                use_global_settings: Some(use_global_settings),
+                filtering_enabled: Some(filtering_enabled),
+                ..Default::default()
+            };
+            service
+                .update_client(&client_name, &update)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Client '{}' not found", client_name))?;
+        }
+        RestoreState::BlockedServices { added } => {
+            let current = service.get_blocked_services().await?;
+            service
+                .set_blocked_services(&BlockedServices {
+                    ids: remove_ids(&current.ids, &added),
+                    schedule: current.schedule,
+                })
+                .await?;
+        }
+        RestoreState::ClientBlockedServices {
+            client_name,
+            use_global_blocked_services,
+            added,
+        } => {
+            let client = find_client(service, &client_name).await?;
+            let update = ClientSettingsUpdate {
+                use_global_blocked_services: Some(use_global_blocked_services),
+                blocked_services: Some(remove_ids(&client.blocked_services, &added)),
+                ..Default::default()
+            };
+            service
+                .update_client(&client_name, &update)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Client '{}' not found", client_name))?;
+        }
+    }
+    Ok(())
+}
+
+/// Audits a protection change made by a schedule. Like API changes, a failure to
+/// write the record is logged rather than failing the run.
+async fn record_protection_change(
+    pool: &SqlitePool,
+    action: &str,
+    duration: Option<Duration>,
+    result: &anyhow::Result<AdguardStatusResponse>,
+) {
+    let change = NewProtectionChange {
+        actor: adguard_history::SCHEDULER_ACTOR,
+        action,
+        duration_seconds: duration.map(|duration| duration.as_secs() as i64),
+        client_ip: None,
+        forwarded_for: None,
+    };
+    if let Err(e) = adguard_history::record_protection_change(pool, &change, result).await {
+        tracing::warn!(action, error = %e, "Failed to record protection change");
+    }
+}
+
+async fn find_client(
+    service: &AdguardService,
+    name: &str,
+) -> anyhow::Result<crate::services::adguard::PersistentClient> {
+    service
+        .get_clients()
+        .await?
+        .clients
+        .into_iter()
+        .find(|client| client.name == name)
+        .ok_or_else(|| anyhow::anyhow!("Client '{}' not found", name))
+}
+
+async fn failed_recently(pool: &SqlitePool, schedule_id: i64, phase: &str) -> anyhow::Result<bool> {
+    let cutoff = format!("-{RETRY_AFTER_FAILURE_MINUTES} minutes");
+    let last_success = sqlx::query_scalar!(
+        r#"
+        SELECT success as "success: bool"
+        FROM adguard_schedule_runs
+        WHERE schedule_id = $1 AND phase = $2 AND ran_at > datetime('now', $3)
+        ORDER BY id DESC
+        LIMIT 1
+        "#,
+        schedule_id,
+        phase,
+        cutoff
+    )
+    .fetch_optional(pool)
+    .await?;
+    Ok(last_success == Some(false))
+}
+
+async fn record_run(
+    pool: &SqlitePool,
+    schedule_id: i64,
+    phase: &str,
+    error: Option<anyhow::Error>,
+) -> anyhow::Result<()> {
+    let success = error.is_none();
+    let error = error.map(|e| e.to_string());
+    if let Some(message) = &error {
+        tracing::warn!(schedule_id, phase, error = %message, "AdGuard schedule run failed");
+    } else {
+        tracing::info!(schedule_id, phase, "AdGuard schedule run succeeded");
+    }
+    sqlx::query!(
+        r#"
+        INSERT INTO adguard_schedule_runs (schedule_id, phase, success, error)
+        VALUES ($1, $2, $3, $4)
+        "#,
+        schedule_id,
+        phase,
+        success,
+        error
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+fn blocked_services_json(input: &ScheduleInput) -> anyhow::Result<Option<String>> {
+    if input.blocked_services.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(serde_json::to_string(&input.blocked_services)?))
+}
+
+fn merge_ids(current: &[String], extra: &[String]) -> Vec<String> {
+    let mut ids: Vec<String> = current.iter().chain(extra).cloned().collect();
+    ids.sort();
+    ids.dedup();
+    ids
+}
+
+/// Ids in `extra` that are not already in `current`.
+fn added_ids(current: &[String], extra: &[String]) -> Vec<String> {
+    extra
+        .iter()
+        .filter(|id| !current.contains(id))
+        .cloned()
+        .collect()
+}
+
+fn remove_ids(current: &[String], removed: &[String]) -> Vec<String> {
+    current
+        .iter()
+        .filter(|id| !removed.contains(id))
+        .cloned()
+        .collect()
+}
+
+fn round_up_to_seconds(duration: Duration) -> Duration {
+    Duration::from_secs(duration.as_millis().div_ceil(1000) as u64)
+}
+
+fn parse_time(value: &str) -> Result<NaiveTime, chrono::ParseError> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M")
+}
+
+fn weekday_name(day: Weekday) -> String {
+    day.to_string().to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn schedule(days: &[&str], start: &str, end: &str) -> Schedule {
+        Schedule {
+            id: 1,
+            name: "TV evenings".to_string(),
+            client_name: Some("TV".to_string()),
+            action: ScheduleAction::DisableProtection,
+            blocked_services: Vec::new(),
+            days: days.iter().map(ToString::to_string).collect(),
+            start_time: start.to_string(),
+            end_time: end.to_string(),
+            enabled: true,
+            active: false,
+            created_at: "2024-01-01 00:00:00".to_string(),
+            restore_state: None,
+        }
+    }
+
+    fn at(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(year, month, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_is_within_window_same_day() {
+        // 2024-01-15 is a Monday.
+        let schedule = schedule(&["mon", "tue", "wed", "thu", "fri"], "19:00", "21:00");
+
+        assert!(schedule.is_within_window(at(2024, 1, 15, 19, 0)));
+        assert!(schedule.is_within_window(at(2024, 1, 15, 20, 59)));
+        assert!(!schedule.is_within_window(at(2024, 1, 15, 21, 0)));
+        assert!(!schedule.is_within_window(at(2024, 1, 15, 18, 59)));
+        assert!(!schedule.is_within_window(at(2024, 1, 20, 19, 30)));
+    }
+
+    #[test]
+    fn test_is_within_window_across_midnight() {
+        let schedule = schedule(&["fri"], "22:00", "06:00");
+
+        // Friday 2024-01-19 late evening and early Saturday belong to the window.
+        assert!(schedule.is_within_window(at(2024, 1, 19, 23, 0)));
+        assert!(schedule.is_within_window(at(2024, 1, 20, 5, 59)));
+        assert!(!schedule.is_within_window(at(2024, 1, 20, 6, 0)));
+        // Early Friday belongs to a Thursday window, which is not scheduled.
+        assert!(!schedule.is_within_window(at(2024, 1, 19, 3, 0)));
+    }
+
+    #[test]
+    fn test_validate_normalizes_days_and_times() {
+        let input = ScheduleInput {
+            name: " Night ".to_string(),
+            client_name: Some(" ".to_string()),
+            action: ScheduleAction::BlockServices,
+            blocked_services: vec!["youtube".to_string(), "tiktok".to_string()],
+            days: vec!["Sunday".to_string(), "mon".to_string(), "SUN".to_string()],
+            start_time: "22:00".to_string(),
+            end_time: "6:00".to_string(),
+            enabled: true,
+        }
+        .validate()
+        .unwrap();
+
+        assert_eq!(input.name, "Night");
+        assert!(input.client_name.is_none());
+        assert_eq!(input.days, vec!["mon", "sun"]);
+        assert_eq!(input.end_time, "06:00");
+        assert_eq!(input.blocked_services, vec!["tiktok", "youtube"]);
+    }
+
+    #[test]
+    fn test_validate_requires_blocked_services_for_block_action() {
+        let result = ScheduleInput {
+            name: "Night".to_string(),
+            client_name: None,
+            action: ScheduleAction::BlockServices,
+            blocked_services: Vec::new(),
+            days: vec!["mon".to_string()],
+            start_time: "22:00".to_string(),
+            end_time: "06:00".to_string(),
+            enabled: true,
+        }
+        .validate();
+
+        assert_eq!(
+            result.unwrap_err(),
+            "blocked_services is required for block_services"
+        );
+    }
+
+    #[test]
+    fn test_overlaps_same_target_across_midnight_and_week_end() {
+        let input = |days: &[&str], start: &str, end: &str| ScheduleInput {
+            name: "Late".to_string(),
+            client_name: Some("TV".to_string()),
+            action: ScheduleAction::DisableProtection,
+            blocked_services: Vec::new(),
+            days: days.iter().map(ToString::to_string).collect(),
+            start_time: start.to_string(),
+            end_time: end.to_string(),
+            enabled: true,
+        };
+        let sunday_night = schedule(&["sun"], "23:00", "01:00");
+
+        assert!(input(&["mon"], "00:30", "02:00").overlaps(&sunday_night));
+        assert!(input(&["sun"], "22:00", "23:30").overlaps(&sunday_night));
+        assert!(!input(&["mon"], "01:00", "02:00").overlaps(&sunday_night));
+        assert!(!input(&["sat"], "23:00", "01:00").overlaps(&sunday_night));
+
+        let mut other_client = input(&["mon"], "00:30", "02:00");
+        other_client.client_name = Some("Phone".to_string());
+        assert!(!other_client.overlaps(&sunday_night));
+
+        let mut disabled = input(&["mon"], "00:30", "02:00");
+        disabled.enabled = false;
+        assert!(!disabled.overlaps(&sunday_night));
+    }
+}
@@ -0,0 +1,320 @@
+use serde::Serialize;
+
+use crate::services::adguard::{AdguardService, FilterList, RewriteEntry};
+
+/// What a sync pass changed on one replica, or why it stopped.
+#[derive(Debug, Default, Serialize)]
+pub struct ReplicaSyncReport {
+    pub name: String,
+    pub user_rules_updated: bool,
+    pub rewrites_added: usize,
+    pub rewrites_removed: usize,
+    pub filters_added: usize,
+    pub filters_updated: usize,
+    pub filters_removed: usize,
+    pub clients_added: usize,
+    pub clients_updated: usize,
+    pub clients_removed: usize,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SyncResponse {
+    pub replicas: Vec<ReplicaSyncReport>,
+}
+
+/// Snapshot of the primary's settings that replicas are made to match.
+struct PrimaryConfig {
+    user_rules: Vec<String>,
+    rewrites: Vec<RewriteEntry>,
+    filters: Vec<FilterList>,
+    clients: Vec<serde_json::Value>,
+}
+
+/// Copies user rules, rewrites, filter lists and persistent clients from `primary`
+/// to each of its replicas. Replicas are synced concurrently; a failure on one
+/// replica is reported without affecting the others.
+pub async fn sync_replicas(
+    primary: &AdguardService,
+) -> Result<Vec<ReplicaSyncReport>, anyhow::Error> {
+    if primary.replicas().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let config = PrimaryConfig {
+        user_rules: primary.get_user_rules().await?,
+        rewrites: primary.get_rewrites().await?,
+        filters: primary.get_filter_lists().await?,
+        clients: primary.get_raw_clients().await?,
+    };
+
+    let reports = futures_util::future::join_all(primary.replicas().iter().map(|replica| {
+        let config = &config;
+        async move {
+            let mut report = ReplicaSyncReport {
+                name: replica.name().to_string(),
+                ..Default::default()
+            };
+            if let Err(e) = sync_replica(replica, config, &mut report).await {
+                tracing::warn!(replica = %replica.name(), error = %e, "AdGuard replica sync failed");
+                report.error = Some(e.to_string());
+            }
+            report
+        }
+    }))
+    .await;
+
+    Ok(reports)
+}
+
+async fn sync_replica(
+    replica: &AdguardService,
+    config: &PrimaryConfig,
+    report: &mut ReplicaSyncReport,
+) -> Result<(), anyhow::Error> {
+    if replica.get_user_rules().await? != config.user_rules {
+        replica.set_user_rules(&config.user_rules).await?;
+        report.user_rules_updated = true;
+    }
+
+    let rewrites = replica.get_rewrites().await?;
+    for entry in rewrites.iter().filter(|e| !config.rewrites.contains(e)) {
+        replica.delete_rewrite(entry).await?;
+        report.rewrites_removed += 1;
+    }
+    for entry in config.rewrites.iter().filter(|e| !rewrites.contains(e)) {
+        replica.add_rewrite(entry).await?;
+        report.rewrites_added += 1;
+    }
+
+    let filters = replica.get_filter_lists().await?;
+    for list in &filters {
+        match config.filters.iter().find(|f| same_filter(f, list)) {
+            None => {
+                replica.remove_filter_list(list).await?;
+                report.filters_removed += 1;
+            }
+            Some(wanted) if wanted.name != list.name || wanted.enabled != list.enabled => {
+                replica
+                    .update_filter_list(list, &wanted.name, wanted.enabled)
+                    .await?;
+                report.filters_updated += 1;
+            }
+            Some(_) => {}
+        }
+    }
+    for wanted in &config.filters {
+        if filters.iter().any(|f| same_filter(f, wanted)) {
+            continue;
+        }
+        replica
+            .add_filter_list(&wanted.name, &wanted.url, wanted.whitelist)
+            .await?;
+        report.filters_added += 1;
+        if !wanted.enabled {
+            replica
+                .update_filter_list(wanted, &wanted.name, false)
+                .await?;
+        }
+    }
+
+    let clients = replica.get_raw_clients().await?;
+    for client in &clients {
+        let name = client_name(client);
+        if !config.clients.iter().any(|c| client_name(c) == name) {
+            replica.delete_client(name).await?;
+            report.clients_removed += 1;
+        }
+    }
+    for wanted in &config.clients {
+        let name = client_name(wanted);
+        match clients.iter().find(|c| client_name(c) == name) {
+            None => {
+                replica.add_client(wanted).await?;
+                report.clients_added += 1;
+            }
+            Some(existing) if existing != wanted => {
+                replica.replace_client(name, wanted).await?;
+                report.clients_updated += 1;
+            }
+            Some(_) => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Filter lists get instance-specific ids, so they are matched by source instead.
+fn same_filter(a: &FilterList, b: &FilterList) -> bool {
+    a.url == b.url && a.whitelist == b.whitelist
+}
+
+fn client_name(client: &serde_json::Value) -> &str {
+    client["name"].as_str().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use wiremock::matchers::{body_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn service(base_url: &str) -> AdguardService {
+        AdguardService::new(base_url, "admin", "password", false).unwrap()
+    }
+
+    async fn mount_config(
+        server: &MockServer,
+        rules: serde_json::Value,
+        rewrites: serde_json::Value,
+        filters: serde_json::Value,
+        clients: serde_json::Value,
+    ) {
+        Mock::given(method("GET"))
+            .and(path("/control/filtering/status"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "enabled": true,
+                "interval": 24,
+                "filters": filters,
+                "whitelist_filters": [],
+                "user_rules": rules
+            })))
+            .mount(server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/control/rewrite/list"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(rewrites))
+            .mount(server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/control/clients"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "clients": clients,
+                "auto_clients": [],
+                "supported_tags": []
+            })))
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn test_sync_replicas_without_replicas_is_noop() {
+        let reports = sync_replicas(&service("http://nonexistent:9999"))
+            .await
+            .unwrap();
+        assert!(reports.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_sync_replicas_applies_diff() {
+        let primary_server = MockServer::start().await;
+        let replica_server = MockServer::start().await;
+
+        let kept_client =
+            json!({ "name": "tv", "ids": ["192.168.1.20"], "filtering_enabled": true });
+        mount_config(
+            &primary_server,
+            json!(["||ads.example.com^"]),
+            json!([{ "domain": "nas.lan", "answer": "192.168.1.10" }]),
+            json!([
+                { "id": 1, "name": "AdGuard DNS filter", "url": "https://example.com/a.txt", "enabled": true, "rules_count": 10 },
+                { "id": 2, "name": "Extra", "url": "https://example.com/b.txt", "enabled": true, "rules_count": 5 }
+            ]),
+            json!([kept_client]),
+        )
+        .await;
+        mount_config(
+            &replica_server,
+            json!(["||ads.example.com^"]),
+            json!([{ "domain": "old.lan", "answer": "192.168.1.99" }]),
+            json!([
+                { "id": 7, "name": "Old name", "url": "https://example.com/a.txt", "enabled": true, "rules_count": 10 },
+                { "id": 8, "name": "Stale", "url": "https://example.com/c.txt", "enabled": true, "rules_count": 1 }
+            ]),
+            json!([{ "name": "laptop", "ids": ["192.168.1.30"] }]),
+        )
+        .await;
+
+        let expectations = [
+            (
+                "/control/rewrite/delete",
+                json!({ "domain": "old.lan", "answer": "192.168.1.99" }),
+            ),
+            (
+                "/control/rewrite/add",
+                json!({ "domain": "nas.lan", "answer": "192.168.1.10" }),
+            ),
+            (
+                "/control/filtering/set_url",
+                json!({
+                    "url": "https://example.com/a.txt",
+                    "whitelist": false,
+                    "data": { "name": "AdGuard DNS filter", "url": "https://example.com/a.txt", "enabled": true }
+                }),
+            ),
+            (
+                "/control/filtering/remove_url",
+                json!({ "url": "https://example.com/c.txt", "whitelist": false }),
+            ),
+            (
+                "/control/filtering/add_url",
+                json!({ "name": "Extra", "url": "https://example.com/b.txt", "whitelist": false }),
+            ),
+            ("/control/clients/delete", json!({ "name": "laptop" })),
+            ("/control/clients/add", kept_client),
+        ];
+        for (endpoint, body) in expectations {
+            Mock::given(method("POST"))
+                .and(path(endpoint))
+                .and(body_json(body))
+                .respond_with(ResponseTemplate::new(200))
+                .expect(1)
+                .mount(&replica_server)
+                .await;
+        }
+        Mock::given(method("POST"))
+            .and(path("/control/filtering/set_rules"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&replica_server)
+            .await;
+
+        let replica = service(&replica_server.uri()).with_name("replica");
+        let primary = service(&primary_server.uri()).with_replicas(vec![replica]);
+        let reports = sync_replicas(&primary).await.unwrap();
+
+        assert_eq!(reports.len(), 1);
+        let report = &reports[0];
+        assert_eq!(report.name, "replica");
+        assert!(report.error.is_none());
+        assert!(!report.user_rules_updated);
+        assert_eq!(report.rewrites_added, 1);
+        assert_eq!(report.rewrites_removed, 1);
+        assert_eq!(report.filters_added, 1);
+        assert_eq!(report.filters_updated, 1);
+        assert_eq!(report.filters_removed, 1);
+        assert_eq!(report.clients_added, 1);
+        assert_eq!(report.clients_removed, 1);
+    }
+
+    #[tokio::test]
+    async fn test_sync_replicas_reports_replica_failure() {
+        let primary_server = MockServer::start().await;
+        let replica_server = MockServer::start().await;
+
+        mount_config(&primary_server, json!([]), json!([]), json!([]), json!([])).await;
+        Mock::given(method("GET"))
+            .and(path("/control/filtering/status"))
+            .respond_with(ResponseTemplate::new(401).set_body_string("unauthorized"))
+            .mount(&replica_server)
+            .await;
+
+        let replica = service(&replica_server.uri()).with_name("replica");
+        let primary = service(&primary_server.uri()).with_replicas(vec![replica]);
+        let reports = sync_replicas(&primary).await.unwrap();
+
+        assert_eq!(reports.len(), 1);
+        assert!(reports[0].error.as_ref().unwrap().contains("401"));
+    }
+}
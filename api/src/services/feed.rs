@@ -1,99 +1,266 @@
+use std::collections::HashMap;
 use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
+use futures_util::StreamExt;
+use serde::Serialize;
 use sqlx::SqlitePool;
-use url::Url;
-
-const MAX_FEED_BYTES: usize = 2 * 1024 * 1024;
+use tokio::time::Instant;
+use url::{Host, Url};
+
+use crate::services::feed_schedule::{self, ScheduleHints};
+use crate::services::ssrf::{GlobalOnlyResolver, NON_GLOBAL_ADDRESS_ERROR, is_global_ip};
+
+pub(crate) const MAX_FEED_BYTES: usize = 2 * 1024 * 1024;
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_REDIRECTS: usize = 5;
+const DEFAULT_PER_HOST_DELAY: Duration = Duration::from_secs(1);
+
+/// Limits for a refresh of all feeds.
+#[derive(Debug, Clone)]
+pub struct RefreshOptions {
+    /// How many hosts are fetched from at the same time.
+    pub max_concurrent_hosts: usize,
+    /// Total time the refresh may take; feeds not fetched by then report an error.
+    pub budget: Duration,
+}
 
-async fn check_dns_is_global(host: &str, port: u16) -> anyhow::Result<()> {
-    let addrs = tokio::net::lookup_host((host, port)).await?;
-    for addr in addrs {
-        let ip = addr.ip();
-        if !is_global_ip(ip) {
-            anyhow::bail!("refusing to fetch non-global address");
+impl Default for RefreshOptions {
+    fn default() -> Self {
+        RefreshOptions {
+            max_concurrent_hosts: 8,
+            budget: Duration::from_secs(120),
         }
     }
-    Ok(())
 }
 
-fn is_global_ip(ip: IpAddr) -> bool {
-    match ip {
-        IpAddr::V4(addr) => {
-            !addr.is_private()
-                && !addr.is_loopback()
-                && !addr.is_link_local()
-                && !addr.is_multicast()
-                && !addr.is_unspecified()
-        }
-        IpAddr::V6(addr) => {
-            !addr.is_loopback()
-                && !addr.is_unicast_link_local()
-                && !addr.is_unspecified()
-                && !addr.is_unique_local()
-                && !addr.is_multicast()
+/// Builds the HTTP client used for feed fetches. Host names only resolve to global
+/// addresses, see `GlobalOnlyResolver`. System proxies are ignored, since a proxy
+/// would resolve and connect to hosts without that check.
+pub fn build_client() -> anyhow::Result<reqwest::Client> {
+    reqwest::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .no_proxy()
+        .dns_resolver(GlobalOnlyResolver)
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .map_err(|e| anyhow::anyhow!("HTTP client error: {}", e))
+}
+
+/// Check run on every URL before it is requested.
+type TargetCheck = Arc<dyn Fn(&Url) -> anyhow::Result<()> + Send + Sync>;
+
+/// Everything that fetches feeds shares one `FeedFetcher`: its client reuses
+/// connections, and its per-host slots keep manual refreshes and the scheduler from
+/// requesting the same host less than `per_host_delay` apart.
+#[derive(Clone)]
+pub struct FeedFetcher {
+    client: reqwest::Client,
+    per_host_delay: Duration,
+    /// Earliest time the next request to each host may start.
+    next_slots: Arc<Mutex<HashMap<String, Instant>>>,
+    check_target: TargetCheck,
+}
+
+impl FeedFetcher {
+    pub fn new() -> anyhow::Result<Self> {
+        Self::with_per_host_delay(DEFAULT_PER_HOST_DELAY)
+    }
+
+    pub fn with_per_host_delay(per_host_delay: Duration) -> anyhow::Result<Self> {
+        Ok(FeedFetcher {
+            client: build_client()?,
+            per_host_delay,
+            next_slots: Arc::new(Mutex::new(HashMap::new())),
+            check_target: Arc::new(check_fetch_target),
+        })
+    }
+
+    /// Replaces `check_fetch_target`, so tests can reach a local mock server.
+    #[cfg(test)]
+    pub(crate) fn with_target_check(
+        mut self,
+        check: impl Fn(&Url) -> anyhow::Result<()> + Send + Sync + 'static,
+    ) -> Self {
+        self.check_target = Arc::new(check);
+        self
+    }
+
+    /// Waits until a request to `url`'s host may start and claims that slot. A slot
+    /// after `deadline` is not claimed and the wait never ends, so callers bound it
+    /// with a timeout at the same deadline.
+    async fn wait_for_host(&self, url: &str, deadline: Option<Instant>) {
+        let Some(host) = Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_lowercase))
+        else {
+            return;
+        };
+
+        let slot = {
+            let mut slots = self.next_slots.lock().unwrap();
+            let now = Instant::now();
+            slots.retain(|_, slot| *slot > now);
+            let start = slots.get(&host).copied().unwrap_or(now).max(now);
+            if deadline.is_some_and(|deadline| start > deadline) {
+                None
+            } else {
+                slots.insert(host, start + self.per_host_delay);
+                Some(start)
+            }
+        };
+        match slot {
+            Some(start) => tokio::time::sleep_until(start).await,
+            None => std::future::pending().await,
         }
     }
 }
 
-#[derive(Debug)]
-pub struct FeedResult {
-    pub feed_id: i64,
-    pub url: String,
-    pub items_inserted: usize,
-    pub duplicates_skipped: usize,
-    pub error: Option<String>,
+/// A response reached by following redirects from the requested URL.
+pub(crate) struct FetchedResponse {
+    pub response: reqwest::Response,
+    pub final_url: Url,
+    /// Every hop was a 301 or 308, so the requested URL should be replaced.
+    pub permanently_moved: bool,
 }
 
-pub async fn refresh_feed(pool: &SqlitePool, feed_id: i64, url: &str) -> FeedResult {
-    let mut result = FeedResult {
-        feed_id,
-        url: url.to_string(),
-        items_inserted: 0,
-        duplicates_skipped: 0,
-        error: None,
-    };
+impl FeedFetcher {
+    /// Sends a GET to `url`, following up to `MAX_REDIRECTS` redirects. Redirects are
+    /// followed here rather than by reqwest so that every hop goes through
+    /// `check_fetch_target` before it is requested. `configure` is applied to each
+    /// hop's request, e.g. to add conditional headers.
+    pub(crate) async fn send_following_redirects(
+        &self,
+        url: &Url,
+        configure: impl Fn(reqwest::RequestBuilder) -> reqwest::RequestBuilder,
+    ) -> anyhow::Result<FetchedResponse> {
+        let mut current = url.clone();
+        let mut permanent = true;
+
+        for _ in 0..=MAX_REDIRECTS {
+            (self.check_target)(&current)?;
+
+            let response = configure(self.client.get(current.clone()))
+                .send()
+                .await
+                .map_err(|e| anyhow::anyhow!("Request failed: {:#}", anyhow::Error::from(e)))?;
+            let status = response.status();
+            if !status.is_redirection() || status == reqwest::StatusCode::NOT_MODIFIED {
+                return Ok(FetchedResponse {
+                    response,
+                    permanently_moved: permanent && current != *url,
+                    final_url: current,
+                });
+            }
 
-    let parsed_url = match Url::parse(url) {
-        Ok(parsed) => parsed,
-        Err(e) => {
-            result.error = Some(format!("Invalid URL: {}", e));
-            return result;
+            let location = response
+                .headers()
+                .get(reqwest::header::LOCATION)
+                .and_then(|v| v.to_str().ok())
+                .ok_or_else(|| anyhow::anyhow!("HTTP error: {} without Location header", status))?;
+            current = current
+                .join(location)
+                .map_err(|e| anyhow::anyhow!("Invalid redirect location: {}", e))?;
+            permanent &= matches!(
+                status,
+                reqwest::StatusCode::MOVED_PERMANENTLY | reqwest::StatusCode::PERMANENT_REDIRECT
+            );
         }
-    };
 
-    if parsed_url.scheme() != "https" {
-        result.error = Some("URL must use HTTPS scheme".to_string());
-        return result;
+        anyhow::bail!("Too many redirects (max {})", MAX_REDIRECTS)
     }
+}
 
-    if !parsed_url.username().is_empty() || parsed_url.password().is_some() {
-        result.error = Some("URL must not contain userinfo".to_string());
-        return result;
+/// Checks that `url` is safe to fetch: HTTPS, no userinfo, and not an IP literal
+/// outside the global ranges. Host names are checked when they are resolved, by the
+/// `GlobalOnlyResolver` installed in `build_client`.
+pub(crate) fn check_fetch_target(url: &Url) -> anyhow::Result<()> {
+    if url.scheme() != "https" {
+        anyhow::bail!("URL must use HTTPS scheme");
     }
 
-    let Some(host) = parsed_url.host_str() else {
-        result.error = Some("URL missing host".to_string());
-        return result;
+    if !url.username().is_empty() || url.password().is_some() {
+        anyhow::bail!("URL must not contain userinfo");
+    }
+
+    let ip = match url.host() {
+        None => anyhow::bail!("URL missing host"),
+        Some(Host::Domain(_)) => return Ok(()),
+        Some(Host::Ipv4(ip)) => IpAddr::V4(ip),
+        Some(Host::Ipv6(ip)) => IpAddr::V6(ip),
     };
+    if !is_global_ip(ip) {
+        anyhow::bail!(NON_GLOBAL_ADDRESS_ERROR);
+    }
+    Ok(())
+}
 
-    let port = parsed_url.port_or_known_default().unwrap_or(443);
+#[derive(Debug, Serialize)]
+pub struct FeedResult {
+    pub feed_id: i64,
+    pub url: String,
+    /// New URL stored for the feed after it permanently redirected.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub moved_to: Option<String>,
+    pub items_inserted: usize,
+    pub duplicates_skipped: usize,
+    pub error: Option<String>,
+}
 
-    if let Err(e) = check_dns_is_global(host, port).await {
-        result.error = Some(e.to_string());
-        return result;
+impl FeedResult {
+    fn new(feed_id: i64, url: &str) -> Self {
+        FeedResult {
+            feed_id,
+            url: url.to_string(),
+            moved_to: None,
+            items_inserted: 0,
+            duplicates_skipped: 0,
+            error: None,
+        }
     }
+}
 
-    let client = reqwest::Client::builder()
-        .timeout(Duration::from_secs(30))
-        .redirect(reqwest::redirect::Policy::none())
-        .build()
-        .map_err(|e| anyhow::anyhow!("HTTP client error: {}", e))
-        .ok();
+/// Fetches one feed once its host's next slot comes up, stores new items and
+/// schedules its next fetch.
+pub async fn refresh_feed(
+    pool: &SqlitePool,
+    fetcher: &FeedFetcher,
+    feed_id: i64,
+    url: &str,
+) -> FeedResult {
+    fetcher.wait_for_host(url, None).await;
+    fetch_and_schedule(pool, fetcher, feed_id, url).await
+}
+
+async fn fetch_and_schedule(
+    pool: &SqlitePool,
+    fetcher: &FeedFetcher,
+    feed_id: i64,
+    url: &str,
+) -> FeedResult {
+    let mut result = FeedResult::new(feed_id, url);
+    let mut hints = ScheduleHints::default();
+    fetch_feed(pool, fetcher, url, &mut result, &mut hints).await;
+    schedule_next_fetch(pool, &result, hints).await;
+    result
+}
+
+async fn fetch_feed(
+    pool: &SqlitePool,
+    fetcher: &FeedFetcher,
+    url: &str,
+    result: &mut FeedResult,
+    hints: &mut ScheduleHints,
+) {
+    let feed_id = result.feed_id;
 
-    let Some(client) = client else {
-        result.error = Some("Failed to build HTTP client".to_string());
-        return result;
+    let parsed_url = match Url::parse(url) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            result.error = Some(format!("Invalid URL: {}", e));
+            return;
+        }
     };
 
     let feed_meta = match sqlx::query!(
@@ -112,79 +279,65 @@ pub async fn refresh_feed(pool: &SqlitePool, feed_id: i64, url: &str) -> FeedRes
         }
     };
 
-    let mut request = client.get(parsed_url.clone());
+    let fetched = fetcher
+        .send_following_redirects(&parsed_url, |mut request| {
+            if let Some(ref meta) = feed_meta {
+                if let Some(etag) = &meta.etag {
+                    request = request.header("If-None-Match", etag);
+                }
+                if let Some(last_modified) = &meta.last_modified {
+                    request = request.header("If-Modified-Since", last_modified);
+                }
+            }
+            request
+        })
+        .await;
 
-    if let Some(ref meta) = feed_meta {
-        if let Some(etag) = &meta.etag {
-            request = request.header("If-None-Match", etag);
-        }
-        if let Some(last_modified) = &meta.last_modified {
-            request = request.header("If-Modified-Since", last_modified);
+    let (response, moved_to) = match fetched {
+        Ok(fetched) => {
+            let moved_to = fetched.permanently_moved.then_some(fetched.final_url);
+            (fetched.response, moved_to)
         }
-    }
-
-    let response = request.send().await;
-
-    let response = match response {
-        Ok(r) => r,
         Err(e) => {
-            result.error = Some(format!("Request failed: {}", e));
-            return result;
+            result.error = Some(e.to_string());
+            return;
         }
     };
 
     let status = response.status();
+    if let Some(new_url) = moved_to
+        && (status.is_success() || status == reqwest::StatusCode::NOT_MODIFIED)
+    {
+        update_feed_url(pool, result, new_url).await;
+    }
+    let header = |name: &str| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.to_string())
+    };
+    hints.retry_after = header("retry-after")
+        .and_then(|value| feed_schedule::parse_retry_after(&value, chrono::Utc::now()));
+    hints.max_age = header("cache-control").and_then(|value| feed_schedule::parse_max_age(&value));
 
     if status == reqwest::StatusCode::NOT_MODIFIED {
-        if let Err(e) = sqlx::query!(
-            r#"
-            UPDATE feeds SET last_fetched_at = datetime('now'), last_error = NULL WHERE id = $1
-            "#,
-            feed_id
-        )
-        .execute(pool)
-        .await
-        {
-            tracing::warn!(error = ?e, feed_id, "Failed to update feed metadata");
-        }
-        return result;
+        return;
     }
 
     if !status.is_success() {
-        let error_msg = format!("HTTP error: {}", status);
-        result.error = Some(error_msg.clone());
-        let error_for_sql = result.error.as_deref();
-        if let Err(e) = sqlx::query!(
-            r#"
-            UPDATE feeds SET last_fetched_at = datetime('now'), last_error = $1 WHERE id = $2
-            "#,
-            error_for_sql,
-            feed_id
-        )
-        .execute(pool)
-        .await
-        {
-            tracing::warn!(error = ?e, feed_id, "Failed to update feed metadata");
-        }
-        return result;
+        result.error = Some(format!("HTTP error: {}", status));
+        return;
     }
 
-    let etag = response
-        .headers()
-        .get("etag")
-        .and_then(|v| v.to_str().ok())
-        .map(|s| s.to_string());
-    let last_modified = response
-        .headers()
-        .get("last-modified")
-        .and_then(|v| v.to_str().ok())
-        .map(|s| s.to_string());
+    let etag = header("etag");
+    let last_modified = header("last-modified");
 
     let bytes = match response.bytes().await {
         Ok(b) => b,
         Err(e) => {
             result.error = Some(format!("Failed to read body: {}", e));
-            return result;
+            return;
         }
     };
 
@@ -194,7 +347,7 @@ pub async fn refresh_feed(pool: &SqlitePool, feed_id: i64, url: &str) -> FeedRes
             bytes.len(),
             MAX_FEED_BYTES
         ));
-        return result;
+        return;
     }
 
     let body = String::from_utf8_lossy(&bytes);
@@ -204,25 +357,14 @@ pub async fn refresh_feed(pool: &SqlitePool, feed_id: i64, url: &str) -> FeedRes
     let feed = match feed {
         Ok(f) => f,
         Err(e) => {
-            let error_msg = format!("Failed to parse RSS: {}", e);
-            result.error = Some(error_msg.clone());
-            let error_for_sql = result.error.as_deref();
-            if let Err(e) = sqlx::query!(
-                r#"
-                UPDATE feeds SET last_fetched_at = datetime('now'), last_error = $1 WHERE id = $2
-                "#,
-                error_for_sql,
-                feed_id
-            )
-            .execute(pool)
-            .await
-            {
-                tracing::warn!(error = ?e, feed_id, "Failed to update feed metadata");
-            }
-            return result;
+            result.error = Some(format!("Failed to parse RSS: {}", e));
+            return;
         }
     };
 
+    hints.ttl = feed
+        .ttl
+        .map(|minutes| Duration::from_secs(u64::from(minutes) * 60));
     let feed_title = feed.title.map(|t| t.content);
 
     let mut inserted = 0;
@@ -284,9 +426,7 @@ pub async fn refresh_feed(pool: &SqlitePool, feed_id: i64, url: &str) -> FeedRes
         UPDATE feeds SET
             title = $1,
             etag = $2,
-            last_modified = $3,
-            last_fetched_at = datetime('now'),
-            last_error = NULL
+            last_modified = $3
         WHERE id = $4
         "#,
         feed_title,
@@ -299,28 +439,436 @@ pub async fn refresh_feed(pool: &SqlitePool, feed_id: i64, url: &str) -> FeedRes
     {
         tracing::warn!(error = ?e, feed_id, "Failed to update feed metadata");
     }
+}
 
-    result
+/// Stores the URL a feed permanently moved to. If another feed already uses that URL
+/// the old one is kept, since merging subscriptions is left to the user, and the
+/// fetch reports an error so the feed backs off and the conflict shows up in
+/// `last_error`.
+async fn update_feed_url(pool: &SqlitePool, result: &mut FeedResult, new_url: Url) {
+    let feed_id = result.feed_id;
+    let new_url = new_url.to_string();
+    match sqlx::query!("UPDATE feeds SET url = $1 WHERE id = $2", new_url, feed_id)
+        .execute(pool)
+        .await
+    {
+        Ok(_) => {
+            tracing::info!(feed_id, from = %result.url, to = %new_url, "Feed moved permanently");
+            result.moved_to = Some(new_url);
+        }
+        Err(sqlx::Error::Database(db_err)) if db_err.is_unique_violation() => {
+            result.error = Some(format!(
+                "Feed moved permanently to {}, which another feed already uses",
+                new_url
+            ));
+        }
+        Err(e) => {
+            tracing::warn!(error = ?e, feed_id, to = %new_url, "Failed to store moved feed URL");
+        }
+    }
 }
 
-pub async fn refresh_all_feeds(pool: &SqlitePool) -> anyhow::Result<Vec<FeedResult>> {
-    #[derive(Debug)]
-    struct FeedRow {
-        id: i64,
-        url: String,
+/// Records the outcome of a fetch and when the feed is due next.
+async fn schedule_next_fetch(pool: &SqlitePool, result: &FeedResult, mut hints: ScheduleHints) {
+    let feed_id = result.feed_id;
+
+    let previous_errors = match sqlx::query!(
+        "SELECT error_count, refresh_interval_minutes FROM feeds WHERE id = $1",
+        feed_id
+    )
+    .fetch_optional(pool)
+    .await
+    {
+        Ok(Some(row)) => {
+            hints.configured_interval = row
+                .refresh_interval_minutes
+                .and_then(|minutes| u64::try_from(minutes).ok())
+                .map(|minutes| Duration::from_secs(minutes * 60));
+            row.error_count
+        }
+        Ok(None) => 0,
+        Err(e) => {
+            tracing::warn!(error = ?e, feed_id, "Failed to load feed schedule settings");
+            0
+        }
+    };
+    let error_count = if result.error.is_some() {
+        previous_errors + 1
+    } else {
+        0
+    };
+
+    // Average spacing of the most recent dated items. `pub_date` may carry a
+    // timezone suffix, so only its first 19 characters are handed to julianday.
+    match sqlx::query_scalar!(
+        r#"
+        SELECT CAST((julianday(MAX(d)) - julianday(MIN(d))) * 86400 / (COUNT(*) - 1) AS INTEGER)
+            as "seconds: i64"
+        FROM (
+            SELECT substr(pub_date, 1, 19) as d FROM feed_items
+            WHERE feed_id = $1 AND pub_date IS NOT NULL
+            ORDER BY pub_date DESC
+            LIMIT 10
+        )
+        "#,
+        feed_id
+    )
+    .fetch_one(pool)
+    .await
+    {
+        Ok(seconds) => {
+            hints.posting_interval = seconds
+                .filter(|seconds| *seconds > 0)
+                .map(|seconds| Duration::from_secs(seconds as u64));
+        }
+        Err(e) => tracing::warn!(error = ?e, feed_id, "Failed to estimate posting interval"),
     }
 
-    let feeds = sqlx::query_as!(FeedRow, r#"SELECT id as "id!", url FROM feeds"#)
-        .fetch_all(pool)
-        .await
-        .map_err(|e| anyhow::anyhow!("Failed to load feeds: {}", e))?;
+    let delay_seconds =
+        feed_schedule::next_fetch_delay(&hints, error_count.try_into().unwrap_or(u32::MAX))
+            .as_secs() as i64;
+    let last_error = result.error.as_deref();
 
-    let mut results = Vec::with_capacity(feeds.len());
+    if let Err(e) = sqlx::query!(
+        r#"
+        UPDATE feeds SET
+            last_fetched_at = datetime('now'),
+            last_error = $1,
+            error_count = $2,
+            next_fetch_at = datetime('now', '+' || $3 || ' seconds')
+        WHERE id = $4
+        "#,
+        last_error,
+        error_count,
+        delay_seconds,
+        feed_id
+    )
+    .execute(pool)
+    .await
+    {
+        tracing::warn!(error = ?e, feed_id, "Failed to schedule next feed fetch");
+    }
+}
 
-    for feed in feeds {
-        let result = refresh_feed(pool, feed.id, &feed.url).await;
-        results.push(result);
+#[derive(Debug)]
+struct FeedRow {
+    id: i64,
+    url: String,
+}
+
+pub async fn refresh_all_feeds(
+    pool: &SqlitePool,
+    fetcher: &FeedFetcher,
+) -> anyhow::Result<Vec<FeedResult>> {
+    refresh_all_feeds_with(pool, fetcher, &RefreshOptions::default()).await
+}
+
+pub async fn refresh_all_feeds_with(
+    pool: &SqlitePool,
+    fetcher: &FeedFetcher,
+    options: &RefreshOptions,
+) -> anyhow::Result<Vec<FeedResult>> {
+    let feeds = sqlx::query_as!(
+        FeedRow,
+        r#"SELECT id as "id!", url FROM feeds WHERE COALESCE(enabled, TRUE)"#
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to load feeds: {}", e))?;
+
+    refresh_feeds(pool, fetcher, feeds, options).await
+}
+
+/// Refreshes the feeds whose `next_fetch_at` has passed, or that were never fetched.
+pub async fn refresh_due_feeds(
+    pool: &SqlitePool,
+    fetcher: &FeedFetcher,
+) -> anyhow::Result<Vec<FeedResult>> {
+    let feeds = sqlx::query_as!(
+        FeedRow,
+        r#"
+        SELECT id as "id!", url FROM feeds
+        WHERE COALESCE(enabled, TRUE)
+            AND (next_fetch_at IS NULL OR next_fetch_at <= datetime('now'))
+        "#
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| anyhow::anyhow!("Failed to load due feeds: {}", e))?;
+
+    refresh_feeds(pool, fetcher, feeds, &RefreshOptions::default()).await
+}
+
+/// Refreshes `feeds` through `fetcher`. Hosts are fetched concurrently, feeds on the
+/// same host one after another as the fetcher's per-host slots come up.
+async fn refresh_feeds(
+    pool: &SqlitePool,
+    fetcher: &FeedFetcher,
+    feeds: Vec<FeedRow>,
+    options: &RefreshOptions,
+) -> anyhow::Result<Vec<FeedResult>> {
+    if feeds.is_empty() {
+        return Ok(Vec::new());
     }
 
+    let deadline = Instant::now() + options.budget;
+
+    let mut results: Vec<FeedResult> =
+        futures_util::stream::iter(group_by_host(feeds).into_iter().map(|group| async move {
+            let mut results = Vec::with_capacity(group.len());
+            for feed in group {
+                let result = tokio::time::timeout_at(deadline, async {
+                    fetcher.wait_for_host(&feed.url, Some(deadline)).await;
+                    fetch_and_schedule(pool, fetcher, feed.id, &feed.url).await
+                })
+                .await
+                .unwrap_or_else(|_| {
+                    let mut result = FeedResult::new(feed.id, &feed.url);
+                    result.error = Some("Refresh time budget exhausted".to_string());
+                    result
+                });
+                results.push(result);
+            }
+            results
+        }))
+        .buffer_unordered(options.max_concurrent_hosts.max(1))
+        .flat_map(futures_util::stream::iter)
+        .collect()
+        .await;
+
+    results.sort_by_key(|result| result.feed_id);
     Ok(results)
 }
+
+/// Groups feeds by host, keeping each group in feed order. Feeds whose URL does not
+/// parse get a group of their own; `refresh_feed` reports the error.
+fn group_by_host(feeds: Vec<FeedRow>) -> Vec<Vec<FeedRow>> {
+    let mut groups: Vec<Vec<FeedRow>> = Vec::new();
+    let mut by_host: HashMap<String, usize> = HashMap::new();
+    for feed in feeds {
+        let host = Url::parse(&feed.url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_lowercase));
+        match host {
+            Some(host) => match by_host.get(&host) {
+                Some(&index) => groups[index].push(feed),
+                None => {
+                    by_host.insert(host, groups.len());
+                    groups.push(vec![feed]);
+                }
+            },
+            None => groups.push(vec![feed]),
+        }
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const RSS: &str = r#"<?xml version="1.0"?>
+<rss version="2.0"><channel><title>Moved</title>
+  <item><title>Hello</title><link>https://example.com/hello</link><guid>hello</guid></item>
+</channel></rss>"#;
+
+    /// A fetcher that lets requests to `server` through and checks every other
+    /// target as usual.
+    fn fetcher_for(server: &MockServer) -> FeedFetcher {
+        let port = server.address().port();
+        FeedFetcher::with_per_host_delay(Duration::ZERO)
+            .unwrap()
+            .with_target_check(move |url| {
+                if url.host_str() == Some("127.0.0.1") && url.port() == Some(port) {
+                    Ok(())
+                } else {
+                    check_fetch_target(url)
+                }
+            })
+    }
+
+    async fn redirect(server: &MockServer, from: &str, status: u16, to: &str) {
+        Mock::given(method("GET"))
+            .and(path(from))
+            .respond_with(ResponseTemplate::new(status).insert_header("Location", to))
+            .mount(server)
+            .await;
+    }
+
+    async fn serve_feed(server: &MockServer, at: &str) {
+        Mock::given(method("GET"))
+            .and(path(at))
+            .respond_with(ResponseTemplate::new(200).set_body_string(RSS))
+            .mount(server)
+            .await;
+    }
+
+    async fn pool_with_feed(url: &str) -> (SqlitePool, i64) {
+        let pool = SqlitePool::connect(":memory:").await.unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        let feed_id = sqlx::query_scalar!("INSERT INTO feeds (url) VALUES ($1) RETURNING id", url)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        (pool, feed_id)
+    }
+
+    async fn stored_feed(pool: &SqlitePool, feed_id: i64) -> (String, Option<String>) {
+        let row = sqlx::query!("SELECT url, last_error FROM feeds WHERE id = $1", feed_id)
+            .fetch_one(pool)
+            .await
+            .unwrap();
+        (row.url, row.last_error)
+    }
+
+    #[tokio::test]
+    async fn test_redirects_to_insecure_or_private_targets_are_rejected() {
+        let server = MockServer::start().await;
+        redirect(&server, "/insecure", 301, "http://example.com/feed.xml").await;
+        redirect(&server, "/private", 302, "https://10.0.0.1/feed.xml").await;
+        let fetcher = fetcher_for(&server);
+
+        for (from, error) in [
+            ("/insecure", "URL must use HTTPS scheme"),
+            ("/private", NON_GLOBAL_ADDRESS_ERROR),
+        ] {
+            let url = Url::parse(&format!("{}{}", server.uri(), from)).unwrap();
+            let result = fetcher.send_following_redirects(&url, |r| r).await;
+            assert_eq!(result.err().unwrap().to_string(), error);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_redirects_stop_after_max_redirects() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/loop"))
+            .respond_with(ResponseTemplate::new(302).insert_header("Location", "/loop"))
+            .expect(MAX_REDIRECTS as u64 + 1)
+            .mount(&server)
+            .await;
+
+        let url = Url::parse(&format!("{}/loop", server.uri())).unwrap();
+        let result = fetcher_for(&server)
+            .send_following_redirects(&url, |r| r)
+            .await;
+
+        assert_eq!(
+            result.err().unwrap().to_string(),
+            format!("Too many redirects (max {})", MAX_REDIRECTS)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_refresh_stores_url_only_when_every_hop_is_permanent() {
+        let server = MockServer::start().await;
+        redirect(&server, "/old.xml", 301, "/mid.xml").await;
+        redirect(&server, "/mid.xml", 308, "/new.xml").await;
+        redirect(&server, "/temporary.xml", 301, "/elsewhere.xml").await;
+        redirect(&server, "/elsewhere.xml", 302, "/new.xml").await;
+        serve_feed(&server, "/new.xml").await;
+        let fetcher = fetcher_for(&server);
+
+        let old_url = format!("{}/old.xml", server.uri());
+        let new_url = format!("{}/new.xml", server.uri());
+        let (pool, feed_id) = pool_with_feed(&old_url).await;
+        let result = refresh_feed(&pool, &fetcher, feed_id, &old_url).await;
+        assert_eq!(result.error, None);
+        assert_eq!(result.items_inserted, 1);
+        assert_eq!(result.moved_to.as_deref(), Some(new_url.as_str()));
+        assert_eq!(stored_feed(&pool, feed_id).await.0, new_url);
+
+        let temporary_url = format!("{}/temporary.xml", server.uri());
+        let (pool, feed_id) = pool_with_feed(&temporary_url).await;
+        let result = refresh_feed(&pool, &fetcher, feed_id, &temporary_url).await;
+        assert_eq!(result.error, None);
+        assert_eq!(result.items_inserted, 1);
+        assert_eq!(result.moved_to, None);
+        assert_eq!(stored_feed(&pool, feed_id).await.0, temporary_url);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_reports_move_to_url_of_another_feed() {
+        let server = MockServer::start().await;
+        redirect(&server, "/old.xml", 301, "/new.xml").await;
+        serve_feed(&server, "/new.xml").await;
+
+        let old_url = format!("{}/old.xml", server.uri());
+        let new_url = format!("{}/new.xml", server.uri());
+        let (pool, feed_id) = pool_with_feed(&old_url).await;
+        sqlx::query!("INSERT INTO feeds (url) VALUES ($1)", new_url)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let result = refresh_feed(&pool, &fetcher_for(&server), feed_id, &old_url).await;
+
+        let error = format!(
+            "Feed moved permanently to {}, which another feed already uses",
+            new_url
+        );
+        assert_eq!(result.error.as_deref(), Some(error.as_str()));
+        assert_eq!(result.moved_to, None);
+        assert_eq!(
+            stored_feed(&pool, feed_id).await,
+            (old_url, Some(error.clone()))
+        );
+    }
+
+    #[test]
+    fn test_group_by_host_keeps_same_host_feeds_together() {
+        let feeds = [
+            "https://a.example.com/1.xml",
+            "https://b.example.com/feed",
+            "https://A.example.com/2.xml",
+            "not a url",
+        ]
+        .iter()
+        .enumerate()
+        .map(|(id, url)| FeedRow {
+            id: id as i64,
+            url: url.to_string(),
+        })
+        .collect();
+
+        let groups: Vec<Vec<i64>> = group_by_host(feeds)
+            .into_iter()
+            .map(|group| group.into_iter().map(|feed| feed.id).collect())
+            .collect();
+
+        assert_eq!(groups, vec![vec![0, 2], vec![1], vec![3]]);
+    }
+
+    #[tokio::test]
+    async fn test_fetcher_spaces_requests_to_the_same_host() {
+        let fetcher = FeedFetcher::with_per_host_delay(Duration::from_millis(200)).unwrap();
+        let shared = fetcher.clone();
+        let started = Instant::now();
+
+        fetcher
+            .wait_for_host("https://a.example.com/1.xml", None)
+            .await;
+        fetcher
+            .wait_for_host("https://b.example.com/feed", None)
+            .await;
+        assert!(started.elapsed() < Duration::from_millis(200));
+
+        shared
+            .wait_for_host("https://A.example.com/2.xml", None)
+            .await;
+        assert!(started.elapsed() >= Duration::from_millis(200));
+
+        let deadline = Instant::now() + Duration::from_millis(50);
+        let waited = tokio::time::timeout_at(
+            deadline,
+            fetcher.wait_for_host("https://a.example.com/3.xml", Some(deadline)),
+        )
+        .await;
+        assert!(waited.is_err());
+        // The slot after the deadline was not claimed.
+        let slot = fetcher.next_slots.lock().unwrap()["a.example.com"];
+        assert!(slot - started < Duration::from_millis(450));
+    }
+}
@@ -0,0 +1,299 @@
+use serde::Serialize;
+use url::Url;
+
+use crate::services::feed::{FeedFetcher, MAX_FEED_BYTES};
+
+/// Paths tried when a page does not advertise any feeds.
+const COMMON_FEED_PATHS: &[&str] = &[
+    "/feed",
+    "/rss",
+    "/feed.xml",
+    "/rss.xml",
+    "/atom.xml",
+    "/index.xml",
+    "/feed.json",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FeedKind {
+    Rss,
+    Atom,
+    Json,
+}
+
+/// A feed found while looking at a web page.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FeedCandidate {
+    pub url: String,
+    pub title: Option<String>,
+    pub kind: FeedKind,
+}
+
+/// Finds the feeds behind `url`.
+///
+/// If `url` is itself a feed it is the only candidate. Otherwise the page's
+/// `<link rel="alternate">` feed links are returned, falling back to probing
+/// `COMMON_FEED_PATHS` on the same origin when the page advertises none. Every
+/// request goes through the same checks as feed refreshes.
+pub async fn discover_feeds(
+    fetcher: &FeedFetcher,
+    url: &Url,
+) -> anyhow::Result<Vec<FeedCandidate>> {
+    let (page_url, body) = fetch(fetcher, url).await?;
+
+    if let Some(candidate) = parse_feed(&page_url, &body) {
+        return Ok(vec![candidate]);
+    }
+
+    let candidates = find_feed_links(&String::from_utf8_lossy(&body), &page_url);
+    if !candidates.is_empty() {
+        return Ok(candidates);
+    }
+
+    let probes = COMMON_FEED_PATHS
+        .iter()
+        .filter_map(|path| page_url.join(path).ok())
+        .map(|probe_url| async move {
+            let (feed_url, body) = fetch(fetcher, &probe_url).await.ok()?;
+            parse_feed(&feed_url, &body)
+        });
+    let mut candidates: Vec<FeedCandidate> = futures_util::future::join_all(probes)
+        .await
+        .into_iter()
+        .flatten()
+        .collect();
+    candidates.dedup_by(|a, b| a.url == b.url);
+    Ok(candidates)
+}
+
+/// Fetches `url` and returns the URL it ended up at along with the body.
+async fn fetch(fetcher: &FeedFetcher, url: &Url) -> anyhow::Result<(Url, Vec<u8>)> {
+    let fetched = fetcher
+        .send_following_redirects(url, |request| request)
+        .await?;
+    let response = fetched.response;
+    if !response.status().is_success() {
+        anyhow::bail!("HTTP error: {}", response.status());
+    }
+
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to read body: {}", e))?;
+    if bytes.len() > MAX_FEED_BYTES {
+        anyhow::bail!(
+            "Page too large: {} bytes (max {})",
+            bytes.len(),
+            MAX_FEED_BYTES
+        );
+    }
+    Ok((fetched.final_url, bytes.to_vec()))
+}
+
+fn parse_feed(url: &Url, body: &[u8]) -> Option<FeedCandidate> {
+    let feed = feed_rs::parser::parse(body).ok()?;
+    let kind = match feed.feed_type {
+        feed_rs::model::FeedType::Atom => FeedKind::Atom,
+        feed_rs::model::FeedType::JSON => FeedKind::Json,
+        _ => FeedKind::Rss,
+    };
+    Some(FeedCandidate {
+        url: url.to_string(),
+        title: feed.title.map(|t| t.content),
+        kind,
+    })
+}
+
+/// Extracts `<link rel="alternate">` RSS, Atom and JSON Feed links from an HTML page,
+/// resolving them against `base`. Only HTTPS links are returned since nothing else
+/// can be subscribed to.
+pub fn find_feed_links(html: &str, base: &Url) -> Vec<FeedCandidate> {
+    let lower = html.to_ascii_lowercase();
+    let mut candidates: Vec<FeedCandidate> = Vec::new();
+    let mut offset = 0;
+
+    while let Some(start) = lower[offset..].find("<link") {
+        let tag_start = offset + start + "<link".len();
+        let Some(end) = lower[tag_start..].find('>') else {
+            break;
+        };
+        let tag_end = tag_start + end;
+        offset = tag_end;
+
+        let attributes = parse_attributes(&html[tag_start..tag_end]);
+        let attr = |name: &str| {
+            attributes
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.as_str())
+        };
+
+        let is_alternate = attr("rel").is_some_and(|rel| {
+            rel.split_ascii_whitespace()
+                .any(|r| r.eq_ignore_ascii_case("alternate"))
+        });
+        if !is_alternate {
+            continue;
+        }
+        let kind = match attr("type")
+            .map(|t| t.trim().to_ascii_lowercase())
+            .as_deref()
+        {
+            Some("application/rss+xml") => FeedKind::Rss,
+            Some("application/atom+xml") => FeedKind::Atom,
+            Some("application/feed+json") => FeedKind::Json,
+            _ => continue,
+        };
+        let Some(url) = attr("href").and_then(|href| base.join(href.trim()).ok()) else {
+            continue;
+        };
+        if url.scheme() != "https" || candidates.iter().any(|c| c.url == url.as_str()) {
+            continue;
+        }
+
+        candidates.push(FeedCandidate {
+            url: url.to_string(),
+            title: attr("title")
+                .map(str::trim)
+                .filter(|t| !t.is_empty())
+                .map(str::to_string),
+            kind,
+        });
+    }
+
+    candidates
+}
+
+/// Parses the attributes of an HTML tag body into lowercase names and values with
+/// the common character entities decoded.
+fn parse_attributes(tag: &str) -> Vec<(String, String)> {
+    let mut attributes = Vec::new();
+    let mut chars = tag.char_indices().peekable();
+
+    loop {
+        while chars
+            .next_if(|(_, c)| c.is_whitespace() || *c == '/')
+            .is_some()
+        {}
+        let Some(&(name_start, _)) = chars.peek() else {
+            break;
+        };
+        let mut name_end = tag.len();
+        while let Some(&(i, c)) = chars.peek() {
+            if c.is_whitespace() || c == '=' || c == '/' {
+                name_end = i;
+                break;
+            }
+            chars.next();
+        }
+        let name = tag[name_start..name_end].to_ascii_lowercase();
+
+        while chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+        if chars.next_if(|(_, c)| *c == '=').is_none() {
+            attributes.push((name, String::new()));
+            continue;
+        }
+        while chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+
+        let value = match chars.next_if(|(_, c)| *c == '"' || *c == '\'') {
+            Some((quote_at, quote)) => {
+                let value_start = quote_at + 1;
+                let mut value_end = tag.len();
+                for (i, c) in chars.by_ref() {
+                    if c == quote {
+                        value_end = i;
+                        break;
+                    }
+                }
+                &tag[value_start..value_end]
+            }
+            None => {
+                let value_start = chars.peek().map_or(tag.len(), |&(i, _)| i);
+                let mut value_end = tag.len();
+                while let Some(&(i, c)) = chars.peek() {
+                    if c.is_whitespace() {
+                        value_end = i;
+                        break;
+                    }
+                    chars.next();
+                }
+                &tag[value_start..value_end]
+            }
+        };
+        attributes.push((name, decode_entities(value)));
+    }
+
+    attributes
+}
+
+fn decode_entities(value: &str) -> String {
+    value
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_feed_links_extracts_alternate_feeds() {
+        let html = r#"<!doctype html>
+<html><head>
+  <LINK rel="alternate" type="application/rss+xml" title="Posts" href="/feed.xml">
+  <link type='application/atom+xml' rel='alternate home' href='https://cdn.example.com/atom?a=1&amp;b=2' />
+  <link rel=alternate type=application/feed+json href=feed.json>
+  <link rel="alternate" type="application/json" href="/wp-json/wp/v2/pages/1">
+  <link rel="stylesheet" type="text/css" href="/style.css">
+  <link rel="alternate" type="application/rss+xml" href="http://example.com/insecure.xml">
+  <link rel="alternate" type="application/rss+xml" href="/feed.xml" title="Duplicate">
+</head></html>"#;
+        let base = Url::parse("https://example.com/blog/post").unwrap();
+
+        let candidates = find_feed_links(html, &base);
+
+        assert_eq!(
+            candidates,
+            vec![
+                FeedCandidate {
+                    url: "https://example.com/feed.xml".to_string(),
+                    title: Some("Posts".to_string()),
+                    kind: FeedKind::Rss,
+                },
+                FeedCandidate {
+                    url: "https://cdn.example.com/atom?a=1&b=2".to_string(),
+                    title: None,
+                    kind: FeedKind::Atom,
+                },
+                FeedCandidate {
+                    url: "https://example.com/blog/feed.json".to_string(),
+                    title: None,
+                    kind: FeedKind::Json,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_feed_links_handles_pages_without_feeds() {
+        let base = Url::parse("https://example.com/").unwrap();
+        assert!(find_feed_links("<html><body>Hello</body></html>", &base).is_empty());
+        assert!(find_feed_links("<link rel=\"alternate\"", &base).is_empty());
+    }
+
+    #[test]
+    fn test_parse_feed_recognizes_feed_documents() {
+        let url = Url::parse("https://example.com/atom.xml").unwrap();
+        let atom = br#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom"><title>Example</title><id>urn:x</id><updated>2026-01-01T00:00:00Z</updated></feed>"#;
+
+        let candidate = parse_feed(&url, atom).unwrap();
+        assert_eq!(candidate.kind, FeedKind::Atom);
+        assert_eq!(candidate.title.as_deref(), Some("Example"));
+        assert!(parse_feed(&url, b"<html><body>Not a feed</body></html>").is_none());
+    }
+}
@@ -0,0 +1,179 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+/// Shortest interval between two fetches of the same feed.
+pub const MIN_INTERVAL: Duration = Duration::from_secs(15 * 60);
+/// Interval used when a feed gives no hints and has no dated items yet.
+pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// Longest interval between two fetches of a healthy feed.
+pub const MAX_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+/// Longest wait before retrying a failing feed.
+pub const MAX_BACKOFF: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// What the last fetch told us about how often a feed should be fetched.
+#[derive(Debug, Default, Clone)]
+pub struct ScheduleHints {
+    /// RSS `<ttl>`.
+    pub ttl: Option<Duration>,
+    /// `Cache-Control: max-age`.
+    pub max_age: Option<Duration>,
+    /// `Retry-After`.
+    pub retry_after: Option<Duration>,
+    /// Average time between the feed's recent items.
+    pub posting_interval: Option<Duration>,
+    /// Interval set by the user, used instead of the posting-frequency estimate.
+    pub configured_interval: Option<Duration>,
+}
+
+/// Returns how long to wait before fetching a feed again.
+///
+/// Healthy feeds are fetched about twice per posting interval, but never more often
+/// than the feed's own caching hints allow. Failing feeds back off exponentially from
+/// `MIN_INTERVAL`, doubling with each consecutive error.
+pub fn next_fetch_delay(hints: &ScheduleHints, consecutive_errors: u32) -> Duration {
+    if consecutive_errors > 0 {
+        let backoff = MIN_INTERVAL
+            .checked_mul(1u32 << (consecutive_errors - 1).min(16))
+            .unwrap_or(MAX_BACKOFF);
+        return backoff
+            .max(hints.retry_after.unwrap_or_default())
+            .min(MAX_BACKOFF);
+    }
+
+    if let Some(interval) = hints.configured_interval {
+        return interval.max(hints.retry_after.unwrap_or_default());
+    }
+
+    let base = hints
+        .posting_interval
+        .map(|interval| (interval / 2).clamp(MIN_INTERVAL, MAX_INTERVAL))
+        .unwrap_or(DEFAULT_INTERVAL);
+
+    [hints.ttl, hints.max_age, hints.retry_after]
+        .into_iter()
+        .flatten()
+        .fold(base, Duration::max)
+        .min(MAX_INTERVAL)
+}
+
+/// Parses a `Retry-After` value given either as seconds or as an HTTP date.
+pub fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at = DateTime::parse_from_rfc2822(value).ok()?;
+    (at.with_timezone(&Utc) - now).to_std().ok()
+}
+
+/// Extracts `max-age` from a `Cache-Control` value. `no-store` and `no-cache`
+/// responses give no hint.
+pub fn parse_max_age(value: &str) -> Option<Duration> {
+    let mut max_age = None;
+    for directive in value.split(',').map(str::trim) {
+        let directive = directive.to_ascii_lowercase();
+        if directive == "no-store" || directive == "no-cache" {
+            return None;
+        }
+        if let Some(seconds) = directive.strip_prefix("max-age=") {
+            max_age = seconds.trim_matches('"').parse::<u64>().ok();
+        }
+    }
+    max_age.map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    const HOUR: Duration = Duration::from_secs(60 * 60);
+
+    #[test]
+    fn test_next_fetch_delay_follows_posting_interval() {
+        let daily = ScheduleHints {
+            posting_interval: Some(HOUR * 24),
+            ..Default::default()
+        };
+        assert_eq!(next_fetch_delay(&daily, 0), HOUR * 12);
+
+        let busy = ScheduleHints {
+            posting_interval: Some(Duration::from_secs(60)),
+            ..Default::default()
+        };
+        assert_eq!(next_fetch_delay(&busy, 0), MIN_INTERVAL);
+
+        assert_eq!(
+            next_fetch_delay(&ScheduleHints::default(), 0),
+            DEFAULT_INTERVAL
+        );
+    }
+
+    #[test]
+    fn test_next_fetch_delay_respects_feed_hints() {
+        let hints = ScheduleHints {
+            ttl: Some(HOUR * 3),
+            max_age: Some(HOUR),
+            posting_interval: Some(HOUR),
+            ..Default::default()
+        };
+        assert_eq!(next_fetch_delay(&hints, 0), HOUR * 3);
+
+        let hints = ScheduleHints {
+            max_age: Some(HOUR * 24 * 7),
+            ..Default::default()
+        };
+        assert_eq!(next_fetch_delay(&hints, 0), MAX_INTERVAL);
+
+        let hints = ScheduleHints {
+            ttl: Some(HOUR * 3),
+            configured_interval: Some(HOUR * 48),
+            ..Default::default()
+        };
+        assert_eq!(next_fetch_delay(&hints, 0), HOUR * 48);
+    }
+
+    #[test]
+    fn test_next_fetch_delay_backs_off_on_errors() {
+        let hints = ScheduleHints::default();
+        assert_eq!(next_fetch_delay(&hints, 1), MIN_INTERVAL);
+        assert_eq!(next_fetch_delay(&hints, 2), MIN_INTERVAL * 2);
+        assert_eq!(next_fetch_delay(&hints, 4), MIN_INTERVAL * 8);
+        assert_eq!(next_fetch_delay(&hints, 40), MAX_BACKOFF);
+
+        let hints = ScheduleHints {
+            retry_after: Some(HOUR * 2),
+            ..Default::default()
+        };
+        assert_eq!(next_fetch_delay(&hints, 1), HOUR * 2);
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        let now = Utc.with_ymd_and_hms(2026, 1, 1, 12, 0, 0).unwrap();
+        assert_eq!(
+            parse_retry_after("120", now),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            parse_retry_after("Thu, 01 Jan 2026 13:00:00 GMT", now),
+            Some(HOUR)
+        );
+        assert_eq!(
+            parse_retry_after("Thu, 01 Jan 2026 11:00:00 GMT", now),
+            None
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
+    #[test]
+    fn test_parse_max_age() {
+        assert_eq!(
+            parse_max_age("public, max-age=1800"),
+            Some(Duration::from_secs(1800))
+        );
+        assert_eq!(parse_max_age("no-cache, max-age=1800"), None);
+        assert_eq!(parse_max_age("private"), None);
+    }
+}
@@ -1,4 +1,11 @@
 pub mod adguard;
+pub mod adguard_history;
+pub mod adguard_schedule;
+pub mod adguard_sync;
 pub mod docker;
 pub mod feed;
+pub mod feed_discovery;
+pub mod feed_schedule;
 pub mod ir;
+pub mod opml;
+pub mod ssrf;
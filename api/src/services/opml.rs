@@ -0,0 +1,200 @@
+use xml::reader::{EventReader, XmlEvent as ReadEvent};
+use xml::writer::{EmitterConfig, EventWriter, XmlEvent as WriteEvent};
+
+/// A feed subscription as it appears in an OPML document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpmlFeed {
+    pub url: String,
+    pub title: Option<String>,
+    /// Name of the innermost folder outline the feed was listed under.
+    pub category: Option<String>,
+}
+
+/// Extracts feed subscriptions from an OPML document.
+///
+/// Outlines with an `xmlUrl` are feeds; outlines without one are treated as folders
+/// and their `text` (or `title`) becomes the category of the feeds nested inside.
+pub fn parse_opml(input: &str) -> Result<Vec<OpmlFeed>, anyhow::Error> {
+    let mut feeds = Vec::new();
+    // One entry per open outline: the folder name if the outline is a folder.
+    let mut outlines: Vec<Option<String>> = Vec::new();
+    let mut saw_opml = false;
+
+    for event in EventReader::from_str(input) {
+        match event? {
+            ReadEvent::StartElement {
+                name, attributes, ..
+            } => match name.local_name.as_str() {
+                "opml" => saw_opml = true,
+                "outline" => {
+                    let attr = |key: &str| {
+                        attributes
+                            .iter()
+                            .find(|a| a.name.local_name == key)
+                            .map(|a| a.value.trim().to_string())
+                            .filter(|value| !value.is_empty())
+                    };
+                    match attr("xmlUrl") {
+                        Some(url) => {
+                            feeds.push(OpmlFeed {
+                                url,
+                                title: attr("title").or_else(|| attr("text")),
+                                category: outlines.iter().rev().find_map(Clone::clone),
+                            });
+                            outlines.push(None);
+                        }
+                        None => outlines.push(attr("text").or_else(|| attr("title"))),
+                    }
+                }
+                _ => {}
+            },
+            ReadEvent::EndElement { name } if name.local_name == "outline" => {
+                outlines.pop();
+            }
+            _ => {}
+        }
+    }
+
+    if !saw_opml {
+        anyhow::bail!("Document is not OPML (missing <opml> root element)");
+    }
+    Ok(feeds)
+}
+
+/// Renders feeds as an OPML 2.0 document, grouping categorized feeds into folders.
+pub fn write_opml(title: &str, feeds: &[OpmlFeed]) -> Result<String, anyhow::Error> {
+    let mut buffer = Vec::new();
+    let mut writer = EmitterConfig::new()
+        .perform_indent(true)
+        .create_writer(&mut buffer);
+
+    writer.write(WriteEvent::start_element("opml").attr("version", "2.0"))?;
+    writer.write(WriteEvent::start_element("head"))?;
+    writer.write(WriteEvent::start_element("title"))?;
+    writer.write(WriteEvent::characters(title))?;
+    writer.write(WriteEvent::end_element())?;
+    writer.write(WriteEvent::end_element())?;
+    writer.write(WriteEvent::start_element("body"))?;
+
+    let mut categories: Vec<&str> = feeds.iter().filter_map(|f| f.category.as_deref()).collect();
+    categories.sort_unstable();
+    categories.dedup();
+
+    for feed in feeds.iter().filter(|f| f.category.is_none()) {
+        write_feed(&mut writer, feed)?;
+    }
+    for category in categories {
+        writer.write(
+            WriteEvent::start_element("outline")
+                .attr("text", category)
+                .attr("title", category),
+        )?;
+        for feed in feeds
+            .iter()
+            .filter(|f| f.category.as_deref() == Some(category))
+        {
+            write_feed(&mut writer, feed)?;
+        }
+        writer.write(WriteEvent::end_element())?;
+    }
+
+    writer.write(WriteEvent::end_element())?;
+    writer.write(WriteEvent::end_element())?;
+    Ok(String::from_utf8(buffer)?)
+}
+
+fn write_feed<W: std::io::Write>(
+    writer: &mut EventWriter<W>,
+    feed: &OpmlFeed,
+) -> Result<(), anyhow::Error> {
+    let title = feed.title.as_deref().unwrap_or(&feed.url);
+    writer.write(
+        WriteEvent::start_element("outline")
+            .attr("type", "rss")
+            .attr("text", title)
+            .attr("title", title)
+            .attr("xmlUrl", &feed.url),
+    )?;
+    writer.write(WriteEvent::end_element())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_opml_preserves_folders_as_categories() {
+        let input = r#"<?xml version="1.0" encoding="UTF-8"?>
+<opml version="1.0">
+  <head><title>Subscriptions</title></head>
+  <body>
+    <outline text="Loose" type="rss" xmlUrl="https://example.com/loose.xml"/>
+    <outline text="Tech" title="Tech">
+      <outline text="Rust Blog" type="rss" xmlUrl="https://blog.rust-lang.org/feed.xml"/>
+      <outline text="Deep">
+        <outline title="Nested" xmlUrl="https://example.com/nested.xml"/>
+      </outline>
+      <outline text="After nested" xmlUrl="https://example.com/after.xml"/>
+    </outline>
+  </body>
+</opml>"#;
+
+        let feeds = parse_opml(input).unwrap();
+
+        assert_eq!(
+            feeds,
+            vec![
+                OpmlFeed {
+                    url: "https://example.com/loose.xml".to_string(),
+                    title: Some("Loose".to_string()),
+                    category: None,
+                },
+                OpmlFeed {
+                    url: "https://blog.rust-lang.org/feed.xml".to_string(),
+                    title: Some("Rust Blog".to_string()),
+                    category: Some("Tech".to_string()),
+                },
+                OpmlFeed {
+                    url: "https://example.com/nested.xml".to_string(),
+                    title: Some("Nested".to_string()),
+                    category: Some("Deep".to_string()),
+                },
+                OpmlFeed {
+                    url: "https://example.com/after.xml".to_string(),
+                    title: Some("After nested".to_string()),
+                    category: Some("Tech".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_opml_rejects_other_documents() {
+        assert!(parse_opml("<rss version=\"2.0\"><channel/></rss>").is_err());
+        assert!(parse_opml("not xml at all").is_err());
+    }
+
+    #[test]
+    fn test_write_opml_round_trips() {
+        let feeds = vec![
+            OpmlFeed {
+                url: "https://example.com/a.xml?x=1&y=2".to_string(),
+                title: Some("A & B".to_string()),
+                category: None,
+            },
+            OpmlFeed {
+                url: "https://example.com/b.xml".to_string(),
+                title: None,
+                category: Some("Family".to_string()),
+            },
+        ];
+
+        let output = write_opml("openhome feeds", &feeds).unwrap();
+        let parsed = parse_opml(&output).unwrap();
+
+        assert_eq!(parsed[0], feeds[0]);
+        assert_eq!(parsed[1].url, feeds[1].url);
+        assert_eq!(parsed[1].category, feeds[1].category);
+    }
+}
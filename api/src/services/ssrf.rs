@@ -0,0 +1,187 @@
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+
+pub const NON_GLOBAL_ADDRESS_ERROR: &str = "refusing to fetch non-global address";
+
+/// Whether `ip` is a publicly routable address that outbound fetches may connect to.
+///
+/// Rejects private, loopback, link-local, multicast, broadcast and unspecified
+/// addresses, shared address space (CGNAT, 100.64.0.0/10), documentation and
+/// benchmarking ranges, and other reserved IPv4 blocks. IPv6 addresses that embed
+/// an IPv4 address (IPv4-mapped, IPv4-compatible, NAT64, 6to4 and Teredo) are
+/// judged by the embedded address.
+pub fn is_global_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(addr) => is_global_ipv4(addr),
+        IpAddr::V6(addr) => is_global_ipv6(addr),
+    }
+}
+
+fn is_global_ipv4(addr: Ipv4Addr) -> bool {
+    let [a, b, c, _] = addr.octets();
+    !(addr.is_private()
+        || addr.is_loopback()
+        || addr.is_link_local()
+        || addr.is_multicast()
+        || addr.is_broadcast()
+        || addr.is_unspecified()
+        || addr.is_documentation()
+        // "This network", 0.0.0.0/8
+        || a == 0
+        // Shared address space (CGNAT), 100.64.0.0/10
+        || (a == 100 && (64..128).contains(&b))
+        // IETF protocol assignments, 192.0.0.0/24
+        || (a == 192 && b == 0 && c == 0)
+        // Benchmarking, 198.18.0.0/15
+        || (a == 198 && (b == 18 || b == 19))
+        // Reserved for future use, 240.0.0.0/4
+        || a >= 240)
+}
+
+fn is_global_ipv6(addr: Ipv6Addr) -> bool {
+    if let Some(v4) = embedded_ipv4(addr) {
+        return is_global_ipv4(v4);
+    }
+    let segments = addr.segments();
+    !(addr.is_loopback()
+        || addr.is_unspecified()
+        || addr.is_unique_local()
+        || addr.is_unicast_link_local()
+        || addr.is_multicast()
+        // Documentation, 2001:db8::/32
+        || (segments[0] == 0x2001 && segments[1] == 0xdb8)
+        // Benchmarking, 2001:2::/48
+        || (segments[0] == 0x2001 && segments[1] == 2 && segments[2] == 0)
+        // Discard-only, 100::/64
+        || segments[..4] == [0x100, 0, 0, 0]
+        // Deprecated site-local, fec0::/10
+        || (segments[0] & 0xffc0) == 0xfec0)
+}
+
+/// The IPv4 address embedded in an IPv4-mapped (::ffff:0:0/96), IPv4-compatible
+/// (::/96, which also covers `::` and `::1`), NAT64 (64:ff9b::/96), 6to4
+/// (2002::/16) or Teredo (2001::/32) address. For Teredo this is the client address
+/// traffic is delivered to, stored with its bits inverted.
+fn embedded_ipv4(addr: Ipv6Addr) -> Option<Ipv4Addr> {
+    let segments = addr.segments();
+    let v4 = |high: u16, low: u16| {
+        let [a, b] = high.to_be_bytes();
+        let [c, d] = low.to_be_bytes();
+        Ipv4Addr::new(a, b, c, d)
+    };
+    match segments {
+        [0, 0, 0, 0, 0, 0xffff, high, low]
+        | [0, 0, 0, 0, 0, 0, high, low]
+        | [0x64, 0xff9b, 0, 0, 0, 0, high, low] => Some(v4(high, low)),
+        [0x2002, high, low, ..] => Some(v4(high, low)),
+        [0x2001, 0, .., high, low] => Some(v4(!high, !low)),
+        _ => None,
+    }
+}
+
+/// DNS resolver for outbound fetches that only hands out global addresses.
+///
+/// The HTTP client connects to exactly the addresses returned here, so checking them
+/// at resolution time (rather than resolving once to check and again to connect)
+/// leaves no window for a rebinding DNS server to swap in an internal address. If
+/// any address for a name is not global the whole lookup fails.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct GlobalOnlyResolver;
+
+impl Resolve for GlobalOnlyResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> =
+                tokio::net::lookup_host((name.as_str(), 0)).await?.collect();
+            if addrs.is_empty() {
+                return Err(format!("no addresses found for {}", name.as_str()).into());
+            }
+            if addrs.iter().any(|addr| !is_global_ip(addr.ip())) {
+                return Err(NON_GLOBAL_ADDRESS_ERROR.into());
+            }
+            let addrs: Addrs = Box::new(addrs.into_iter());
+            Ok(addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn global(ip: &str) -> bool {
+        is_global_ip(ip.parse().unwrap())
+    }
+
+    #[test]
+    fn test_is_global_ip_accepts_public_addresses() {
+        assert!(global("93.184.216.34"));
+        assert!(global("1.1.1.1"));
+        assert!(global("100.128.0.1"));
+        assert!(global("2606:4700:4700::1111"));
+        assert!(global("::ffff:93.184.216.34"));
+        assert!(global("::5db8:d822"));
+        assert!(global("2002:5db8:d822::1"));
+        assert!(global("2001:0:4136:e378:8000:63bf:a247:2dd"));
+    }
+
+    #[test]
+    fn test_is_global_ip_rejects_reserved_ipv4_ranges() {
+        for ip in [
+            "10.0.0.1",
+            "172.16.0.1",
+            "192.168.1.1",
+            "127.0.0.1",
+            "169.254.169.254",
+            "0.0.0.0",
+            "0.1.2.3",
+            "100.64.0.1",
+            "100.127.255.254",
+            "192.0.0.8",
+            "192.0.2.1",
+            "198.51.100.1",
+            "203.0.113.1",
+            "198.18.0.1",
+            "198.19.255.255",
+            "224.0.0.1",
+            "240.0.0.1",
+            "255.255.255.255",
+        ] {
+            assert!(!global(ip), "{ip} should not be global");
+        }
+    }
+
+    #[test]
+    fn test_is_global_ip_rejects_reserved_ipv6_ranges() {
+        for ip in [
+            "::1",
+            "::",
+            "fc00::1",
+            "fe80::1",
+            "ff02::1",
+            "::ffff:127.0.0.1",
+            "::ffff:192.168.1.1",
+            "64:ff9b::a00:1",
+            "::7f00:1",
+            "::10.0.0.1",
+            "2002:7f00:1::1",
+            "2002:c0a8:101::",
+            "2001:0:4136:e378:8000:63bf:f5ff:fffe",
+            "2001:0:4136:e378:8000:63bf:80ff:fffe",
+            "2001:db8::1",
+            "2001:2::1",
+            "100::1",
+            "fec0::1",
+        ] {
+            assert!(!global(ip), "{ip} should not be global");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_global_only_resolver_rejects_local_names() {
+        let name: Name = "localhost".parse().unwrap();
+        let error = GlobalOnlyResolver.resolve(name).await.err().unwrap();
+        assert_eq!(error.to_string(), NON_GLOBAL_ADDRESS_ERROR);
+    }
+}
@@ -4,6 +4,17 @@ use axum::Router;
 use http::StatusCode;
 use serde_json::json;
 
+fn adguard_test_app(base_url: &str) -> Router {
+    let service =
+        openhome_api::services::adguard::AdguardService::new(base_url, "test", "test", false)
+            .unwrap();
+    let state = common::create_mock_state_with_adguard(service);
+
+    Router::new()
+        .merge(openhome_api::routes::adguard::router())
+        .with_state(state)
+}
+
 #[tokio::test]
 async fn test_status_endpoint_returns_503_when_service_not_configured() {
     let app = common::test_app().await;
@@ -226,7 +237,7 @@ async fn test_all_adguard_routes_are_registered() {
 }
 
 #[tokio::test]
-async fn test_pause_endpoint_returns_422_when_minutes_missing() {
+async fn test_pause_endpoint_returns_400_when_duration_missing() {
     let app = common::test_app().await;
     let body = json!({});
 
@@ -239,8 +250,8 @@ async fn test_pause_endpoint_returns_422_when_minutes_missing() {
     )
     .await;
 
-    // Missing required field should return 422
-    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    // One of seconds, minutes or until is required
+    assert_eq!(status, StatusCode::BAD_REQUEST);
 }
 
 #[tokio::test]
@@ -260,3 +271,848 @@ async fn test_pause_endpoint_rejects_zero_minutes() {
     // Zero minutes is not valid - should be a validation error
     assert_eq!(status, StatusCode::BAD_REQUEST);
 }
+
+#[tokio::test]
+async fn test_block_domain_rejects_invalid_domain() {
+    let app = common::test_app_with_adguard().await;
+
+    let (status, body) = common::send_request_with_method(
+        app,
+        "/api/adguard/rules/block",
+        http::Method::POST,
+        Some(json!({ "domain": "not a domain" })),
+        Some("test-api-key"),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "domain must contain at least one dot");
+}
+
+#[tokio::test]
+async fn test_add_rule_rejects_multiline_rule() {
+    let app = common::test_app_with_adguard().await;
+
+    let (status, body) = common::send_request_with_method(
+        app,
+        "/api/adguard/rules",
+        http::Method::POST,
+        Some(json!({ "rule": "||a.example^\n||b.example^" })),
+        Some("test-api-key"),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "rule must be a single line");
+}
+
+#[tokio::test]
+async fn test_remove_rule_returns_404_for_unknown_rule() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/control/filtering/status"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "user_rules": ["||ads.example.com^"]
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let app = adguard_test_app(&mock_server.uri());
+
+    let (status, body) = common::send_request_with_method(
+        app,
+        "/api/adguard/rules",
+        http::Method::DELETE,
+        Some(json!({ "rule": "||other.example^" })),
+        None,
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["error"], "Rule '||other.example^' not found");
+}
+
+#[tokio::test]
+async fn test_add_filter_list_rejects_non_http_url() {
+    let app = common::test_app_with_adguard().await;
+
+    let (status, body) = common::send_request_with_method(
+        app,
+        "/api/adguard/filters",
+        http::Method::POST,
+        Some(json!({ "name": "Local", "url": "file:///etc/hosts" })),
+        Some("test-api-key"),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "URL must use HTTP or HTTPS scheme");
+}
+
+#[tokio::test]
+async fn test_update_filter_list_returns_404_for_unknown_id() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/control/filtering/status"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "filters": [],
+            "whitelist_filters": [],
+            "user_rules": []
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let app = adguard_test_app(&mock_server.uri());
+
+    let (status, body) = common::send_request_with_method(
+        app,
+        "/api/adguard/filters/42",
+        http::Method::PATCH,
+        Some(json!({ "enabled": false })),
+        None,
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["error"], "Filter list with id 42 not found");
+}
+
+#[tokio::test]
+async fn test_check_endpoint_requires_name() {
+    let app = common::test_app_with_adguard().await;
+
+    let (status, body) =
+        common::send_request(app, "/api/adguard/check", Some("test-api-key")).await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "name is required");
+}
+
+#[tokio::test]
+async fn test_check_endpoint_explains_blocked_host() {
+    use wiremock::matchers::{method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/control/filtering/check_host"))
+        .and(query_param("name", "ads.example.com"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "reason": "FilteredBlackList",
+            "rules": [{ "filter_list_id": 0, "text": "||ads.example.com^" }]
+        })))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/control/filtering/status"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "filters": [] })))
+        .mount(&mock_server)
+        .await;
+
+    let app = adguard_test_app(&mock_server.uri());
+
+    let (status, body) =
+        common::send_request(app, "/api/adguard/check?name=Ads.Example.com", None).await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["name"], "ads.example.com");
+    assert_eq!(body["blocked"], true);
+    assert_eq!(
+        body["rules"][0]["filter_list_name"],
+        "Custom filtering rules"
+    );
+}
+
+#[tokio::test]
+async fn test_add_rewrite_rejects_invalid_answer() {
+    let app = common::test_app_with_adguard().await;
+
+    let (status, body) = common::send_request_with_method(
+        app,
+        "/api/adguard/rewrites",
+        http::Method::POST,
+        Some(json!({ "domain": "nas.home.arpa", "answer": "not an answer" })),
+        Some("test-api-key"),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body["error"],
+        "answer must be an IP address or a domain name: 'not an answer'"
+    );
+}
+
+#[tokio::test]
+async fn test_add_rewrite_creates_wildcard_rewrite() {
+    use wiremock::matchers::{body_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/control/rewrite/list"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/control/rewrite/add"))
+        .and(body_json(
+            json!({ "domain": "*.lab.home.arpa", "answer": "192.168.1.10" }),
+        ))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let app = adguard_test_app(&mock_server.uri());
+
+    let (status, body) = common::send_request_with_method(
+        app,
+        "/api/adguard/rewrites",
+        http::Method::POST,
+        Some(json!({ "domain": "*.Lab.home.arpa", "answer": "192.168.1.10" })),
+        None,
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(body["domain"], "*.lab.home.arpa");
+}
+
+#[tokio::test]
+async fn test_delete_rewrite_returns_404_when_missing() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/control/rewrite/list"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            { "domain": "nas.home.arpa", "answer": "192.168.1.10" }
+        ])))
+        .mount(&mock_server)
+        .await;
+
+    let app = adguard_test_app(&mock_server.uri());
+
+    let (status, body) = common::send_request_with_method(
+        app,
+        "/api/adguard/rewrites",
+        http::Method::DELETE,
+        Some(json!({ "domain": "nas.home.arpa", "answer": "192.168.1.99" })),
+        None,
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(
+        body["error"],
+        "Rewrite nas.home.arpa -> 192.168.1.99 not found"
+    );
+}
+
+#[tokio::test]
+async fn test_delete_rewrite_matches_existing_entry_as_stored() {
+    use wiremock::matchers::{body_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/control/rewrite/list"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!([
+            { "domain": "NAS.home.arpa", "answer": "fd00:0:0::10" }
+        ])))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/control/rewrite/delete"))
+        .and(body_json(
+            json!({ "domain": "NAS.home.arpa", "answer": "fd00:0:0::10" }),
+        ))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let app = adguard_test_app(&mock_server.uri());
+
+    let (status, _) = common::send_request_with_method(
+        app,
+        "/api/adguard/rewrites",
+        http::Method::DELETE,
+        Some(json!({ "domain": "NAS.home.arpa", "answer": "fd00:0:0::10" })),
+        None,
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::NO_CONTENT);
+}
+
+#[tokio::test]
+async fn test_update_blocked_services_rejects_inverted_schedule() {
+    let app = common::test_app_with_adguard().await;
+
+    let (status, body) = common::send_request_with_method(
+        app,
+        "/api/adguard/blocked-services",
+        http::Method::PUT,
+        Some(json!({
+            "ids": ["tiktok"],
+            "schedule": { "mon": { "start_seconds": 72000, "end_seconds": 68400 } }
+        })),
+        Some("test-api-key"),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body["error"],
+        "schedule for mon must satisfy start_seconds < end_seconds <= 86400"
+    );
+}
+
+#[tokio::test]
+async fn test_update_blocked_services_rejects_unknown_service() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/control/blocked_services/all"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "blocked_services": [
+                { "id": "tiktok", "name": "TikTok", "icon_svg": "", "rules": [] }
+            ],
+            "groups": []
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let app = adguard_test_app(&mock_server.uri());
+
+    let (status, body) = common::send_request_with_method(
+        app,
+        "/api/adguard/blocked-services",
+        http::Method::PUT,
+        Some(json!({ "ids": ["tiktok", "myspace"] })),
+        None,
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "Unknown service id 'myspace'");
+}
+
+#[tokio::test]
+async fn test_update_client_returns_404_for_unknown_client() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/control/clients"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "clients": [],
+            "auto_clients": [],
+            "supported_tags": ["device_tv"]
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let app = adguard_test_app(&mock_server.uri());
+
+    let (status, body) = common::send_request_with_method(
+        app,
+        "/api/adguard/clients/Living%20room%20TV",
+        http::Method::PATCH,
+        Some(json!({ "filtering_enabled": false })),
+        None,
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["error"], "Client 'Living room TV' not found");
+}
+
+#[tokio::test]
+async fn test_update_client_rejects_unsupported_tag() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/control/clients"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "clients": [],
+            "auto_clients": [],
+            "supported_tags": ["device_tv"]
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let app = adguard_test_app(&mock_server.uri());
+
+    let (status, body) = common::send_request_with_method(
+        app,
+        "/api/adguard/clients/TV",
+        http::Method::PATCH,
+        Some(json!({ "tags": ["device_fridge"] })),
+        None,
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "Unsupported tag 'device_fridge'");
+}
+
+#[tokio::test]
+async fn test_update_client_rejects_unknown_blocked_service() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/control/blocked_services/all"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "blocked_services": [
+                { "id": "tiktok", "name": "TikTok", "icon_svg": "", "rules": [] }
+            ],
+            "groups": []
+        })))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/control/clients/update"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&mock_server)
+        .await;
+
+    let app = adguard_test_app(&mock_server.uri());
+
+    let (status, body) = common::send_request_with_method(
+        app,
+        "/api/adguard/clients/TV",
+        http::Method::PATCH,
+        Some(json!({ "blocked_services": ["tiktok", "myspace"] })),
+        None,
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "Unknown service id 'myspace'");
+}
+
+#[tokio::test]
+async fn test_set_dhcp_config_rejects_invalid_v6_range_start() {
+    let app = common::test_app_with_adguard().await;
+
+    let (status, body) = common::send_request_with_method(
+        app,
+        "/api/adguard/dhcp/config",
+        http::Method::PUT,
+        Some(json!({
+            "enabled": true,
+            "interface_name": "eth0",
+            "v6": { "range_start": "192.168.1.100", "lease_duration_seconds": 86400 }
+        })),
+        Some("test-api-key"),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "v6.range_start must be an IPv6 address");
+}
+
+#[tokio::test]
+async fn test_add_static_lease_rejects_invalid_mac() {
+    let app = common::test_app_with_adguard().await;
+
+    let (status, body) = common::send_request_with_method(
+        app,
+        "/api/adguard/dhcp/static-leases",
+        http::Method::POST,
+        Some(json!({ "mac": "not-a-mac", "ip": "192.168.1.50", "hostname": "tv" })),
+        Some("test-api-key"),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "invalid MAC address: 'not-a-mac'");
+}
+
+#[tokio::test]
+async fn test_remove_static_lease_sends_full_lease() {
+    use wiremock::matchers::{body_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/control/dhcp/status"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "enabled": true,
+            "interface_name": "eth0",
+            "leases": [],
+            "static_leases": [
+                { "mac": "aa:bb:cc:00:11:33", "ip": "192.168.1.50", "hostname": "tv" }
+            ]
+        })))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/control/clients"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "clients": [] })))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/control/dhcp/remove_static_lease"))
+        .and(body_json(json!({
+            "mac": "aa:bb:cc:00:11:33",
+            "ip": "192.168.1.50",
+            "hostname": "tv"
+        })))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let app = adguard_test_app(&mock_server.uri());
+
+    let (status, _body) = common::send_request_with_method(
+        app,
+        "/api/adguard/dhcp/static-leases/AA-BB-CC-00-11-33",
+        http::Method::DELETE,
+        None,
+        None,
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::NO_CONTENT);
+}
+
+#[tokio::test]
+async fn test_instances_endpoint_reports_each_instance() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let primary_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/control/status"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "version": "v0.107.0",
+            "protection_disabled_duration": 0,
+            "protection_enabled": true,
+            "protection_disabled_until": null,
+            "running": true
+        })))
+        .mount(&primary_server)
+        .await;
+
+    let replica = openhome_api::services::adguard::AdguardService::new(
+        "http://127.0.0.1:1",
+        "test",
+        "test",
+        false,
+    )
+    .unwrap()
+    .with_name("backup");
+    let service = openhome_api::services::adguard::AdguardService::new(
+        &primary_server.uri(),
+        "test",
+        "test",
+        false,
+    )
+    .unwrap()
+    .with_replicas(vec![replica]);
+    let app = Router::new()
+        .merge(openhome_api::routes::adguard::router())
+        .with_state(common::create_mock_state_with_adguard(service));
+
+    let (status, body) = common::send_request(app, "/api/adguard/instances", None).await;
+
+    assert_eq!(status, StatusCode::OK);
+    let instances = body["instances"].as_array().unwrap();
+    assert_eq!(instances.len(), 2);
+    assert_eq!(instances[0]["name"], "primary");
+    assert_eq!(instances[0]["primary"], true);
+    assert_eq!(instances[0]["reachable"], true);
+    assert_eq!(instances[0]["status"]["protection_enabled"], true);
+    assert_eq!(instances[1]["name"], "backup");
+    assert_eq!(instances[1]["primary"], false);
+    assert_eq!(instances[1]["reachable"], false);
+    assert!(instances[1]["error"].is_string());
+}
+
+#[tokio::test]
+async fn test_set_upstreams_keeps_omitted_fields() {
+    use wiremock::matchers::{body_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/control/dns_info"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "upstream_dns": ["1.1.1.1"],
+            "bootstrap_dns": ["9.9.9.10"],
+            "fallback_dns": [],
+            "upstream_mode": "parallel"
+        })))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/control/dns_config"))
+        .and(body_json(json!({
+            "upstream_dns": ["https://dns.quad9.net/dns-query", "8.8.8.8"],
+            "bootstrap_dns": ["9.9.9.10"],
+            "fallback_dns": [],
+            "upstream_mode": "parallel"
+        })))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let app = adguard_test_app(&mock_server.uri());
+    let (status, _body) = common::send_request_with_method(
+        app,
+        "/api/adguard/upstreams",
+        http::Method::PUT,
+        Some(json!({ "upstream_dns": [" https://dns.quad9.net/dns-query ", "", "8.8.8.8"] })),
+        None,
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_set_upstreams_rejects_empty_upstream_list() {
+    let app = adguard_test_app("http://127.0.0.1:1");
+    let (status, body) = common::send_request_with_method(
+        app,
+        "/api/adguard/upstreams",
+        http::Method::PUT,
+        Some(json!({ "upstream_dns": ["  "] })),
+        None,
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(
+        body["error"],
+        "upstream_dns must contain at least one server"
+    );
+}
+
+#[tokio::test]
+async fn test_flush_dns_cache() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/control/cache_clear"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let app = adguard_test_app(&mock_server.uri());
+    let (status, _body) = common::send_request_with_method(
+        app,
+        "/api/adguard/cache/flush",
+        http::Method::POST,
+        None,
+        None,
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::NO_CONTENT);
+}
+
+#[tokio::test]
+async fn test_resume_endpoint_refuses_indefinite_disable() {
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/control/status"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "version": "v0.107.0",
+            "protection_disabled_duration": 0,
+            "protection_enabled": false,
+            "protection_disabled_until": null,
+            "running": true
+        })))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/control/protection"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&mock_server)
+        .await;
+
+    let app = adguard_test_app(&mock_server.uri());
+    let (status, body) = common::send_request_with_method(
+        app,
+        "/api/adguard/resume",
+        http::Method::POST,
+        None,
+        None,
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(
+        body["error"],
+        "Protection is disabled, not paused; use enable instead"
+    );
+}
+
+#[tokio::test]
+async fn test_resume_endpoint_enables_paused_protection() {
+    use wiremock::matchers::{body_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/control/status"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "version": "v0.107.0",
+            "protection_disabled_duration": 120000,
+            "protection_enabled": false,
+            "protection_disabled_until": null,
+            "running": true
+        })))
+        .up_to_n_times(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/control/status"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "version": "v0.107.0",
+            "protection_disabled_duration": 0,
+            "protection_enabled": true,
+            "protection_disabled_until": null,
+            "running": true
+        })))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/control/protection"))
+        .and(body_json(json!({ "enabled": true })))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let app = adguard_test_app(&mock_server.uri());
+    let (status, body) = common::send_request_with_method(
+        app,
+        "/api/adguard/resume",
+        http::Method::POST,
+        None,
+        None,
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["protection"]["state"], "enabled");
+}
+
+#[tokio::test]
+async fn test_safe_search_update_keeps_other_engines() {
+    use wiremock::matchers::{body_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/control/safesearch/status"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "enabled": true,
+            "bing": true,
+            "duckduckgo": true,
+            "ecosia": true,
+            "google": true,
+            "pixabay": true,
+            "yandex": true,
+            "youtube": true
+        })))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/control/safebrowsing/status"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "enabled": true })))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/control/parental/status"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "enabled": false })))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("PUT"))
+        .and(path("/control/safesearch/settings"))
+        .and(body_json(json!({
+            "enabled": true,
+            "bing": true,
+            "duckduckgo": true,
+            "ecosia": true,
+            "google": true,
+            "pixabay": true,
+            "yandex": true,
+            "youtube": false
+        })))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let app = adguard_test_app(&mock_server.uri());
+    let (status, body) = common::send_request_with_method(
+        app,
+        "/api/adguard/policy/safe-search",
+        http::Method::PUT,
+        Some(json!({ "youtube": false })),
+        None,
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["safe_browsing"], true);
+    assert_eq!(body["parental_control"], false);
+}
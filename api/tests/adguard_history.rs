@@ -0,0 +1,176 @@
+mod common;
+
+use axum::Router;
+use common::send_request_with_method;
+use http::{Method, StatusCode};
+use openhome_api::auth::{ApiKey, auth_middleware};
+use openhome_api::services::adguard::AdguardService;
+use serde_json::json;
+use sqlx::SqlitePool;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+async fn history_test_app(base_url: &str) -> Router {
+    let db = SqlitePool::connect(":memory:").await.unwrap();
+    sqlx::migrate!("./migrations").run(&db).await.unwrap();
+
+    let service = AdguardService::new(base_url, "test", "test", false).unwrap();
+    let mut state = common::create_mock_state_with_adguard(service);
+    state.db = db;
+
+    Router::new()
+        .merge(openhome_api::routes::adguard::router())
+        .with_state(state)
+        .layer(axum::middleware::from_fn(|req, next| {
+            auth_middleware(req, next, ApiKey::new("test-api-key".to_string()))
+        }))
+}
+
+#[tokio::test]
+async fn test_should_record_protection_changes() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/control/protection"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/control/status"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "version": "v1.5.0",
+            "protection_disabled_duration": 900000,
+            "protection_enabled": false,
+            "protection_disabled_until": "2026-01-01T12:15:00Z",
+            "running": true
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let app = history_test_app(&mock_server.uri()).await;
+
+    let (status, _) = send_request_with_method(
+        app.clone(),
+        "/api/adguard/pause",
+        Method::POST,
+        Some(json!({ "minutes": 15 })),
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, history) = send_request_with_method(
+        app,
+        "/api/adguard/history",
+        Method::GET,
+        None,
+        Some("test-api-key"),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    let entries = history.as_array().unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["action"], "pause");
+    assert_eq!(entries[0]["duration_seconds"], 900);
+    assert!(entries[0].get("key_hint").is_none());
+    assert_eq!(entries[0]["success"], true);
+    assert_eq!(entries[0]["protection_enabled"], false);
+    assert!(entries[0]["protection_disabled_until"].is_string());
+}
+
+#[tokio::test]
+async fn test_should_record_failed_protection_changes() {
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/control/protection"))
+        .respond_with(ResponseTemplate::new(500).set_body_string("boom"))
+        .mount(&mock_server)
+        .await;
+
+    let app = history_test_app(&mock_server.uri()).await;
+
+    let (status, _) = send_request_with_method(
+        app.clone(),
+        "/api/adguard/disable",
+        Method::POST,
+        None,
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+
+    let (status, history) = send_request_with_method(
+        app,
+        "/api/adguard/history?limit=10",
+        Method::GET,
+        None,
+        Some("test-api-key"),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    let entries = history.as_array().unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["action"], "disable");
+    assert_eq!(entries[0]["success"], false);
+    assert!(entries[0]["protection_enabled"].is_null());
+    assert!(entries[0]["error"].as_str().unwrap().contains("500"));
+}
+
+#[tokio::test]
+async fn test_should_record_peer_address_separately_from_forwarded_for() {
+    use axum::body::Body;
+    use axum::extract::ConnectInfo;
+    use std::net::SocketAddr;
+    use tower::ServiceExt;
+
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("POST"))
+        .and(path("/control/protection"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("GET"))
+        .and(path("/control/status"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "version": "v1.5.0",
+            "protection_enabled": true,
+            "running": true
+        })))
+        .mount(&mock_server)
+        .await;
+
+    let app = history_test_app(&mock_server.uri()).await;
+
+    let mut request = http::Request::builder()
+        .method(Method::POST)
+        .uri("/api/adguard/enable")
+        .header(http::header::AUTHORIZATION, "Bearer test-api-key")
+        .header("x-forwarded-for", "203.0.113.7, 10.0.0.2")
+        .body(Body::empty())
+        .unwrap();
+    request.extensions_mut().insert(ConnectInfo(
+        "192.168.1.20:51234".parse::<SocketAddr>().unwrap(),
+    ));
+    let response = app.clone().oneshot(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let (status, history) = send_request_with_method(
+        app,
+        "/api/adguard/history",
+        Method::GET,
+        None,
+        Some("test-api-key"),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(history[0]["action"], "enable");
+    assert_eq!(history[0]["client_ip"], "192.168.1.20");
+    assert_eq!(history[0]["forwarded_for"], "203.0.113.7, 10.0.0.2");
+}
@@ -0,0 +1,547 @@
+mod common;
+
+use chrono::NaiveDate;
+use common::{send_request_with_method, test_app_with_db};
+use http::{Method, StatusCode};
+use openhome_api::services::adguard::AdguardService;
+use openhome_api::services::adguard_schedule;
+use serde_json::json;
+use wiremock::matchers::{body_partial_json, method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+fn status_body(enabled: bool) -> serde_json::Value {
+    json!({
+        "version": "v1.5.0",
+        "protection_disabled_duration": 0,
+        "protection_enabled": enabled,
+        "protection_disabled_until": null,
+        "running": true
+    })
+}
+
+#[tokio::test]
+async fn test_should_reject_schedule_with_invalid_time() {
+    let app = common::test_app().await;
+
+    let (status, response) = send_request_with_method(
+        app,
+        "/api/adguard/schedules",
+        Method::POST,
+        Some(json!({
+            "name": "TV evenings",
+            "action": "disable_protection",
+            "days": ["mon"],
+            "start_time": "7pm",
+            "end_time": "21:00"
+        })),
+        Some("test-api-key"),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(response["error"], "start_time must be formatted as HH:MM");
+}
+
+#[tokio::test]
+async fn test_should_create_and_list_schedules() {
+    let (app, _state) = test_app_with_db().await;
+
+    let (status, created) = send_request_with_method(
+        app.clone(),
+        "/api/adguard/schedules",
+        Method::POST,
+        Some(json!({
+            "name": "TV evenings",
+            "client_name": "Living room TV",
+            "action": "disable_protection",
+            "days": ["friday", "mon"],
+            "start_time": "19:00",
+            "end_time": "21:00"
+        })),
+        Some("test-api-key"),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(created["days"], json!(["mon", "fri"]));
+    assert_eq!(created["enabled"], true);
+    assert_eq!(created["active"], false);
+
+    let (status, listed) = send_request_with_method(
+        app,
+        "/api/adguard/schedules",
+        Method::GET,
+        None,
+        Some("test-api-key"),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(listed.as_array().unwrap().len(), 1);
+    assert_eq!(listed[0]["client_name"], "Living room TV");
+}
+
+#[tokio::test]
+async fn test_should_reject_overlapping_schedules_on_the_same_target() {
+    let (app, _state) = test_app_with_db().await;
+
+    let night = |name: &str, start: &str, end: &str| {
+        json!({
+            "name": name,
+            "action": "disable_protection",
+            "days": ["mon"],
+            "start_time": start,
+            "end_time": end
+        })
+    };
+    let create = |body: serde_json::Value| {
+        send_request_with_method(
+            app.clone(),
+            "/api/adguard/schedules",
+            Method::POST,
+            Some(body),
+            Some("test-api-key"),
+        )
+    };
+
+    let (status, first) = create(night("A", "22:00", "02:00")).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let first_id = first["id"].as_i64().unwrap();
+
+    let (status, response) = create(night("B", "23:00", "06:00")).await;
+    assert_eq!(status, StatusCode::CONFLICT);
+    assert_eq!(
+        response["error"],
+        format!("Schedule overlaps schedule 'A' (id {first_id}), which changes the same setting")
+    );
+
+    // Back-to-back windows, other clients and disabled schedules do not conflict.
+    let (status, second) = create(night("B", "02:00", "06:00")).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let mut client_window = night("TV", "23:00", "06:00");
+    client_window["client_name"] = json!("Living room TV");
+    let (status, _) = create(client_window).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let mut disabled = night("C", "23:00", "06:00");
+    disabled["enabled"] = json!(false);
+    let (status, _) = create(disabled).await;
+    assert_eq!(status, StatusCode::CREATED);
+
+    // Updates are checked against the other schedules, not against themselves.
+    let (status, _) = send_request_with_method(
+        app.clone(),
+        &format!("/api/adguard/schedules/{first_id}"),
+        Method::PUT,
+        Some(night("A", "21:00", "02:00")),
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let second_id = second["id"].as_i64().unwrap();
+    let (status, _) = send_request_with_method(
+        app.clone(),
+        &format!("/api/adguard/schedules/{second_id}"),
+        Method::PUT,
+        Some(night("B", "23:30", "06:00")),
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn test_should_return_404_for_unknown_schedule_runs() {
+    let app = common::test_app().await;
+
+    let (status, response) = send_request_with_method(
+        app,
+        "/api/adguard/schedules/42/runs",
+        Method::GET,
+        None,
+        Some("test-api-key"),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(response["error"], "Schedule with id 42 not found");
+}
+
+#[tokio::test]
+async fn test_should_disable_and_restore_protection_around_window() {
+    let (app, state) = test_app_with_db().await;
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/control/status"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(status_body(true)))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/control/protection"))
+        .and(body_partial_json(json!({ "enabled": false })))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/control/protection"))
+        .and(body_partial_json(json!({ "enabled": true })))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let (status, created) = send_request_with_method(
+        app.clone(),
+        "/api/adguard/schedules",
+        Method::POST,
+        Some(json!({
+            "name": "Weekday evenings",
+            "action": "disable_protection",
+            "days": ["mon", "tue", "wed", "thu", "fri"],
+            "start_time": "19:00",
+            "end_time": "21:00"
+        })),
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CREATED);
+    let id = created["id"].as_i64().unwrap();
+
+    let service = AdguardService::new(&mock_server.uri(), "test", "test", false).unwrap();
+    // 2024-01-15 is a Monday.
+    let monday = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+
+    let inside = monday.and_hms_opt(19, 30, 0).unwrap();
+    adguard_schedule::run_due_schedules(&state.db, &service, inside)
+        .await
+        .unwrap();
+    // A second tick inside the same window must not re-apply the action.
+    adguard_schedule::run_due_schedules(&state.db, &service, inside)
+        .await
+        .unwrap();
+
+    let schedule = adguard_schedule::get_schedule(&state.db, id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(schedule.active);
+
+    let after = monday.and_hms_opt(21, 5, 0).unwrap();
+    adguard_schedule::run_due_schedules(&state.db, &service, after)
+        .await
+        .unwrap();
+
+    let (status, runs) = send_request_with_method(
+        app,
+        &format!("/api/adguard/schedules/{id}/runs"),
+        Method::GET,
+        None,
+        Some("test-api-key"),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    let runs = runs.as_array().unwrap();
+    assert_eq!(runs.len(), 2);
+    assert_eq!(runs[0]["phase"], "end");
+    assert_eq!(runs[0]["success"], true);
+    assert_eq!(runs[1]["phase"], "start");
+    assert_eq!(runs[1]["success"], true);
+}
+
+#[tokio::test]
+async fn test_should_record_failed_schedule_run() {
+    let (_app, state) = test_app_with_db().await;
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/control/clients"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "clients": [] })))
+        .mount(&mock_server)
+        .await;
+
+    let input = adguard_schedule::ScheduleInput {
+        name: "TV evenings".to_string(),
+        client_name: Some("Living room TV".to_string()),
+        action: adguard_schedule::ScheduleAction::DisableProtection,
+        blocked_services: Vec::new(),
+        days: vec!["mon".to_string()],
+        start_time: "19:00".to_string(),
+        end_time: "21:00".to_string(),
+        enabled: true,
+    }
+    .validate()
+    .unwrap();
+    let schedule = adguard_schedule::create_schedule(&state.db, &input)
+        .await
+        .unwrap();
+
+    let service = AdguardService::new(&mock_server.uri(), "test", "test", false).unwrap();
+    let inside = NaiveDate::from_ymd_opt(2024, 1, 15)
+        .unwrap()
+        .and_hms_opt(19, 30, 0)
+        .unwrap();
+    adguard_schedule::run_due_schedules(&state.db, &service, inside)
+        .await
+        .unwrap();
+    // Retries are held back after a failure.
+    adguard_schedule::run_due_schedules(&state.db, &service, inside)
+        .await
+        .unwrap();
+
+    let runs = adguard_schedule::list_runs(&state.db, schedule.id, 10)
+        .await
+        .unwrap();
+    assert_eq!(runs.len(), 1);
+    assert!(!runs[0].success);
+    assert_eq!(
+        runs[0].error,
+        Some("Client 'Living room TV' not found".to_string())
+    );
+}
+
+#[tokio::test]
+async fn test_should_resume_interrupted_pause_when_window_ends() {
+    let (_app, state) = test_app_with_db().await;
+    let mock_server = MockServer::start().await;
+
+    let paused_until = chrono::Utc::now() + chrono::Duration::hours(2);
+    Mock::given(method("GET"))
+        .and(path("/control/status"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "version": "v1.5.0",
+            "protection_disabled_duration": 0,
+            "protection_enabled": false,
+            "protection_disabled_until": paused_until.to_rfc3339(),
+            "running": true
+        })))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("POST"))
+        .and(path("/control/protection"))
+        .respond_with(ResponseTemplate::new(200))
+        .mount(&mock_server)
+        .await;
+
+    let input = adguard_schedule::ScheduleInput {
+        name: "Evenings".to_string(),
+        client_name: None,
+        action: adguard_schedule::ScheduleAction::DisableProtection,
+        blocked_services: Vec::new(),
+        days: vec!["mon".to_string()],
+        start_time: "19:00".to_string(),
+        end_time: "21:00".to_string(),
+        enabled: true,
+    }
+    .validate()
+    .unwrap();
+    adguard_schedule::create_schedule(&state.db, &input)
+        .await
+        .unwrap();
+
+    let service = AdguardService::new(&mock_server.uri(), "test", "test", false).unwrap();
+    let monday = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+    adguard_schedule::run_due_schedules(
+        &state.db,
+        &service,
+        monday.and_hms_opt(19, 30, 0).unwrap(),
+    )
+    .await
+    .unwrap();
+    adguard_schedule::run_due_schedules(&state.db, &service, monday.and_hms_opt(21, 5, 0).unwrap())
+        .await
+        .unwrap();
+
+    let requests = mock_server.received_requests().await.unwrap();
+    let toggles: Vec<serde_json::Value> = requests
+        .iter()
+        .filter(|request| request.url.path() == "/control/protection")
+        .map(|request| request.body_json().unwrap())
+        .collect();
+    assert_eq!(toggles.len(), 2);
+    assert_eq!(toggles[0], json!({ "enabled": false }));
+    assert_eq!(toggles[1]["enabled"], false);
+    let duration_ms = toggles[1]["duration"].as_u64().unwrap();
+    assert!((7_100_000..=7_200_000).contains(&duration_ms));
+
+    let history =
+        openhome_api::services::adguard_history::list_protection_changes(&state.db, None, 10)
+            .await
+            .unwrap();
+    assert_eq!(history.len(), 2);
+    assert_eq!(history[0].actor, "scheduler");
+    assert_eq!(history[0].action, "pause");
+    assert_eq!(history[1].actor, "scheduler");
+    assert_eq!(history[1].action, "disable");
+}
+
+#[tokio::test]
+async fn test_should_only_remove_services_added_by_window() {
+    let (_app, state) = test_app_with_db().await;
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/control/blocked_services/get"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "ids": ["tiktok"],
+            "schedule": {}
+        })))
+        .up_to_n_times(1)
+        .mount(&mock_server)
+        .await;
+
+    // Someone blocks netflix while the window is active.
+    Mock::given(method("GET"))
+        .and(path("/control/blocked_services/get"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "ids": ["netflix", "tiktok", "youtube"],
+            "schedule": {}
+        })))
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("PUT"))
+        .and(path("/control/blocked_services/update"))
+        .and(body_partial_json(json!({ "ids": ["tiktok", "youtube"] })))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    Mock::given(method("PUT"))
+        .and(path("/control/blocked_services/update"))
+        .and(body_partial_json(json!({ "ids": ["netflix", "tiktok"] })))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let input = adguard_schedule::ScheduleInput {
+        name: "Homework".to_string(),
+        client_name: None,
+        action: adguard_schedule::ScheduleAction::BlockServices,
+        blocked_services: vec!["tiktok".to_string(), "youtube".to_string()],
+        days: vec!["mon".to_string()],
+        start_time: "16:00".to_string(),
+        end_time: "18:00".to_string(),
+        enabled: true,
+    }
+    .validate()
+    .unwrap();
+    let schedule = adguard_schedule::create_schedule(&state.db, &input)
+        .await
+        .unwrap();
+
+    let service = AdguardService::new(&mock_server.uri(), "test", "test", false).unwrap();
+    let monday = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+    adguard_schedule::run_due_schedules(
+        &state.db,
+        &service,
+        monday.and_hms_opt(16, 30, 0).unwrap(),
+    )
+    .await
+    .unwrap();
+    adguard_schedule::run_due_schedules(&state.db, &service, monday.and_hms_opt(18, 5, 0).unwrap())
+        .await
+        .unwrap();
+
+    let runs = adguard_schedule::list_runs(&state.db, schedule.id, 10)
+        .await
+        .unwrap();
+    assert_eq!(runs.len(), 2);
+    assert!(runs.iter().all(|run| run.success));
+}
+
+#[tokio::test]
+async fn test_should_keep_running_other_schedules_when_one_cannot_be_saved() {
+    let (_app, state) = test_app_with_db().await;
+    let mock_server = MockServer::start().await;
+
+    Mock::given(method("GET"))
+        .and(path("/control/status"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(status_body(true)))
+        .mount(&mock_server)
+        .await;
+    // The broken schedule must not touch AdGuard, since its state was never saved.
+    Mock::given(method("POST"))
+        .and(path("/control/protection"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(0)
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/control/blocked_services/get"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "ids": [],
+            "schedule": {}
+        })))
+        .mount(&mock_server)
+        .await;
+    Mock::given(method("PUT"))
+        .and(path("/control/blocked_services/update"))
+        .respond_with(ResponseTemplate::new(200))
+        .expect(1)
+        .mount(&mock_server)
+        .await;
+
+    let mut ids = Vec::new();
+    for (action, blocked_services) in [
+        (adguard_schedule::ScheduleAction::DisableProtection, vec![]),
+        (
+            adguard_schedule::ScheduleAction::BlockServices,
+            vec!["tiktok".to_string()],
+        ),
+    ] {
+        let input = adguard_schedule::ScheduleInput {
+            name: "Evening".to_string(),
+            client_name: None,
+            action,
+            blocked_services,
+            days: vec!["mon".to_string()],
+            start_time: "19:00".to_string(),
+            end_time: "21:00".to_string(),
+            enabled: true,
+        }
+        .validate()
+        .unwrap();
+        let schedule = adguard_schedule::create_schedule(&state.db, &input)
+            .await
+            .unwrap();
+        ids.push(schedule.id);
+    }
+    sqlx::query(&format!(
+        r#"
+        CREATE TRIGGER fail_schedule_update BEFORE UPDATE ON adguard_schedules
+        WHEN NEW.id = {}
+        BEGIN SELECT RAISE(ABORT, 'disk full'); END
+        "#,
+        ids[0]
+    ))
+    .execute(&state.db)
+    .await
+    .unwrap();
+
+    let service = AdguardService::new(&mock_server.uri(), "test", "test", false).unwrap();
+    let inside = NaiveDate::from_ymd_opt(2024, 1, 15)
+        .unwrap()
+        .and_hms_opt(19, 30, 0)
+        .unwrap();
+    adguard_schedule::run_due_schedules(&state.db, &service, inside)
+        .await
+        .unwrap();
+
+    let broken = adguard_schedule::get_schedule(&state.db, ids[0])
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(!broken.active);
+    let started = adguard_schedule::get_schedule(&state.db, ids[1])
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(started.active);
+}
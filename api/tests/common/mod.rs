@@ -4,12 +4,13 @@ use axum::http::{Method, Request, StatusCode};
 use openhome_api::AppState;
 use openhome_api::auth::{ApiKey, auth_middleware};
 use openhome_api::routes::{
-    adguard::router as adguard_router, docker::router as docker_router,
-    facts::router as facts_router, feeds::router as feeds_router, health::router as health_router,
-    ir::router as ir_router, timeline::router as timeline_router,
+    adguard::router as adguard_router, adguard_schedules::router as adguard_schedules_router,
+    docker::router as docker_router, facts::router as facts_router, feeds::router as feeds_router,
+    health::router as health_router, ir::router as ir_router, timeline::router as timeline_router,
 };
 use openhome_api::services::adguard::AdguardService;
 use openhome_api::services::docker::DockerService;
+use openhome_api::services::feed::FeedFetcher;
 use openhome_api::services::ir::IrService;
 use sqlx::SqlitePool;
 use tower::ServiceExt;
@@ -39,6 +40,7 @@ pub fn create_mock_state_with_adguard(service: AdguardService) -> AppState {
         docker_cache: std::sync::Arc::new(tokio::sync::Mutex::new(
             openhome_api::DockerCache::default(),
         )),
+        feed_fetcher: FeedFetcher::new().unwrap(),
     }
 }
 
@@ -53,6 +55,7 @@ pub fn create_mock_state_with_ir(service: IrService) -> AppState {
         docker_cache: std::sync::Arc::new(tokio::sync::Mutex::new(
             openhome_api::DockerCache::default(),
         )),
+        feed_fetcher: FeedFetcher::new().unwrap(),
     }
 }
 
@@ -77,10 +80,12 @@ pub async fn test_app_with_db_and_adguard(adguard_enabled: Option<bool>) -> (Rou
         docker_cache: std::sync::Arc::new(tokio::sync::Mutex::new(
             openhome_api::DockerCache::default(),
         )),
+        feed_fetcher: FeedFetcher::new().unwrap(),
     };
 
     let app = health_router()
         .merge(adguard_router())
+        .merge(adguard_schedules_router())
         .merge(facts_router())
         .merge(feeds_router())
         .merge(ir_router())
@@ -123,10 +128,12 @@ pub async fn test_app_with_docker_and_adguard(adguard_enabled: Option<bool>) ->
         docker_cache: std::sync::Arc::new(tokio::sync::Mutex::new(
             openhome_api::DockerCache::default(),
         )),
+        feed_fetcher: FeedFetcher::new().unwrap(),
     };
 
     let app = health_router()
         .merge(adguard_router())
+        .merge(adguard_schedules_router())
         .merge(docker_router())
         .merge(facts_router())
         .merge(feeds_router())
@@ -193,3 +200,36 @@ pub async fn send_request_with_method(
 
     (status, json)
 }
+
+/// Sends a request with a raw (non-JSON) body and returns the raw response body.
+#[allow(dead_code)]
+pub async fn send_raw_request(
+    app: Router,
+    uri: &str,
+    method: Method,
+    content_type: &str,
+    body: String,
+    authorization: Option<&str>,
+) -> (StatusCode, http::HeaderMap, String) {
+    let mut builder = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header(http::header::CONTENT_TYPE, content_type);
+
+    if let Some(auth) = authorization {
+        builder = builder.header(http::header::AUTHORIZATION, format!("Bearer {auth}"));
+    }
+
+    let response = app
+        .oneshot(builder.body(Body::from(body)).unwrap())
+        .await
+        .unwrap();
+
+    let status = response.status();
+    let headers = response.headers().clone();
+    let body = axum::body::to_bytes(response.into_body(), 1024 * 100)
+        .await
+        .unwrap();
+
+    (status, headers, String::from_utf8_lossy(&body).into_owned())
+}
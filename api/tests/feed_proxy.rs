@@ -0,0 +1,32 @@
+use openhome_api::services::feed;
+use openhome_api::services::ssrf::NON_GLOBAL_ADDRESS_ERROR;
+use wiremock::MockServer;
+
+// Runs in its own test binary: the proxy variables it sets would otherwise leak
+// into clients built by other tests.
+#[tokio::test]
+async fn test_should_ignore_system_proxy_for_feed_fetches() {
+    let proxy = MockServer::start().await;
+    // SAFETY: this is the only test in this binary, nothing reads the environment
+    // concurrently.
+    unsafe {
+        std::env::remove_var("NO_PROXY");
+        std::env::remove_var("no_proxy");
+        std::env::set_var("HTTPS_PROXY", proxy.uri());
+        std::env::set_var("ALL_PROXY", proxy.uri());
+    }
+
+    let client = feed::build_client().unwrap();
+    let error = client
+        .get("https://localhost/feed.xml")
+        .send()
+        .await
+        .unwrap_err();
+
+    // The request went through the global-only resolver, not to the proxy.
+    assert!(
+        format!("{:#}", anyhow::Error::from(error)).contains(NON_GLOBAL_ADDRESS_ERROR),
+        "request should be refused by the resolver"
+    );
+    assert!(proxy.received_requests().await.unwrap().is_empty());
+}
@@ -16,9 +16,15 @@ async fn test_should_return_error_for_invalid_feed_url() {
     .fetch_one(&state.db)
     .await
     .unwrap();
+    let fetcher = feed::FeedFetcher::new().unwrap();
 
-    let result =
-        feed::refresh_feed(&state.db, feed_id, "https://example.invalid/invalid.xml").await;
+    let result = feed::refresh_feed(
+        &state.db,
+        &fetcher,
+        feed_id,
+        "https://example.invalid/invalid.xml",
+    )
+    .await;
 
     assert_eq!(result.feed_id, feed_id);
     assert_eq!(result.items_inserted, 0);
@@ -39,8 +45,10 @@ async fn test_should_reject_http_feed_url() {
     .fetch_one(&state.db)
     .await
     .unwrap();
+    let fetcher = feed::FeedFetcher::new().unwrap();
 
-    let result = feed::refresh_feed(&state.db, feed_id, "http://example.com/feed.xml").await;
+    let result =
+        feed::refresh_feed(&state.db, &fetcher, feed_id, "http://example.com/feed.xml").await;
 
     assert_eq!(result.feed_id, feed_id);
     assert_eq!(result.items_inserted, 0);
@@ -61,8 +69,10 @@ async fn test_should_reject_private_host_feed_url() {
     .fetch_one(&state.db)
     .await
     .unwrap();
+    let fetcher = feed::FeedFetcher::new().unwrap();
 
-    let result = feed::refresh_feed(&state.db, feed_id, "https://127.0.0.1/feed.xml").await;
+    let result =
+        feed::refresh_feed(&state.db, &fetcher, feed_id, "https://127.0.0.1/feed.xml").await;
 
     assert_eq!(result.feed_id, feed_id);
     assert_eq!(result.items_inserted, 0);
@@ -72,3 +82,75 @@ async fn test_should_reject_private_host_feed_url() {
         Some("refusing to fetch non-global address".to_string())
     );
 }
+
+#[tokio::test]
+async fn test_should_report_feeds_left_when_refresh_budget_is_exhausted() {
+    let (_app, state) = test_app_with_db().await;
+
+    for url in ["https://example.com/a.xml", "https://example.com/b.xml"] {
+        sqlx::query!("INSERT INTO feeds (url) VALUES ($1)", url)
+            .execute(&state.db)
+            .await
+            .unwrap();
+    }
+
+    let options = feed::RefreshOptions {
+        budget: std::time::Duration::ZERO,
+        ..Default::default()
+    };
+    let results = feed::refresh_all_feeds_with(&state.db, &state.feed_fetcher, &options)
+        .await
+        .unwrap();
+
+    assert_eq!(results.len(), 2);
+    for result in results {
+        assert_eq!(
+            result.error,
+            Some("Refresh time budget exhausted".to_string())
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_should_back_off_after_consecutive_errors() {
+    let (_app, state) = test_app_with_db().await;
+
+    let feed_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO feeds (url) VALUES ('http://example.com/feed.xml')
+        RETURNING id
+        "#
+    )
+    .fetch_one(&state.db)
+    .await
+    .unwrap();
+    let fetcher = feed::FeedFetcher::new().unwrap();
+
+    let mut delays = Vec::new();
+    for _ in 0..2 {
+        feed::refresh_feed(&state.db, &fetcher, feed_id, "http://example.com/feed.xml").await;
+        let row = sqlx::query!(
+            r#"
+            SELECT
+                error_count,
+                last_error,
+                CAST(ROUND((julianday(next_fetch_at) - julianday(last_fetched_at)) * 86400) AS INTEGER)
+                    as "delay_seconds!: i64"
+            FROM feeds WHERE id = $1
+            "#,
+            feed_id
+        )
+        .fetch_one(&state.db)
+        .await
+        .unwrap();
+        assert_eq!(row.last_error.as_deref(), Some("URL must use HTTPS scheme"));
+        delays.push((row.error_count, row.delay_seconds));
+    }
+
+    assert_eq!(delays, vec![(1, 15 * 60), (2, 30 * 60)]);
+
+    let due = feed::refresh_due_feeds(&state.db, &state.feed_fetcher)
+        .await
+        .unwrap();
+    assert!(due.is_empty());
+}
@@ -195,3 +195,584 @@ async fn test_should_get_feeds() {
     assert!(urls.contains(&"https://example.com/feed1.xml"));
     assert!(urls.contains(&"https://example.com/feed2.xml"));
 }
+
+const OPML_FIXTURE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<opml version="2.0">
+  <head><title>Reader export</title></head>
+  <body>
+    <outline text="Existing" xmlUrl="https://example.com/existing.xml"/>
+    <outline text="Tech">
+      <outline text="Rust Blog" xmlUrl="https://blog.rust-lang.org/feed.xml"/>
+      <outline text="Router admin" xmlUrl="https://192.168.1.1/feed.xml"/>
+    </outline>
+    <outline text="Family">
+      <outline text="Family blog" xmlUrl="https://family.example.org/rss"/>
+      <outline text="Rust Blog again" xmlUrl="https://blog.rust-lang.org/feed.xml"/>
+    </outline>
+  </body>
+</opml>"#;
+
+#[tokio::test]
+async fn test_should_import_opml_and_report_duplicates() {
+    let (app, state) = test_app_with_db().await;
+
+    sqlx::query("INSERT INTO feeds (url) VALUES ('https://example.com/existing.xml')")
+        .execute(&state.db)
+        .await
+        .unwrap();
+
+    let (status, _headers, body) = common::send_raw_request(
+        app,
+        "/api/feeds/import",
+        Method::POST,
+        "text/x-opml",
+        OPML_FIXTURE.to_string(),
+        Some("test-api-key"),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    let report: serde_json::Value = serde_json::from_str(&body).unwrap();
+    let imported = report["imported"].as_array().unwrap();
+    assert_eq!(imported.len(), 2);
+    assert_eq!(imported[0]["url"], "https://blog.rust-lang.org/feed.xml");
+    assert_eq!(imported[0]["title"], "Rust Blog");
+    assert_eq!(imported[0]["category"], "Tech");
+    assert_eq!(imported[1]["url"], "https://family.example.org/rss");
+    assert_eq!(imported[1]["category"], "Family");
+    assert_eq!(
+        report["duplicates"],
+        json!([
+            "https://example.com/existing.xml",
+            "https://blog.rust-lang.org/feed.xml"
+        ])
+    );
+    assert_eq!(report["invalid"][0]["url"], "https://192.168.1.1/feed.xml");
+    assert_eq!(
+        report["invalid"][0]["error"],
+        "URL host is a private or reserved IP address"
+    );
+}
+
+#[tokio::test]
+async fn test_should_reject_imported_feeds_with_overlong_category() {
+    let (app, _state) = test_app_with_db().await;
+    let document = format!(
+        r#"<opml version="2.0"><body>
+  <outline text="{}">
+    <outline text="Feed" xmlUrl="https://example.com/feed.xml"/>
+  </outline>
+</body></opml>"#,
+        "x".repeat(101)
+    );
+
+    let (status, _headers, body) = common::send_raw_request(
+        app,
+        "/api/feeds/import",
+        Method::POST,
+        "text/x-opml",
+        document,
+        Some("test-api-key"),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    let report: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(report["imported"], json!([]));
+    assert_eq!(report["invalid"][0]["url"], "https://example.com/feed.xml");
+    assert_eq!(
+        report["invalid"][0]["error"],
+        "Category must be at most 100 characters"
+    );
+}
+
+#[tokio::test]
+async fn test_should_return_400_for_invalid_opml() {
+    let app = common::test_app().await;
+
+    let (status, _headers, _body) = common::send_raw_request(
+        app,
+        "/api/feeds/import",
+        Method::POST,
+        "text/x-opml",
+        "<rss><channel/></rss>".to_string(),
+        Some("test-api-key"),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_should_export_feeds_as_opml() {
+    let (app, state) = test_app_with_db().await;
+
+    sqlx::query(
+        "INSERT INTO feeds (url, title, category) VALUES
+            ('https://example.com/a.xml', 'Feed A', 'Tech'),
+            ('https://example.com/b.xml', NULL, NULL)",
+    )
+    .execute(&state.db)
+    .await
+    .unwrap();
+
+    let (status, headers, body) = common::send_raw_request(
+        app,
+        "/api/feeds/export",
+        Method::GET,
+        "text/plain",
+        String::new(),
+        Some("test-api-key"),
+    )
+    .await;
+
+    assert_eq!(status, StatusCode::OK);
+    assert!(
+        headers[http::header::CONTENT_TYPE]
+            .to_str()
+            .unwrap()
+            .starts_with("text/x-opml")
+    );
+    let feeds = openhome_api::services::opml::parse_opml(&body).unwrap();
+    assert_eq!(feeds.len(), 2);
+    assert_eq!(feeds[0].url, "https://example.com/b.xml");
+    assert_eq!(feeds[1].url, "https://example.com/a.xml");
+    assert_eq!(feeds[1].title.as_deref(), Some("Feed A"));
+    assert_eq!(feeds[1].category.as_deref(), Some("Tech"));
+}
+
+#[tokio::test]
+async fn test_should_set_feed_category_and_tags() {
+    let (app, _state) = test_app_with_db().await;
+
+    let (_status, feed) = send_request_with_method(
+        app.clone(),
+        "/api/feeds",
+        Method::POST,
+        Some(json!({ "url": "https://example.com/feed.xml" })),
+        Some("test-api-key"),
+    )
+    .await;
+    let id = feed["id"].as_i64().unwrap();
+    assert_eq!(feed["tags"], json!([]));
+
+    let (status, response) = send_request_with_method(
+        app.clone(),
+        &format!("/api/feeds/{id}/category"),
+        Method::PUT,
+        Some(json!({ "category": " Tech " })),
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(response["category"], "Tech");
+
+    let (status, response) = send_request_with_method(
+        app.clone(),
+        &format!("/api/feeds/{id}/tags"),
+        Method::PUT,
+        Some(json!({ "tags": ["Rust", "news", "rust"] })),
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(response["tags"], json!(["news", "rust"]));
+
+    let (_status, feeds) = send_request_with_method(
+        app.clone(),
+        "/api/feeds",
+        Method::GET,
+        None,
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(feeds[0]["category"], "Tech");
+    assert_eq!(feeds[0]["tags"], json!(["news", "rust"]));
+
+    let (status, tags) = send_request_with_method(
+        app.clone(),
+        "/api/feeds/tags",
+        Method::GET,
+        None,
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        tags,
+        json!([{ "name": "news", "feeds": 1 }, { "name": "rust", "feeds": 1 }])
+    );
+
+    let (status, categories) = send_request_with_method(
+        app.clone(),
+        "/api/feeds/categories",
+        Method::GET,
+        None,
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(categories, json!([{ "name": "Tech", "feeds": 1 }]));
+
+    let (status, response) = send_request_with_method(
+        app,
+        &format!("/api/feeds/{id}/category"),
+        Method::PUT,
+        Some(json!({ "category": null })),
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(response["category"], serde_json::Value::Null);
+}
+
+#[tokio::test]
+async fn test_should_reject_invalid_tags_and_unknown_feed() {
+    let (app, _state) = test_app_with_db().await;
+
+    let (status, _response) = send_request_with_method(
+        app.clone(),
+        "/api/feeds/9999/tags",
+        Method::PUT,
+        Some(json!({ "tags": ["news"] })),
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    let (status, _response) = send_request_with_method(
+        app,
+        "/api/feeds/9999/tags",
+        Method::PUT,
+        Some(json!({ "tags": [" "] })),
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_should_include_item_counts_and_fetch_status_in_feeds() {
+    let (app, state) = test_app_with_db().await;
+
+    let feed_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO feeds (url, last_fetched_at, last_error)
+        VALUES ('https://example.com/feed.xml', '2026-01-02 03:04:05', 'HTTP error: 500')
+        RETURNING id
+        "#
+    )
+    .fetch_one(&state.db)
+    .await
+    .unwrap();
+    for i in 0..3 {
+        let guid = format!("guid-{i}");
+        sqlx::query!(
+            r#"
+            INSERT INTO feed_items (feed_id, title, link, guid, read_at)
+            VALUES ($1, 'Item', 'https://example.com/item', $2,
+                CASE WHEN $3 = 0 THEN datetime('now') END)
+            "#,
+            feed_id,
+            guid,
+            i
+        )
+        .execute(&state.db)
+        .await
+        .unwrap();
+    }
+
+    let (status, response) =
+        send_request_with_method(app, "/api/feeds", Method::GET, None, Some("test-api-key")).await;
+
+    assert_eq!(status, StatusCode::OK);
+    let feed = &response[0];
+    assert_eq!(feed["item_count"], 3);
+    assert_eq!(feed["unread_count"], 2);
+    assert_eq!(feed["last_fetched_at"], "2026-01-02 03:04:05");
+    assert_eq!(feed["last_error"], "HTTP error: 500");
+}
+
+#[tokio::test]
+async fn test_should_get_and_patch_feed() {
+    let (app, _state) = test_app_with_db().await;
+
+    let (_status, feed) = send_request_with_method(
+        app.clone(),
+        "/api/feeds",
+        Method::POST,
+        Some(json!({ "url": "https://example.com/feed.xml" })),
+        Some("test-api-key"),
+    )
+    .await;
+    let id = feed["id"].as_i64().unwrap();
+    assert_eq!(feed["enabled"], true);
+
+    let (status, response) = send_request_with_method(
+        app.clone(),
+        &format!("/api/feeds/{id}"),
+        Method::PATCH,
+        Some(json!({
+            "url": "https://example.com/moved.xml",
+            "custom_title": "My feed",
+            "enabled": false,
+            "refresh_interval_minutes": 120
+        })),
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(response["url"], "https://example.com/moved.xml");
+    assert_eq!(response["title"], "My feed");
+    assert_eq!(response["custom_title"], "My feed");
+    assert_eq!(response["enabled"], false);
+    assert_eq!(response["refresh_interval_minutes"], 120);
+
+    let (status, response) = send_request_with_method(
+        app.clone(),
+        &format!("/api/feeds/{id}"),
+        Method::PATCH,
+        Some(json!({ "custom_title": null })),
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(response["custom_title"], serde_json::Value::Null);
+    assert_eq!(response["refresh_interval_minutes"], 120);
+
+    let (status, response) = send_request_with_method(
+        app.clone(),
+        &format!("/api/feeds/{id}"),
+        Method::GET,
+        None,
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(response["id"], id);
+    assert_eq!(response["enabled"], false);
+
+    let (status, _response) = send_request_with_method(
+        app.clone(),
+        &format!("/api/feeds/{id}"),
+        Method::PATCH,
+        Some(json!({ "refresh_interval_minutes": 1 })),
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _response) = send_request_with_method(
+        app,
+        "/api/feeds/9999",
+        Method::GET,
+        None,
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_should_reschedule_feed_when_interval_changes_or_it_is_reenabled() {
+    let (app, state) = test_app_with_db().await;
+
+    let feed_id = sqlx::query_scalar!(
+        r#"
+        INSERT INTO feeds (url, last_fetched_at, next_fetch_at)
+        VALUES ('https://example.com/feed.xml', '2026-01-01 00:00:00', '2026-01-02 00:00:00')
+        RETURNING id
+        "#
+    )
+    .fetch_one(&state.db)
+    .await
+    .unwrap();
+    let uri = format!("/api/feeds/{feed_id}");
+
+    let (status, response) = send_request_with_method(
+        app.clone(),
+        &uri,
+        Method::PATCH,
+        Some(json!({ "refresh_interval_minutes": 120 })),
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(response["next_fetch_at"], "2026-01-01 02:00:00");
+
+    // Unrelated changes keep the schedule.
+    let (_status, response) = send_request_with_method(
+        app.clone(),
+        &uri,
+        Method::PATCH,
+        Some(json!({ "custom_title": "Renamed", "refresh_interval_minutes": 120 })),
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(response["next_fetch_at"], "2026-01-01 02:00:00");
+
+    let (_status, response) = send_request_with_method(
+        app.clone(),
+        &uri,
+        Method::PATCH,
+        Some(json!({ "refresh_interval_minutes": null })),
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(response["next_fetch_at"], serde_json::Value::Null);
+
+    sqlx::query!(
+        "UPDATE feeds SET enabled = FALSE, next_fetch_at = '2026-02-01 00:00:00' WHERE id = $1",
+        feed_id
+    )
+    .execute(&state.db)
+    .await
+    .unwrap();
+    let (_status, response) = send_request_with_method(
+        app.clone(),
+        &uri,
+        Method::PATCH,
+        Some(json!({ "enabled": true })),
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(response["enabled"], true);
+    assert_eq!(response["next_fetch_at"], serde_json::Value::Null);
+
+    // A failing feed keeps its backoff.
+    sqlx::query!(
+        "UPDATE feeds SET error_count = 3, next_fetch_at = '2026-01-01 04:00:00' WHERE id = $1",
+        feed_id
+    )
+    .execute(&state.db)
+    .await
+    .unwrap();
+    let (_status, response) = send_request_with_method(
+        app,
+        &uri,
+        Method::PATCH,
+        Some(json!({ "refresh_interval_minutes": 30 })),
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(response["next_fetch_at"], "2026-01-01 04:00:00");
+}
+
+#[tokio::test]
+async fn test_should_return_409_when_patching_to_existing_url() {
+    let (app, _state) = test_app_with_db().await;
+
+    let mut ids = Vec::new();
+    for url in ["https://example.com/a.xml", "https://example.com/b.xml"] {
+        let (_status, feed) = send_request_with_method(
+            app.clone(),
+            "/api/feeds",
+            Method::POST,
+            Some(json!({ "url": url })),
+            Some("test-api-key"),
+        )
+        .await;
+        ids.push(feed["id"].as_i64().unwrap());
+    }
+
+    let (status, _response) = send_request_with_method(
+        app,
+        &format!("/api/feeds/{}", ids[1]),
+        Method::PATCH,
+        Some(json!({ "url": "https://example.com/a.xml" })),
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn test_should_refresh_single_feed_unless_disabled() {
+    let (app, state) = test_app_with_db().await;
+
+    let feed_id = sqlx::query_scalar!(
+        "INSERT INTO feeds (url) VALUES ('https://127.0.0.1/feed.xml') RETURNING id"
+    )
+    .fetch_one(&state.db)
+    .await
+    .unwrap();
+
+    let (status, response) = send_request_with_method(
+        app.clone(),
+        &format!("/api/feeds/{feed_id}/refresh"),
+        Method::POST,
+        None,
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(response["feed_id"], feed_id);
+    assert_eq!(response["items_inserted"], 0);
+    assert_eq!(response["error"], "refusing to fetch non-global address");
+
+    sqlx::query!("UPDATE feeds SET enabled = FALSE WHERE id = $1", feed_id)
+        .execute(&state.db)
+        .await
+        .unwrap();
+
+    let (status, _response) = send_request_with_method(
+        app,
+        &format!("/api/feeds/{feed_id}/refresh"),
+        Method::POST,
+        None,
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn test_should_validate_url_before_feed_discovery() {
+    let (app, _state) = test_app_with_db().await;
+
+    let (status, _response) = send_request_with_method(
+        app.clone(),
+        "/api/feeds/discover",
+        Method::POST,
+        Some(json!({ "url": "http://example.com/" })),
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let (status, _response) = send_request_with_method(
+        app,
+        "/api/feeds",
+        Method::POST,
+        Some(json!({ "url": "https://192.168.1.1/", "discover": true })),
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_should_reject_reserved_ip_literals() {
+    let (app, _state) = test_app_with_db().await;
+
+    for url in [
+        "https://100.64.0.1/feed.xml",
+        "https://198.18.0.1/feed.xml",
+        "https://[::1]/feed.xml",
+        "https://[::ffff:192.168.1.1]/feed.xml",
+    ] {
+        let (status, response) = send_request_with_method(
+            app.clone(),
+            "/api/feeds",
+            Method::POST,
+            Some(json!({ "url": url })),
+            Some("test-api-key"),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{url}");
+        assert_eq!(
+            response["error"],
+            "URL host is a private or reserved IP address"
+        );
+    }
+}
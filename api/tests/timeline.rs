@@ -367,8 +367,624 @@ async fn test_should_return_404_when_marking_unknown_item() {
     .await;
 
     assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(response["error"], "Item with id 99999 not found");
+}
+
+#[tokio::test]
+async fn test_should_filter_timeline_by_category_and_tag() {
+    let (app, state) = test_app_with_db().await;
+
+    let tech_feed = sqlx::query_scalar!(
+        r#"
+        INSERT INTO feeds (url, title, category) VALUES ('https://example.com/tech.xml', 'Tech', 'Tech')
+        RETURNING id
+        "#
+    )
+    .fetch_one(&state.db)
+    .await
+    .unwrap();
+    let other_feed = sqlx::query_scalar!(
+        r#"
+        INSERT INTO feeds (url, title) VALUES ('https://example.com/other.xml', 'Other')
+        RETURNING id
+        "#
+    )
+    .fetch_one(&state.db)
+    .await
+    .unwrap();
+    sqlx::query!(
+        "INSERT INTO feed_tags (feed_id, tag) VALUES ($1, 'rust')",
+        other_feed
+    )
+    .execute(&state.db)
+    .await
+    .unwrap();
+
+    for (feed_id, name) in [(tech_feed, "tech"), (other_feed, "other")] {
+        let link = format!("https://example.com/{name}");
+        sqlx::query!(
+            r#"
+            INSERT INTO feed_items (feed_id, title, link, guid, pub_date)
+            VALUES ($1, $2, $3, $2, datetime('now'))
+            "#,
+            feed_id,
+            name,
+            link
+        )
+        .execute(&state.db)
+        .await
+        .unwrap();
+    }
+
+    let titles = |response: &Value| -> Vec<String> {
+        response
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item["title"].as_str().unwrap().to_string())
+            .collect()
+    };
+
+    let (status, response) = send_request_with_method(
+        app.clone(),
+        "/api/timeline?category=Tech",
+        Method::GET,
+        None,
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(titles(&response), vec!["tech"]);
+
+    let (status, response) = send_request_with_method(
+        app.clone(),
+        "/api/timeline?view=compact&tag=Rust",
+        Method::GET,
+        None,
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(titles(&response), vec!["other"]);
+
+    let (status, response) = send_request_with_method(
+        app,
+        "/api/timeline?category=Tech&tag=rust&unread=true",
+        Method::GET,
+        None,
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(titles(&response).is_empty());
+}
+
+#[tokio::test]
+async fn test_should_filter_timeline_by_feed_ids() {
+    let (app, state) = test_app_with_db().await;
+
+    let mut feed_ids = Vec::new();
+    for name in ["a", "b", "c"] {
+        let url = format!("https://example.com/{name}.xml");
+        let feed_id = sqlx::query_scalar!("INSERT INTO feeds (url) VALUES ($1) RETURNING id", url)
+            .fetch_one(&state.db)
+            .await
+            .unwrap();
+        let link = format!("https://example.com/{name}");
+        sqlx::query!(
+            r#"
+            INSERT INTO feed_items (feed_id, title, link, guid, pub_date)
+            VALUES ($1, $2, $3, $2, datetime('now'))
+            "#,
+            feed_id,
+            name,
+            link
+        )
+        .execute(&state.db)
+        .await
+        .unwrap();
+        feed_ids.push(feed_id);
+    }
+
+    let (status, response) = send_request_with_method(
+        app.clone(),
+        &format!("/api/timeline?feed_id={}", feed_ids[1]),
+        Method::GET,
+        None,
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let items = response.as_array().unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["feed_id"], json!(feed_ids[1]));
+
+    let (status, response) = send_request_with_method(
+        app.clone(),
+        &format!("/api/timeline?feed_id={},{}", feed_ids[0], feed_ids[2]),
+        Method::GET,
+        None,
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let mut returned: Vec<i64> = response
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| item["feed_id"].as_i64().unwrap())
+        .collect();
+    returned.sort_unstable();
+    assert_eq!(returned, vec![feed_ids[0], feed_ids[2]]);
+
+    let (status, _response) = send_request_with_method(
+        app.clone(),
+        "/api/timeline?feed_id=abc",
+        Method::GET,
+        None,
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+
+    let repeated = vec![feed_ids[0].to_string(); 500].join(",");
+    let (status, response) = send_request_with_method(
+        app.clone(),
+        &format!("/api/timeline?feed_id={repeated}"),
+        Method::GET,
+        None,
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(response.as_array().unwrap().len(), 1);
+
+    let too_many: Vec<String> = (1..=101).map(|id| id.to_string()).collect();
+    let (status, response) = send_request_with_method(
+        app,
+        &format!("/api/timeline?feed_id={}", too_many.join(",")),
+        Method::GET,
+        None,
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(response["error"], "At most 100 feed ids can be given");
+}
+
+#[tokio::test]
+async fn test_should_search_timeline_with_ranking_snippets_and_cursor() {
+    let (app, state) = test_app_with_db().await;
+
+    let feed_id = sqlx::query_scalar!(
+        "INSERT INTO feeds (url, title) VALUES ('https://example.com/feed.xml', 'Storage') RETURNING id"
+    )
+    .fetch_one(&state.db)
+    .await
+    .unwrap();
+
+    let items = [
+        (
+            "zfs-title",
+            "ZFS snapshots explained",
+            "A tour of ZFS send and receive.",
+        ),
+        (
+            "zfs-body",
+            "Backup strategies",
+            "Why I moved my backups to ZFS.",
+        ),
+        (
+            "other",
+            "Btrfs balance",
+            "Nothing about the other filesystem.",
+        ),
+    ];
+    for (guid, title, description) in items {
+        sqlx::query!(
+            r#"
+            INSERT INTO feed_items (feed_id, title, description, link, guid)
+            VALUES ($1, $2, $3, 'https://example.com/item', $4)
+            "#,
+            feed_id,
+            title,
+            description,
+            guid
+        )
+        .execute(&state.db)
+        .await
+        .unwrap();
+    }
+
+    let (status, response) = send_request_with_method(
+        app.clone(),
+        "/api/timeline/search?q=zfs",
+        Method::GET,
+        None,
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let results = response.as_array().unwrap();
+    assert_eq!(results.len(), 2);
+    assert_eq!(results[0]["title"], "ZFS snapshots explained");
     assert_eq!(
-        response["error"],
-        "Item with id 99999 not found or already read"
+        results[0]["title_highlight"],
+        "<mark>ZFS</mark> snapshots explained"
+    );
+    assert_eq!(results[0]["feed_title"], "Storage");
+    assert!(
+        results[1]["snippet"]
+            .as_str()
+            .unwrap()
+            .contains("<mark>ZFS</mark>")
     );
+
+    let (status, response) = send_request_with_method(
+        app.clone(),
+        "/api/timeline/search?q=zfs&limit=1",
+        Method::GET,
+        None,
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let cursor = response[0]["cursor"].as_str().unwrap().to_string();
+
+    // Items indexed after the first page must not shift or join later pages, and
+    // removing the last returned item must not invalidate the cursor.
+    sqlx::query!("DELETE FROM feed_items WHERE guid = 'zfs-title'")
+        .execute(&state.db)
+        .await
+        .unwrap();
+    sqlx::query!(
+        r#"
+        INSERT INTO feed_items (feed_id, title, description, link, guid)
+        VALUES ($1, 'ZFS ZFS ZFS', 'ZFS', 'https://example.com/new', 'zfs-new')
+        "#,
+        feed_id
+    )
+    .execute(&state.db)
+    .await
+    .unwrap();
+
+    let (status, response) = send_request_with_method(
+        app.clone(),
+        &format!("/api/timeline/search?q=zfs&limit=1&cursor={cursor}"),
+        Method::GET,
+        None,
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let page = response.as_array().unwrap();
+    assert_eq!(page.len(), 1);
+    assert_eq!(page[0]["title"], "Backup strategies");
+
+    let cursor = page[0]["cursor"].as_str().unwrap();
+    let (status, response) = send_request_with_method(
+        app.clone(),
+        &format!("/api/timeline/search?q=zfs&cursor={cursor}"),
+        Method::GET,
+        None,
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(response.as_array().unwrap().len(), 0);
+
+    let (status, response) = send_request_with_method(
+        app.clone(),
+        "/api/timeline/search?q=zfs&cursor=garbage",
+        Method::GET,
+        None,
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(response["error"], "Invalid search cursor");
+
+    let (status, _response) = send_request_with_method(
+        app.clone(),
+        "/api/timeline/search?q=%22unbalanced%20AND",
+        Method::GET,
+        None,
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+
+    let (status, _response) = send_request_with_method(
+        app,
+        "/api/timeline/search?q=%20",
+        Method::GET,
+        None,
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_should_escape_html_in_search_highlights() {
+    let (app, state) = test_app_with_db().await;
+
+    let feed_id = sqlx::query_scalar!(
+        "INSERT INTO feeds (url) VALUES ('https://example.com/feed.xml') RETURNING id"
+    )
+    .fetch_one(&state.db)
+    .await
+    .unwrap();
+    sqlx::query!(
+        r#"
+        INSERT INTO feed_items (feed_id, title, description, link, guid)
+        VALUES ($1, '<img src=x onerror=alert(1)> zfs', 'zfs & <script>alert("x")</script>',
+                'https://example.com/item', 'xss')
+        "#,
+        feed_id
+    )
+    .execute(&state.db)
+    .await
+    .unwrap();
+
+    let (status, response) = send_request_with_method(
+        app,
+        "/api/timeline/search?q=zfs",
+        Method::GET,
+        None,
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(
+        response[0]["title_highlight"],
+        "&lt;img src=x onerror=alert(1)&gt; <mark>zfs</mark>"
+    );
+    assert_eq!(
+        response[0]["snippet"],
+        "<mark>zfs</mark> &amp; &lt;script&gt;alert(&quot;x&quot;)&lt;/script&gt;"
+    );
+}
+
+#[tokio::test]
+async fn test_should_star_unstar_and_filter_starred_items() {
+    let (app, state) = test_app_with_db().await;
+
+    let feed_id = sqlx::query_scalar!(
+        "INSERT INTO feeds (url) VALUES ('https://example.com/feed.xml') RETURNING id"
+    )
+    .fetch_one(&state.db)
+    .await
+    .unwrap();
+
+    let mut item_ids = Vec::new();
+    for guid in ["keep", "skip"] {
+        let item_id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO feed_items (feed_id, title, link, guid, pub_date)
+            VALUES ($1, $2, 'https://example.com/item', $2, datetime('now'))
+            RETURNING id
+            "#,
+            feed_id,
+            guid
+        )
+        .fetch_one(&state.db)
+        .await
+        .unwrap()
+        .expect("id should be returned");
+        item_ids.push(item_id);
+    }
+
+    for _ in 0..2 {
+        let (status, _response) = send_request_with_method(
+            app.clone(),
+            &format!("/api/items/{}/star", item_ids[0]),
+            Method::POST,
+            None,
+            Some("test-api-key"),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+    }
+
+    let (status, response) = send_request_with_method(
+        app.clone(),
+        "/api/timeline?starred=true",
+        Method::GET,
+        None,
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let items = response.as_array().unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["id"], json!(item_ids[0]));
+    assert!(items[0]["starred_at"].is_string());
+
+    let (status, _response) = send_request_with_method(
+        app.clone(),
+        &format!("/api/items/{}/star", item_ids[0]),
+        Method::DELETE,
+        None,
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NO_CONTENT);
+
+    let (_status, response) = send_request_with_method(
+        app.clone(),
+        "/api/timeline?starred=true",
+        Method::GET,
+        None,
+        Some("test-api-key"),
+    )
+    .await;
+    assert!(response.as_array().unwrap().is_empty());
+
+    let (status, _response) = send_request_with_method(
+        app,
+        "/api/items/9999/star",
+        Method::POST,
+        None,
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+async fn insert_read_state_fixture(state: &openhome_api::AppState) -> (i64, i64, Vec<i64>) {
+    let tech_feed = sqlx::query_scalar!(
+        "INSERT INTO feeds (url, category) VALUES ('https://example.com/tech.xml', 'Tech') RETURNING id"
+    )
+    .fetch_one(&state.db)
+    .await
+    .unwrap();
+    let other_feed = sqlx::query_scalar!(
+        "INSERT INTO feeds (url) VALUES ('https://example.com/other.xml') RETURNING id"
+    )
+    .fetch_one(&state.db)
+    .await
+    .unwrap();
+
+    let mut item_ids = Vec::new();
+    for (feed_id, guid, pub_date) in [
+        (tech_feed, "tech-old", "2026-01-01 00:00:00"),
+        (tech_feed, "tech-new", "2026-03-01 00:00:00"),
+        (other_feed, "other-old", "2026-01-01 00:00:00"),
+    ] {
+        let item_id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO feed_items (feed_id, title, link, guid, pub_date)
+            VALUES ($1, $2, 'https://example.com/item', $2, $3)
+            RETURNING id
+            "#,
+            feed_id,
+            guid,
+            pub_date
+        )
+        .fetch_one(&state.db)
+        .await
+        .unwrap()
+        .expect("id should be returned");
+        item_ids.push(item_id);
+    }
+    (tech_feed, other_feed, item_ids)
+}
+
+async fn unread_ids(state: &openhome_api::AppState) -> Vec<i64> {
+    sqlx::query_scalar!(r#"SELECT id as "id!" FROM feed_items WHERE read_at IS NULL ORDER BY id"#)
+        .fetch_all(&state.db)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_should_mark_read_idempotently_and_mark_unread() {
+    let (app, state) = test_app_with_db().await;
+    let (_tech_feed, _other_feed, item_ids) = insert_read_state_fixture(&state).await;
+    let uri = format!("/api/items/{}/read", item_ids[0]);
+
+    for _ in 0..2 {
+        let (status, _response) =
+            send_request_with_method(app.clone(), &uri, Method::POST, None, Some("test-api-key"))
+                .await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+    }
+    assert_eq!(unread_ids(&state).await, vec![item_ids[1], item_ids[2]]);
+
+    for _ in 0..2 {
+        let (status, _response) = send_request_with_method(
+            app.clone(),
+            &uri,
+            Method::DELETE,
+            None,
+            Some("test-api-key"),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+    }
+    assert_eq!(unread_ids(&state).await, item_ids);
+}
+
+#[tokio::test]
+async fn test_should_bulk_update_read_state() {
+    let (app, state) = test_app_with_db().await;
+    let (_tech_feed, _other_feed, item_ids) = insert_read_state_fixture(&state).await;
+
+    let (status, response) = send_request_with_method(
+        app.clone(),
+        "/api/items/read",
+        Method::POST,
+        Some(json!({ "ids": [item_ids[0], item_ids[1], 9999] })),
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(response, json!({ "updated": 2, "not_found": [9999] }));
+    assert_eq!(unread_ids(&state).await, vec![item_ids[2]]);
+
+    let (status, response) = send_request_with_method(
+        app.clone(),
+        "/api/items/read",
+        Method::POST,
+        Some(json!({ "ids": [item_ids[0], item_ids[1]] })),
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(response, json!({ "updated": 0, "not_found": [] }));
+
+    let (status, response) = send_request_with_method(
+        app,
+        "/api/items/read",
+        Method::POST,
+        Some(json!({ "ids": [item_ids[1]], "read": false })),
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(response["updated"], 1);
+    assert_eq!(unread_ids(&state).await, vec![item_ids[1], item_ids[2]]);
+}
+
+#[tokio::test]
+async fn test_should_mark_all_read_by_scope_and_cutoff() {
+    let (app, state) = test_app_with_db().await;
+    let (_tech_feed, other_feed, item_ids) = insert_read_state_fixture(&state).await;
+
+    let (status, response) = send_request_with_method(
+        app.clone(),
+        "/api/items/read-all",
+        Method::POST,
+        Some(json!({ "category": "Tech", "older_than": "2026-02-01T00:00:00Z" })),
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(response["updated"], 1);
+    assert_eq!(unread_ids(&state).await, vec![item_ids[1], item_ids[2]]);
+
+    let (status, response) = send_request_with_method(
+        app.clone(),
+        "/api/items/read-all",
+        Method::POST,
+        Some(json!({ "feed_id": other_feed })),
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(response["updated"], 1);
+    assert_eq!(unread_ids(&state).await, vec![item_ids[1]]);
+
+    let (status, response) = send_request_with_method(
+        app,
+        "/api/items/read-all",
+        Method::POST,
+        Some(json!({})),
+        Some("test-api-key"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(response["updated"], 1);
+    assert!(unread_ids(&state).await.is_empty());
 }
@@ -1,8 +1,13 @@
 import { callApi } from "./client";
 
+export type ProtectionState =
+  | { state: "enabled" }
+  | { state: "disabled_indefinitely" }
+  | { state: "paused"; until: string; remaining_seconds: number };
+
 export interface AdguardStatus {
   protection_enabled: boolean;
-  protection_disabled_duration: number;
+  protection: ProtectionState;
   protection_disabled_until: string | null;
   version: string;
   running: boolean;
@@ -12,33 +12,13 @@ interface AdguardControlProps {
   class?: string;
 }
 
-const formatTimeUntil = (isoString: string | null, durationMs: number): string => {
-  // Try using the ISO string first
-  if (isoString) {
-    const now = new Date();
-    const until = new Date(isoString);
-    const diffMs = until.getTime() - now.getTime();
-    
-    if (diffMs <= 0) return "Expired";
-    
-    const minutes = Math.floor(diffMs / 60000);
-    const hours = Math.floor(minutes / 60);
-    
-    if (hours > 0) return `${hours}h ${minutes % 60}m`;
-    return `${minutes}m`;
-  }
-  
-  // Fallback to duration if no ISO string
-  if (durationMs > 0) {
-    const minutes = Math.floor(durationMs / 60000);
-    const hours = Math.floor(minutes / 60);
-    
-    if (hours > 0) return `${hours}h ${minutes % 60}m`;
-    if (minutes > 0) return `${minutes}m`;
-    return "<1m";
-  }
-  
-  return "";
+const formatRemaining = (remainingSeconds: number): string => {
+  const minutes = Math.floor(remainingSeconds / 60);
+  const hours = Math.floor(minutes / 60);
+
+  if (hours > 0) return `${hours}h ${minutes % 60}m`;
+  if (minutes > 0) return `${minutes}m`;
+  return "<1m";
 };
 
 const PAUSE_OPTIONS = [5, 15, 30, 60];
@@ -119,10 +99,13 @@ const AdguardControl = (props: AdguardControlProps) => {
             <Show when={status()}>
               {(s) => {
                 // Compute status states from the unwrapped value
-                // Check both protection_disabled_until AND protection_disabled_duration
-                const enabled = () => s().protection_enabled;
-                const paused = () => !s().protection_enabled && (!!s().protection_disabled_until || s().protection_disabled_duration > 0);
-                const disabled = () => !s().protection_enabled && !s().protection_disabled_until && s().protection_disabled_duration === 0;
+                const enabled = () => s().protection.state === "enabled";
+                const paused = () => s().protection.state === "paused";
+                const disabled = () => s().protection.state === "disabled_indefinitely";
+                const remainingSeconds = () => {
+                  const protection = s().protection;
+                  return protection.state === "paused" ? protection.remaining_seconds : 0;
+                };
                 
                 return (
                 <div class="flex flex-col items-center">
@@ -171,7 +154,7 @@ const AdguardControl = (props: AdguardControlProps) => {
                       disabled() && "text-error"
                     )}>
                       {enabled() && "Protected"}
-                      {paused() && `Paused · ${formatTimeUntil(s().protection_disabled_until, s().protection_disabled_duration)}`}
+                      {paused() && `Paused · ${formatRemaining(remainingSeconds())}`}
                       {disabled() && "Unprotected"}
                     </p>
                     <p class="text-text-muted text-xs mt-1">